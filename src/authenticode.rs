@@ -0,0 +1,544 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use goblin::pe::certificate_table::AttributeCertificateType;
+use goblin::pe::PE;
+use memmap2::Mmap;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::fs::File;
+use std::path::Path;
+use windows::core::{HSTRING, PCWSTR};
+use windows::Win32::Foundation::HWND;
+use windows::Win32::Security::WinTrust::{
+    WinVerifyTrust, WINTRUST_ACTION_GENERIC_VERIFY_V2, WINTRUST_DATA, WINTRUST_FILE_INFO,
+    WTD_CACHE_ONLY_URL_RETRIEVAL, WTD_CHOICE_FILE, WTD_REVOCATION_CHECK_NONE, WTD_REVOKE_NONE,
+    WTD_STATEACTION_CLOSE, WTD_STATEACTION_VERIFY, WTD_UI_NONE,
+};
+
+/// PKCS#7 SignedData：1.2.840.113549.1.7.2
+const OID_SIGNED_DATA: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x02];
+/// 签名属性 messageDigest：1.2.840.113549.1.9.4
+const OID_MESSAGE_DIGEST: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x04];
+/// 签名属性 signingTime：1.2.840.113549.1.9.5
+const OID_SIGNING_TIME: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x05];
+/// 副署签名（Authenticode 时间戳）：1.2.840.113549.1.9.6
+const OID_COUNTER_SIGNATURE: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x06];
+/// RFC 3161 时间戳：1.3.6.1.4.1.311.3.3.1
+const OID_RFC3161_TIMESTAMP: &[u8] = &[0x2B, 0x06, 0x01, 0x04, 0x01, 0x82, 0x37, 0x03, 0x03, 0x01];
+/// SHA-1：1.3.14.3.2.26
+const OID_SHA1: &[u8] = &[0x2B, 0x0E, 0x03, 0x02, 0x1A];
+/// SHA-256：2.16.840.1.101.3.4.2.1
+const OID_SHA256: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+/// SHA-384：2.16.840.1.101.3.4.2.2
+const OID_SHA384: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02];
+/// SHA-512：2.16.840.1.101.3.4.2.3
+const OID_SHA512: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03];
+/// 通用名称（CN）：2.5.4.3
+const OID_COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+/// 组织名称（O）：2.5.4.10
+const OID_ORGANIZATION: &[u8] = &[0x55, 0x04, 0x0A];
+
+/// DER 标签
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_OID: u8 = 0x06;
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_SET: u8 = 0x31;
+const TAG_CONTEXT_0: u8 = 0xA0;
+const TAG_CONTEXT_1: u8 = 0xA1;
+
+/// 程序的 Authenticode 数字签名
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Signature {
+    /// 签名者名称（签名证书的通用名称，没有时为组织名称）
+    pub signer: Option<String>,
+    /// 签名证书颁发者名称
+    pub issuer: Option<String>,
+    /// 签名时间（时间戳）
+    pub timestamp: Option<DateTime<Utc>>,
+    /// 重新计算的程序摘要与签名中的摘要是否一致（仅校验完整性，不能证明签名者身份）
+    pub digest_valid: bool,
+    /// 签名是否通过系统验证（签名者签名、证书链与程序摘要均有效）
+    pub verified: bool,
+}
+
+impl Signature {
+    /// 读取程序的数字签名
+    ///
+    /// # 参数
+    /// - `path`: 程序路径
+    ///
+    /// # 返回值
+    /// - `Ok(Some(Signature))`: 数字签名
+    /// - `Ok(None)`: 没有数字签名
+    /// - `Err(...)`: 无法读取程序或不是有效的 PE 文件
+    pub fn read(path: impl AsRef<Path>) -> Result<Option<Signature>> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+        let mut signature = Signature::from_pe(&mmap)?;
+        if let Some(signature) = signature
+            .as_mut()
+            .filter(|signature| signature.digest_valid)
+        {
+            signature.verified = verify_trust(path);
+        }
+        Ok(signature)
+    }
+
+    /// 从 PE 文件内容中解析数字签名，并校验程序摘要
+    ///
+    /// 只校验程序摘要，不验证签名者签名与证书链，`verified` 始终为 `false`
+    ///
+    /// # 参数
+    /// - `bytes`: PE 文件内容
+    ///
+    /// # 返回值
+    /// - `Ok(Some(Signature))`: 数字签名（无法解析的签名视为摘要不一致）
+    /// - `Ok(None)`: 没有数字签名
+    pub fn from_pe(bytes: &[u8]) -> Result<Option<Signature>> {
+        let pe = PE::parse(bytes)?;
+        let Some(certificate) = pe
+            .certificates
+            .iter()
+            .find(|cert| cert.certificate_type == AttributeCertificateType::PkcsSignedData)
+        else {
+            return Ok(None);
+        };
+
+        let Some(signed_data) = parse_signed_data(certificate.certificate) else {
+            return Ok(Some(Signature::default()));
+        };
+        let mut signature = signed_data.signature();
+        signature.digest_valid = signed_data.verify(pe.authenticode_ranges());
+        Ok(Some(signature))
+    }
+
+    /// 判断签名是否通过系统验证且签名者在受信任的签名者列表中
+    ///
+    /// # 参数
+    /// - `trusted_signers`: 受信任的签名者名称（不区分大小写）
+    pub fn is_trusted(&self, trusted_signers: &[String]) -> bool {
+        self.verified
+            && self.signer.as_ref().is_some_and(|signer| {
+                trusted_signers
+                    .iter()
+                    .any(|trusted| trusted.trim().eq_ignore_ascii_case(signer))
+            })
+    }
+}
+
+/// 使用 WinVerifyTrust 验证程序的签名者签名、证书链与程序摘要
+///
+/// 不检查证书吊销状态，也不访问网络
+///
+/// # 参数
+/// - `path`: 程序路径
+///
+/// # 返回值
+/// - 签名是否受系统信任
+fn verify_trust(path: &Path) -> bool {
+    let path = HSTRING::from(path.as_os_str());
+    let mut file_info = WINTRUST_FILE_INFO {
+        cbStruct: size_of::<WINTRUST_FILE_INFO>() as u32,
+        pcwszFilePath: PCWSTR(path.as_ptr()),
+        ..Default::default()
+    };
+    let mut data = WINTRUST_DATA {
+        cbStruct: size_of::<WINTRUST_DATA>() as u32,
+        dwUIChoice: WTD_UI_NONE,
+        fdwRevocationChecks: WTD_REVOKE_NONE,
+        dwUnionChoice: WTD_CHOICE_FILE,
+        dwStateAction: WTD_STATEACTION_VERIFY,
+        dwProvFlags: WTD_REVOCATION_CHECK_NONE | WTD_CACHE_ONLY_URL_RETRIEVAL,
+        ..Default::default()
+    };
+    data.Anonymous.pFile = &mut file_info;
+    let mut action = WINTRUST_ACTION_GENERIC_VERIFY_V2;
+    let status = unsafe {
+        WinVerifyTrust(
+            HWND::default(),
+            &mut action,
+            &mut data as *mut WINTRUST_DATA as *mut _,
+        )
+    };
+    // 释放验证状态
+    data.dwStateAction = WTD_STATEACTION_CLOSE;
+    unsafe {
+        WinVerifyTrust(
+            HWND::default(),
+            &mut action,
+            &mut data as *mut WINTRUST_DATA as *mut _,
+        );
+    }
+    status == 0
+}
+
+/// DER 编码的元素
+#[derive(Debug, Clone, Copy)]
+struct Der<'a> {
+    /// 标签
+    tag: u8,
+    /// 内容
+    content: &'a [u8],
+    /// 包含标签与长度的完整编码
+    raw: &'a [u8],
+}
+
+impl<'a> Der<'a> {
+    /// 读取一个元素
+    ///
+    /// # 返回值
+    /// - `Some((元素, 剩余数据))`
+    fn parse(data: &'a [u8]) -> Option<(Der<'a>, &'a [u8])> {
+        let tag = *data.first()?;
+        // 不支持多字节标签
+        if tag & 0x1F == 0x1F {
+            return None;
+        }
+        let first = *data.get(1)? as usize;
+        let (len, header) = if first < 0x80 {
+            (first, 2)
+        } else {
+            // 长格式长度，不支持不定长编码
+            let count = first & 0x7F;
+            if count == 0 || count > 4 {
+                return None;
+            }
+            let len = data
+                .get(2..2 + count)?
+                .iter()
+                .fold(0usize, |len, &byte| (len << 8) | byte as usize);
+            (len, 2 + count)
+        };
+        let end = header.checked_add(len)?;
+        let raw = data.get(..end)?;
+        Some((
+            Der {
+                tag,
+                content: &raw[header..],
+                raw,
+            },
+            &data[end..],
+        ))
+    }
+
+    /// 读取构造类型元素的全部子元素
+    fn children(&self) -> Vec<Der<'a>> {
+        let mut children = Vec::new();
+        let mut rest = self.content;
+        while let Some((child, next)) = Der::parse(rest) {
+            children.push(child);
+            rest = next;
+        }
+        children
+    }
+
+    /// 读取第一个子元素
+    fn first(&self) -> Option<Der<'a>> {
+        Der::parse(self.content).map(|(child, _)| child)
+    }
+
+    /// 判断是否为指定标识的 OID
+    fn is_oid(&self, oid: &[u8]) -> bool {
+        self.tag == TAG_OID && self.content == oid
+    }
+}
+
+/// 摘要算法
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DigestAlgorithm {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl DigestAlgorithm {
+    /// 从 AlgorithmIdentifier 中识别摘要算法
+    fn from_identifier(identifier: &Der) -> Option<DigestAlgorithm> {
+        let oid = identifier.first()?;
+        [
+            (OID_SHA1, DigestAlgorithm::Sha1),
+            (OID_SHA256, DigestAlgorithm::Sha256),
+            (OID_SHA384, DigestAlgorithm::Sha384),
+            (OID_SHA512, DigestAlgorithm::Sha512),
+        ]
+        .into_iter()
+        .find(|(id, _)| oid.is_oid(id))
+        .map(|(_, algorithm)| algorithm)
+    }
+
+    /// 计算多段数据的摘要
+    fn digest<'b>(&self, chunks: impl IntoIterator<Item = &'b [u8]>) -> Vec<u8> {
+        fn hash<'b, D: Digest>(chunks: impl IntoIterator<Item = &'b [u8]>) -> Vec<u8> {
+            let mut hasher = D::new();
+            for chunk in chunks {
+                hasher.update(chunk);
+            }
+            hasher.finalize().to_vec()
+        }
+
+        match self {
+            DigestAlgorithm::Sha1 => hash::<Sha1>(chunks),
+            DigestAlgorithm::Sha256 => hash::<Sha256>(chunks),
+            DigestAlgorithm::Sha384 => hash::<Sha384>(chunks),
+            DigestAlgorithm::Sha512 => hash::<Sha512>(chunks),
+        }
+    }
+}
+
+/// Authenticode 签名数据中用到的部分
+struct SignedData<'a> {
+    /// SpcIndirectDataContent
+    indirect_data: Der<'a>,
+    /// 证书列表
+    certificates: Vec<Der<'a>>,
+    /// 签名者信息
+    signer_info: Der<'a>,
+}
+
+/// 解析 WIN_CERTIFICATE 中的 PKCS#7 ContentInfo
+fn parse_signed_data(data: &[u8]) -> Option<SignedData<'_>> {
+    // ContentInfo ::= SEQUENCE { contentType, [0] EXPLICIT SignedData }
+    let (content_info, _) = Der::parse(data)?;
+    let signed_data = signed_data_of(&content_info)?;
+
+    // SignedData ::= SEQUENCE { version, digestAlgorithms, encapContentInfo, [0] certificates, [1] crls, signerInfos }
+    let children = signed_data.children();
+    let encap = children.get(2).filter(|der| der.tag == TAG_SEQUENCE)?;
+    let indirect_data = encap
+        .children()
+        .into_iter()
+        .find(|der| der.tag == TAG_CONTEXT_0)?
+        .first()
+        .filter(|der| der.tag == TAG_SEQUENCE)?;
+    let certificates = children
+        .iter()
+        .find(|der| der.tag == TAG_CONTEXT_0)
+        .map(Der::children)
+        .unwrap_or_default();
+    let signer_info = children
+        .iter()
+        .rev()
+        .find(|der| der.tag == TAG_SET)?
+        .first()?;
+
+    Some(SignedData {
+        indirect_data,
+        certificates,
+        signer_info,
+    })
+}
+
+/// 读取 ContentInfo 中的 SignedData
+fn signed_data_of<'a>(content_info: &Der<'a>) -> Option<Der<'a>> {
+    let children = content_info.children();
+    if !children.first()?.is_oid(OID_SIGNED_DATA) {
+        return None;
+    }
+    children
+        .get(1)
+        .filter(|der| der.tag == TAG_CONTEXT_0)?
+        .first()
+        .filter(|der| der.tag == TAG_SEQUENCE)
+}
+
+impl SignedData<'_> {
+    /// 提取签名者、颁发者与时间戳
+    fn signature(&self) -> Signature {
+        let signer_info = self.signer_info.children();
+        // sid ::= IssuerAndSerialNumber { issuer, serialNumber }
+        let sid = signer_info.get(1).map(Der::children).unwrap_or_default();
+        let issuer = sid.first();
+        let serial = sid.get(1);
+
+        // 查找与签名者信息对应的签名证书
+        let subject = self.certificates.iter().find_map(|certificate| {
+            let tbs = certificate.first()?.children();
+            // TBSCertificate ::= SEQUENCE { [0] version, serialNumber, signature, issuer, validity, subject, ... }
+            let offset = usize::from(tbs.first()?.tag == TAG_CONTEXT_0);
+            let (cert_serial, cert_issuer, cert_subject) =
+                (tbs.get(offset)?, tbs.get(offset + 2)?, tbs.get(offset + 4)?);
+            (Some(cert_serial.raw) == serial.map(|der| der.raw)
+                && Some(cert_issuer.raw) == issuer.map(|der| der.raw))
+            .then_some(*cert_subject)
+        });
+
+        Signature {
+            signer: subject.as_ref().and_then(display_name),
+            issuer: issuer.and_then(display_name),
+            timestamp: signing_time(&self.signer_info),
+            digest_valid: false,
+            verified: false,
+        }
+    }
+
+    /// 校验程序摘要与签名内容摘要
+    ///
+    /// # 参数
+    /// - `ranges`: 参与 Authenticode 摘要计算的程序数据
+    fn verify<'b>(&self, ranges: impl IntoIterator<Item = &'b [u8]>) -> bool {
+        // SpcIndirectDataContent ::= SEQUENCE { data, messageDigest DigestInfo }
+        // DigestInfo ::= SEQUENCE { digestAlgorithm, digest OCTET STRING }
+        let Some(digest_info) = self.indirect_data.children().get(1).map(Der::children) else {
+            return false;
+        };
+        let (Some(algorithm), Some(expected)) = (
+            digest_info
+                .first()
+                .and_then(DigestAlgorithm::from_identifier),
+            digest_info.get(1).filter(|der| der.tag == TAG_OCTET_STRING),
+        ) else {
+            return false;
+        };
+        if algorithm.digest(ranges) != expected.content {
+            return false;
+        }
+
+        // 签名属性中的 messageDigest 为 SpcIndirectDataContent 内容（不含标签与长度）的摘要
+        let signer_info = self.signer_info.children();
+        let Some(signer_algorithm) = signer_info
+            .get(2)
+            .and_then(DigestAlgorithm::from_identifier)
+        else {
+            return false;
+        };
+        let message_digest = signer_info
+            .iter()
+            .find(|der| der.tag == TAG_CONTEXT_0)
+            .and_then(|attrs| attribute(attrs, OID_MESSAGE_DIGEST))
+            .filter(|der| der.tag == TAG_OCTET_STRING);
+        message_digest.is_some_and(|digest| {
+            signer_algorithm.digest([self.indirect_data.content]) == digest.content
+        })
+    }
+}
+
+/// 读取属性集合中指定属性的第一个值
+///
+/// # 参数
+/// - `attrs`: 属性集合（`[0]` 签名属性或 `[1]` 非签名属性）
+/// - `oid`: 属性标识
+fn attribute<'a>(attrs: &Der<'a>, oid: &[u8]) -> Option<Der<'a>> {
+    // Attribute ::= SEQUENCE { attrType, attrValues SET }
+    attrs.children().into_iter().find_map(|attr| {
+        let children = attr.children();
+        children
+            .first()?
+            .is_oid(oid)
+            .then(|| children.get(1)?.first())
+            .flatten()
+    })
+}
+
+/// 读取签名者信息中的签名时间，依次查找时间戳副署签名、RFC 3161 时间戳与签名属性
+fn signing_time(signer_info: &Der) -> Option<DateTime<Utc>> {
+    let children = signer_info.children();
+    let signed_attrs = children.iter().find(|der| der.tag == TAG_CONTEXT_0);
+    let unsigned_attrs = children.iter().find(|der| der.tag == TAG_CONTEXT_1);
+
+    if let Some(attrs) = unsigned_attrs {
+        // 副署签名是签名者信息，签名时间在其签名属性中
+        if let Some(counter_signer) = attribute(attrs, OID_COUNTER_SIGNATURE)
+            && let Some(time) = counter_signer
+                .children()
+                .iter()
+                .find(|der| der.tag == TAG_CONTEXT_0)
+                .and_then(|attrs| attribute(attrs, OID_SIGNING_TIME))
+                .and_then(|time| parse_time(&time))
+        {
+            return Some(time);
+        }
+
+        // RFC 3161 时间戳令牌的内容为 TSTInfo，其中的 genTime 为签名时间
+        if let Some(token) = attribute(attrs, OID_RFC3161_TIMESTAMP)
+            && let Some(time) = signed_data_of(&token)
+                .and_then(|signed_data| signed_data.children().get(2).copied())
+                .and_then(|encap| {
+                    encap
+                        .children()
+                        .into_iter()
+                        .find(|der| der.tag == TAG_CONTEXT_0)
+                })
+                .and_then(|content| content.first())
+                .filter(|der| der.tag == TAG_OCTET_STRING)
+                .and_then(|octets| Der::parse(octets.content))
+                .and_then(|(tst_info, _)| {
+                    tst_info
+                        .children()
+                        .into_iter()
+                        .find(|der| der.tag == TAG_GENERALIZED_TIME)
+                })
+                .and_then(|time| parse_time(&time))
+        {
+            return Some(time);
+        }
+    }
+
+    signed_attrs
+        .and_then(|attrs| attribute(attrs, OID_SIGNING_TIME))
+        .and_then(|time| parse_time(&time))
+}
+
+/// 解析 UTCTime 或 GeneralizedTime
+fn parse_time(der: &Der) -> Option<DateTime<Utc>> {
+    let text = std::str::from_utf8(der.content).ok()?.trim_end_matches('Z');
+    // 忽略秒的小数部分
+    let text = text.split('.').next()?;
+    let text = match der.tag {
+        // UTCTime 的两位年份：50 及以上为 19xx
+        TAG_UTC_TIME => {
+            let year: u32 = text.get(..2)?.parse().ok()?;
+            format!("{}{}", if year >= 50 { "19" } else { "20" }, text)
+        }
+        TAG_GENERALIZED_TIME => text.to_string(),
+        _ => return None,
+    };
+    NaiveDateTime::parse_from_str(&text, "%Y%m%d%H%M%S")
+        .ok()
+        .map(|time| time.and_utc())
+}
+
+/// 读取名称中的通用名称，没有时为组织名称
+fn display_name(name: &Der) -> Option<String> {
+    // Name ::= SEQUENCE OF SET OF SEQUENCE { type, value }
+    let attributes: Vec<(Der, Der)> = name
+        .children()
+        .iter()
+        .flat_map(Der::children)
+        .filter_map(|attr| {
+            let children = attr.children();
+            Some((*children.first()?, *children.get(1)?))
+        })
+        .collect();
+    [OID_COMMON_NAME, OID_ORGANIZATION]
+        .into_iter()
+        .find_map(|oid| {
+            attributes
+                .iter()
+                .find(|(attr_type, _)| attr_type.is_oid(oid))
+                .and_then(|(_, value)| directory_string(value))
+        })
+}
+
+/// 读取 DirectoryString 的文本
+fn directory_string(der: &Der) -> Option<String> {
+    let text = match der.tag {
+        // BMPString
+        0x1E => {
+            let units: Vec<u16> = der
+                .content
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        // UTF8String、PrintableString、TeletexString、IA5String
+        0x0C | 0x13 | 0x14 | 0x16 => String::from_utf8_lossy(der.content).to_string(),
+        _ => return None,
+    };
+    let text = text.trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
@@ -1,6 +1,8 @@
+use crate::config::RootConfig;
 use crate::utils::process_env;
 use clap::ArgAction;
 use clap::Parser;
+use clap::ValueEnum;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
@@ -10,21 +12,26 @@ use std::path::PathBuf;
 pub struct Cli {
     /// 程序目录
     #[clap(help = "Program Path")]
-    #[clap(value_parser = exist_dir_parser, required_unless_present = "config")]
+    #[clap(value_parser = exist_dir_parser, required_unless_present_any = &["config", "root", "icons"])]
     pub targetPath: Option<PathBuf>,
 
     /// 快捷方式路径
     #[clap(help = "Shortcut Path")]
-    #[clap(value_parser = exist_dir_parser, required_unless_present_any = &["config", "list", "start"]
+    #[clap(value_parser = exist_dir_parser, required_unless_present_any = &["config", "list", "start", "root", "icons"]
     )]
     pub lnkPath: Option<PathBuf>,
 
     /// 配置文件路径
     #[clap(help = "Config Path")]
-    #[clap(short = 'c',long, value_parser = exist_file_parser, required_unless_present_any = &["targetPath", "lnkPath"]
+    #[clap(short = 'c',long, value_parser = exist_file_parser, required_unless_present_any = &["targetPath", "lnkPath", "root"]
     )]
     pub config: Option<PathBuf>,
 
+    /// 扫描目录（可重复指定）
+    #[clap(help = "Scan root and shortcut path as `path=dest` (repeatable)")]
+    #[clap(long, value_name = "PATH=DEST", value_parser = root_parser)]
+    pub root: Vec<RootConfig>,
+
     /// 匹配配置文件中的信息创建快捷方式
     #[arg(short = 'm', long, requires = "config", help = "Create shortcuts to match profiles")]
     pub only_match: bool,
@@ -62,6 +69,46 @@ pub struct Cli {
     #[clap(long)]
     pub debug: bool,
 
+    /// 输出识别过程说明
+    #[clap(help = "Explain why each directory and program was chosen or skipped")]
+    #[clap(long)]
+    pub explain: bool,
+
+    /// 禁用内置的常见程序信息
+    #[clap(help = "Disable the built-in database of known applications")]
+    #[clap(long)]
+    pub no_known_apps: bool,
+
+    /// 交互模式
+    #[clap(help = "Ask which programs to use when the main program of a directory is ambiguous")]
+    #[clap(long, conflicts_with = "list")]
+    pub interactive: bool,
+
+    /// 导出配置文件路径
+    #[clap(help = "Write the created shortcuts to the file as a config file")]
+    #[clap(long, value_name = "FILE", conflicts_with = "list")]
+    pub export_config: Option<PathBuf>,
+
+    /// 扫描报告路径
+    #[clap(help = "Write a JSON report of the scan to the file")]
+    #[clap(long, value_name = "FILE")]
+    pub report: Option<PathBuf>,
+
+    /// 输出格式
+    #[clap(help = "Output format of list mode")]
+    #[clap(long, value_enum, default_value_t = OutputFormat::Text, requires = "list")]
+    pub format: OutputFormat,
+
+    /// 列出图标的程序
+    #[clap(help = "List the icon groups of a program")]
+    #[clap(long, value_name = "PROGRAM", value_parser = exist_file_parser)]
+    pub icons: Option<PathBuf>,
+
+    /// 图标输出目录
+    #[clap(help = "Save the listed icon groups as .ico files and PNG images to the directory")]
+    #[clap(long, value_name = "DIR", requires = "icons")]
+    pub icon_output: Option<PathBuf>,
+
     /// 启动程序
     #[clap(help = "Run program")]
     #[clap(short, long)]
@@ -72,6 +119,15 @@ pub struct Cli {
     version: Option<bool>,
 }
 
+/// 输出格式
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// 文本
+    Text,
+    /// JSON（输出扫描报告）
+    Json,
+}
+
 /// 用于 clap 参数解析：验证路径必须为已存在文件。
 ///
 /// # 参数:
@@ -118,6 +174,32 @@ fn exist_dir_parser(s: &str) -> Result<PathBuf, String> {
     Ok(path)
 }
 
+/// 用于 clap 参数解析：解析 `path=dest` 形式的扫描目录，`=dest` 可省略。
+///
+/// # 参数:
+/// - `s`: 命令行中传入的字符串。
+///
+/// # 返回值:
+/// - `Ok(RootConfig)`: 扫描目录为已存在的目录。
+/// - `Err(String)`: 扫描目录不存在或不是目录，返回错误信息。
+pub fn root_parser(s: &str) -> Result<RootConfig, String> {
+    let (path, dest) = match s.split_once('=') {
+        Some((path, dest)) => (path, Some(dest)),
+        None => (s, None),
+    };
+    let path = exist_dir_parser(path.trim())?;
+    let dest = dest
+        .map(str::trim)
+        .filter(|dest| !dest.is_empty())
+        .map(|dest| process_env(normalize_drive_root(dest), None));
+
+    Ok(RootConfig {
+        path: path.to_string_lossy().to_string(),
+        dest,
+        ..Default::default()
+    })
+}
+
 /// 如果 s 是 “X:”（只有盘符），就返回 “X:\”；否则原样返回
 fn normalize_drive_root(s: &str) -> String {
     if s.len() == 2 && s.as_bytes()[1] == b':' && s.as_bytes()[0].is_ascii_alphabetic() {
@@ -1,10 +1,15 @@
-use crate::console::{write_console, ConsoleType};
-use crate::utils::process_env;
+use crate::authenticode::Signature;
+use crate::console::{write_console, write_console_key, ConsoleType};
+use crate::utils::{
+    get_exe_file_version, get_program_arch, has_icon_in_program, is_gui_program, process_env,
+};
+use crate::version_info::{lang_id_from_tag, preferred_langs};
 use crate::DEBUG;
 use anyhow::Result;
 use rust_i18n::t;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::fs;
 use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
@@ -12,13 +17,16 @@ use std::sync::atomic::Ordering;
 use toml::Table;
 
 /// 默认名称模板
-pub const DEFAULT_NAME_TEMPLATE: &str =
-    "{product ? product : {desc ? desc : {orig_filename ? orig_filename : stem}}}";
+pub const DEFAULT_NAME_TEMPLATE: &str = "{known_name ? known_name : {product ? product : {desc ? desc : {orig_filename ? orig_filename : {manifest_name ? manifest_name : stem}}}}}";
 
 fn default_name_template() -> Option<String> {
     Some(DEFAULT_NAME_TEMPLATE.to_string())
 }
 
+fn default_prefer_link_path() -> bool {
+    true
+}
+
 /// 配置文件信息
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ConfigInfo {
@@ -46,6 +54,10 @@ pub struct ConfigInfo {
     #[serde(default)]
     pub ignore: Vec<String>,
 
+    /// 包含列表（仅处理匹配的程序）
+    #[serde(default)]
+    pub include: Vec<String>,
+
     /// 安装脚本
     #[serde(default)]
     pub install: bool,
@@ -62,6 +74,54 @@ pub struct ConfigInfo {
     #[serde(default)]
     pub shortcut: Vec<Lnk>,
 
+    /// 目录结构识别规则
+    #[serde(default)]
+    pub detection: Detection,
+
+    /// 安装程序处理策略
+    #[serde(default)]
+    pub installers: InstallerPolicy,
+
+    /// 程序过滤条件
+    #[serde(default)]
+    pub filter: ProgramFilter,
+
+    /// 受信任的签名者名称，有效签名来自这些签名者的程序额外加分
+    #[serde(default)]
+    pub trusted_signers: Vec<String>,
+
+    /// 扫描限制
+    #[serde(default)]
+    pub limits: ScanLimits,
+
+    /// 符号链接、目录联接处理策略
+    #[serde(default)]
+    pub follow_links: FollowLinks,
+
+    /// 通过链接找到的程序，快捷方式指向链接路径（否则指向解析后的真实路径）
+    #[serde(default = "default_prefer_link_path")]
+    pub prefer_link_path: bool,
+
+    /// 架构变体的首选架构（默认为系统架构）
+    #[serde(default)]
+    pub prefer_arch: Option<Arch>,
+
+    /// 保留全部架构变体，快捷方式名称追加架构后缀
+    #[serde(default)]
+    pub keep_arch_variants: bool,
+
+    /// 混合目录根目录中的单文件程序放在快捷方式位置下的该子文件夹中
+    #[serde(default)]
+    pub hybrid_root_folder: Option<String>,
+
+    /// 图标缓存目录（可移动磁盘、网络位置上程序的图标复制到该目录）
+    #[serde(default)]
+    pub icon_cache: Option<String>,
+
+    /// 扫描目录列表（一次运行处理多个目录）
+    #[serde(default)]
+    pub root: Vec<RootConfig>,
+
     /// 映射表: 别名
     #[serde(default)]
     name: Table,
@@ -116,6 +176,10 @@ pub struct Template {
     /// 快捷方式备注模版
     #[serde(default)]
     pub comment: Option<String>,
+
+    /// 读取程序版本信息时优先使用的语言
+    #[serde(default)]
+    pub name_language: NameLanguage,
 }
 
 impl Template {
@@ -126,6 +190,454 @@ impl Template {
             dest: None,
             work_dir: None,
             comment: None,
+            name_language: NameLanguage::default(),
+        })
+    }
+}
+
+/// 读取程序版本信息时优先使用的语言
+///
+/// 可以是 `system`（系统语言，其次为英语）、语言名称（如 `en`、`zh-CN`），或按顺序回退的语言列表
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum NameLanguage {
+    /// 单个语言
+    Single(String),
+    /// 语言列表（靠前的优先）
+    List(Vec<String>),
+}
+
+impl Default for NameLanguage {
+    fn default() -> Self {
+        NameLanguage::Single("system".to_string())
+    }
+}
+
+impl NameLanguage {
+    /// 按优先级排列的语言标识（忽略无法识别的语言名称）
+    pub fn lang_ids(&self) -> Vec<u16> {
+        let tags = match self {
+            NameLanguage::Single(tag) => std::slice::from_ref(tag),
+            NameLanguage::List(tags) => tags.as_slice(),
+        };
+        let mut langs = Vec::new();
+        for tag in tags {
+            let ids = if tag.eq_ignore_ascii_case("system") {
+                preferred_langs()
+            } else {
+                lang_id_from_tag(tag).into_iter().collect()
+            };
+            for id in ids {
+                if !langs.contains(&id) {
+                    langs.push(id);
+                }
+            }
+        }
+        langs
+    }
+}
+
+/// 扫描目录配置
+///
+/// 未填写的配置项使用全局配置（或命令行参数）
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RootConfig {
+    /// 扫描目录
+    pub path: String,
+
+    /// 快捷方式位置
+    #[serde(default)]
+    pub dest: Option<String>,
+
+    /// 是否建立目录
+    #[serde(default)]
+    pub create_dir: Option<bool>,
+
+    /// 忽略列表（追加到全局忽略列表）
+    #[serde(default)]
+    pub ignore: Vec<String>,
+
+    /// 快捷方式模版
+    #[serde(default)]
+    pub template: Option<Template>,
+
+    /// 评分阈值
+    #[serde(default)]
+    pub score_ratio: Option<f32>,
+}
+
+impl RootConfig {
+    /// 将扫描目录配置合并到全局配置上
+    ///
+    /// # 参数
+    /// - `global`: 全局配置
+    ///
+    /// # 返回值
+    /// - 作用于该扫描目录的配置
+    pub fn apply(&self, global: &ConfigInfo) -> ConfigInfo {
+        let mut config = global.clone();
+        config.root.clear();
+        config.ignore.extend(self.ignore.iter().cloned());
+        if self.template.is_some() {
+            config.template = self.template.clone();
+        }
+        if self.score_ratio.is_some() {
+            config.score_ratio = self.score_ratio;
+        }
+        config
+    }
+}
+
+/// 安装程序、自解压程序处理策略
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallerPolicy {
+    /// 跳过，不创建快捷方式
+    #[default]
+    Skip,
+    /// 列出安装程序，不创建快捷方式
+    List,
+    /// 视为普通程序
+    Shortcut,
+}
+
+/// 程序过滤条件（`[filter]`），作用于单文件程序、绿色软件主程序候选与其他程序
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct ProgramFilter {
+    /// 排除控制台程序
+    pub exclude_console: bool,
+    /// 最小文件大小（字节）
+    pub min_size: Option<u64>,
+    /// 最大文件大小（字节）
+    pub max_size: Option<u64>,
+    /// 要求有有效的数字签名
+    pub require_signature: bool,
+    /// 要求有来自受信任签名者的有效数字签名
+    pub require_trusted_signer: bool,
+    /// 要求有图标
+    pub require_icon: bool,
+    /// 要求有版本信息
+    pub require_version_info: bool,
+    /// 允许的架构（为空时不限制）
+    pub allowed_arch: Vec<Arch>,
+}
+
+impl ProgramFilter {
+    /// 检查程序是否满足过滤条件
+    ///
+    /// # 参数
+    /// - `program`: 程序路径
+    /// - `trusted_signers`: 受信任的签名者名称
+    ///
+    /// # 返回值
+    /// - `Some(&str)`: 排除程序的过滤条件名称
+    /// - `None`: 满足全部过滤条件
+    pub fn check(&self, program: &Path, trusted_signers: &[String]) -> Option<&'static str> {
+        let size = program.metadata().map(|metadata| metadata.len()).ok();
+        if let Some(min_size) = self.min_size
+            && size.is_none_or(|size| size < min_size)
+        {
+            return Some("min_size");
+        }
+        if let Some(max_size) = self.max_size
+            && size.is_none_or(|size| size > max_size)
+        {
+            return Some("max_size");
+        }
+        // 无法解析的程序不视为控制台程序
+        if self.exclude_console && is_gui_program(program).is_ok_and(|gui| !gui) {
+            return Some("exclude_console");
+        }
+        if !self.allowed_arch.is_empty()
+            && get_program_arch(program)
+                .ok()
+                .and_then(Arch::from_machine)
+                .is_none_or(|arch| !self.allowed_arch.contains(&arch))
+        {
+            return Some("allowed_arch");
+        }
+        if self.require_icon && !has_icon_in_program(program) {
+            return Some("require_icon");
+        }
+        if self.require_version_info && !matches!(get_exe_file_version(program), Ok(Some(_))) {
+            return Some("require_version_info");
+        }
+        if self.require_signature || self.require_trusted_signer {
+            let signature = Signature::read(program).ok().flatten();
+            if self.require_signature
+                && !signature
+                    .as_ref()
+                    .is_some_and(|signature| signature.verified)
+            {
+                return Some("require_signature");
+            }
+            if self.require_trusted_signer
+                && !signature
+                    .as_ref()
+                    .is_some_and(|signature| signature.is_trusted(trusted_signers))
+            {
+                return Some("require_trusted_signer");
+            }
+        }
+        None
+    }
+}
+
+/// 扫描限制（`[limits]`），超出限制的目录将被跳过
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ScanLimits {
+    /// 最大扫描深度（相对于扫描目录）
+    pub max_depth: Option<usize>,
+    /// 目录中的最大文件数（含子目录）
+    pub max_entries_per_dir: Option<usize>,
+    /// 跳过的目录名（支持以 `/` 分隔的多级目录）
+    pub heavy_dirs: Vec<String>,
+    /// 单次运行的时间限制（秒）
+    pub time_budget: Option<u64>,
+}
+
+impl Default for ScanLimits {
+    fn default() -> Self {
+        ScanLimits {
+            max_depth: None,
+            max_entries_per_dir: None,
+            heavy_dirs: [
+                "node_modules",
+                ".git",
+                ".svn",
+                ".hg",
+                "__pycache__",
+                "WinSxS",
+                "Windows/Installer",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+            time_budget: None,
+        }
+    }
+}
+
+impl ScanLimits {
+    /// 检查目录是否超出扫描限制（时间限制、文件数限制除外）
+    ///
+    /// # 参数
+    /// - `dir`: 目录路径
+    /// - `depth`: 相对于扫描目录的深度
+    ///
+    /// # 返回值
+    /// - `Some(&str)`: 超出的限制名称
+    /// - `None`: 未超出限制
+    pub fn check_dir(&self, dir: &Path, depth: usize) -> Option<&'static str> {
+        if depth == 0 {
+            return None;
+        }
+        if Detection::dir_matches(&self.heavy_dirs, dir) {
+            return Some("heavy_dir");
+        }
+        if self.max_depth.is_some_and(|max_depth| depth > max_depth) {
+            return Some("max_depth");
+        }
+        None
+    }
+
+    /// 读取目录的一级条目，并检查条目数量限制（`max_entries_per_dir`）
+    ///
+    /// # 参数
+    /// - `dir`: 目录路径
+    /// - `depth`: 相对于扫描目录的深度
+    ///
+    /// # 返回值
+    /// - `Some(Vec<DirEntry>)`: 目录中的条目（无法读取的目录视为空目录）
+    /// - `None`: 条目数量超出限制
+    pub fn read_entries(&self, dir: &Path, depth: usize) -> Option<Vec<fs::DirEntry>> {
+        let max_entries = self
+            .max_entries_per_dir
+            .filter(|_| depth > 0)
+            .unwrap_or(usize::MAX);
+        // 仅读取到超出限制为止，避免在文件很多的目录上耗时
+        let entries: Vec<_> = match fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(Result::ok)
+                .take(max_entries.saturating_add(1))
+                .collect(),
+            Err(_) => return Some(Vec::new()),
+        };
+        (entries.len() <= max_entries).then_some(entries)
+    }
+}
+
+/// 程序架构
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Arch {
+    /// x86 (32 位)
+    X86,
+    /// x64
+    X64,
+    /// ARM64
+    Arm64,
+}
+
+impl Arch {
+    /// 根据 PE 文件头中的机器类型获取架构
+    pub fn from_machine(machine: u16) -> Option<Arch> {
+        match machine {
+            0x014c => Some(Arch::X86),
+            0x8664 => Some(Arch::X64),
+            0xAA64 => Some(Arch::Arm64),
+            _ => None,
+        }
+    }
+
+    /// 架构名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            Arch::X86 => "x86",
+            Arch::X64 => "x64",
+            Arch::Arm64 => "arm64",
+        }
+    }
+}
+
+/// 符号链接、目录联接处理策略
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FollowLinks {
+    /// 不跟随链接
+    #[default]
+    Never,
+    /// 总是跟随链接
+    Always,
+    /// 仅跟随指向扫描目录内部的链接
+    WithinRoot,
+}
+
+/// 目录结构识别规则
+///
+/// 默认值即内置的识别规则，配置文件中只需填写需要修改的字段。
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Detection {
+    /// 应用支持文件扩展名（轻量识别）
+    pub support_exts: Vec<String>,
+
+    /// 常见应用子目录（轻量识别）
+    pub common_subdirs: Vec<String>,
+
+    /// 文档文件扩展名
+    pub doc_exts: Vec<String>,
+
+    /// 文档文件名（不含扩展名）
+    pub doc_stems: Vec<String>,
+
+    /// 组件目录名（轻量识别），这些目录通常不是应用的最高层根目录
+    pub component_dirs: Vec<String>,
+
+    /// 向上回溯时父目录的应用特征文件扩展名
+    pub root_marker_exts: Vec<String>,
+
+    /// 向上回溯时父目录的应用特征子目录
+    pub root_marker_subdirs: Vec<String>,
+
+    /// 向上回溯时视为组件目录的目录名
+    pub root_component_dirs: Vec<String>,
+
+    /// 单文件目录、混合目录中允许存在的辅助文件扩展名
+    pub allowed_exts: Vec<String>,
+
+    /// 绿色软件目录中搜索主程序的深度
+    pub scan_depth: usize,
+
+    /// 向上回溯应用根目录的最大层数
+    pub max_upward_depth: usize,
+
+    /// 混合目录根目录中允许的未知文件比例
+    pub hybrid_unknown_ratio: f32,
+
+    /// 混合目录中应用子目录的比例阈值
+    pub hybrid_app_ratio: f32,
+}
+
+impl Default for Detection {
+    fn default() -> Self {
+        let to_vec = |items: &[&str]| items.iter().map(|s| s.to_string()).collect();
+        Detection {
+            support_exts: to_vec(&[
+                "dll", "pak", "ini", "json", "xml", "yaml", "dat", "cfg", "conf", "log", "reg",
+                "key", "cupf",
+            ]),
+            common_subdirs: to_vec(&[
+                "bin",
+                "lib",
+                "data",
+                "program",
+                "assets",
+                "resources",
+                "content",
+                "modules",
+                "plugins",
+                "drivers",
+            ]),
+            doc_exts: to_vec(&["txt", "md", "pdf"]),
+            doc_stems: to_vec(&["readme", "license", "eula", "changelog"]),
+            component_dirs: to_vec(&[
+                "bin",
+                "program",
+                "executables",
+                "x64",
+                "win64",
+                "modules",
+                "plugins",
+                "drivers",
+            ]),
+            root_marker_exts: to_vec(&["dll", "ini", "json", "xml", "dat", "cfg", "conf"]),
+            root_marker_subdirs: to_vec(&[
+                "bin",
+                "lib",
+                "data",
+                "program",
+                "assets",
+                "resources",
+                "content",
+            ]),
+            root_component_dirs: to_vec(&["bin", "program", "executables", "x64", "win64"]),
+            allowed_exts: to_vec(&["ico"]),
+            scan_depth: 2,
+            max_upward_depth: 3,
+            hybrid_unknown_ratio: 0.3,
+            hybrid_app_ratio: 0.8,
+        }
+    }
+}
+
+impl Detection {
+    /// 判断名称是否在列表中（不区分大小写）
+    pub fn list_contains(list: &[String], name: &str) -> bool {
+        list.iter().any(|item| item.eq_ignore_ascii_case(name))
+    }
+
+    /// 判断目录是否匹配列表中的目录名
+    ///
+    /// 列表项可以是单个目录名（如 `bin`），也可以是以 `/` 分隔的多级路径（如 `Engine/Binaries`），
+    /// 多级路径与目录路径的末尾几级进行比较。
+    pub fn dir_matches(list: &[String], dir: &Path) -> bool {
+        list.iter().any(|item| {
+            let segments: Vec<&str> = item.split(['/', '\\']).filter(|s| !s.is_empty()).collect();
+            if segments.is_empty() {
+                return false;
+            }
+            let components: Vec<String> = dir
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            components.len() >= segments.len()
+                && components[components.len() - segments.len()..]
+                    .iter()
+                    .zip(&segments)
+                    .all(|(c, s)| c.eq_ignore_ascii_case(s))
         })
     }
 }
@@ -195,39 +707,7 @@ impl ConfigInfo {
     /// # 返回值
     pub fn parse_config_file(path: &Path) -> Result<ConfigInfo> {
         // 读取配置
-        let mut config_content = String::new();
-        File::open(path)?.read_to_string(&mut config_content)?;
-
-        // 判断是否启用转义
-        let enable_escape = config_content
-            .lines()
-            .filter(|line| !line.trim_start().starts_with('#')) // 跳过注释行
-            .any(|line| line.replace(" ", "").contains("enable_escape=true"));
-        config_content = if enable_escape {
-            if DEBUG.load(Ordering::Relaxed) {
-                write_console(ConsoleType::Debug, &t!("config.enable_escape"));
-            }
-            config_content
-        } else {
-            // 将所有双引号内的Windows路径中的反斜杠进行特殊处理
-            let mut result = String::new();
-            let mut in_quotes = false;
-            let chars = config_content.chars().peekable();
-
-            for c in chars {
-                if c == '"' {
-                    in_quotes = !in_quotes;
-                    result.push(c);
-                } else if in_quotes && c == '\\' {
-                    // 在双引号内遇到反斜杠，确保它被转义
-                    result.push('\\');
-                    result.push('\\');
-                } else {
-                    result.push(c);
-                }
-            }
-            result
-        };
+        let config_content = read_config_content(path)?;
 
         // 解析 Toml
         let mut config: ConfigInfo = toml::from_str(&config_content)?;
@@ -309,6 +789,79 @@ impl ConfigInfo {
     }
 }
 
+/// 读取配置文件内容
+///
+/// 未启用转义时，将双引号内的反斜杠转义，以便直接书写 Windows 路径
+///
+/// # 参数
+/// - `path`: 配置文件路径
+fn read_config_content(path: &Path) -> Result<String> {
+    let mut config_content = String::new();
+    File::open(path)?.read_to_string(&mut config_content)?;
+
+    if escape_enabled(&config_content) && DEBUG.load(Ordering::Relaxed) {
+        write_console(ConsoleType::Debug, &t!("config.enable_escape"));
+    }
+    Ok(normalize_config_content(&config_content))
+}
+
+/// 配置文件是否启用转义（`enable_escape = true`）
+///
+/// # 参数
+/// - `config_content`: 配置文件内容
+pub fn escape_enabled(config_content: &str) -> bool {
+    config_content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#')) // 跳过注释行
+        .any(|line| line.replace(" ", "").contains("enable_escape=true"))
+}
+
+/// 将配置文件内容转换为标准 TOML
+///
+/// 未启用转义时，将双引号内的反斜杠转义，以便直接书写 Windows 路径
+///
+/// # 参数
+/// - `config_content`: 配置文件内容
+pub fn normalize_config_content(config_content: &str) -> String {
+    if escape_enabled(config_content) {
+        return config_content.to_string();
+    }
+
+    // 将所有双引号内的Windows路径中的反斜杠进行特殊处理
+    let mut result = String::new();
+    let mut in_quotes = false;
+    let chars = config_content.chars().peekable();
+
+    for c in chars {
+        if c == '"' {
+            in_quotes = !in_quotes;
+            result.push(c);
+        } else if in_quotes && c == '\\' {
+            // 在双引号内遇到反斜杠，确保它被转义
+            result.push('\\');
+            result.push('\\');
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// 按配置文件的写法将路径写为字符串值
+///
+/// 未启用转义时写在双引号内、反斜杠原样保留（读取时再转义）；启用转义时使用标准 TOML 字符串
+///
+/// # 参数
+/// - `path`: 路径（不含双引号）
+/// - `enable_escape`: 配置文件是否启用转义
+pub fn config_path_string(path: &str, enable_escape: bool) -> String {
+    if enable_escape {
+        toml::Value::String(path.to_string()).to_string()
+    } else {
+        format!("\"{}\"", path)
+    }
+}
+
 /// 处理 ConfigInfo 中的环境变量
 ///
 /// # 参数
@@ -317,21 +870,7 @@ impl ConfigInfo {
 fn process_env_in_config(config: &mut ConfigInfo, config_path: &Path) {
     // 处理 template
     if let Some(ref mut template) = config.template {
-        if let Some(ref mut name) = template.name {
-            *name = process_env(name.clone(), Some(config_path));
-        }
-        if let Some(ref mut dest) = template.dest {
-            *dest = process_env(dest.clone(), Some(config_path));
-        }
-        if let Some(ref mut icon) = template.icon {
-            *icon = process_env(icon.clone(), Some(config_path));
-        }
-        if let Some(ref mut work_dir) = template.work_dir {
-            *work_dir = process_env(work_dir.clone(), Some(config_path));
-        }
-        if let Some(ref mut comment) = template.comment {
-            *comment = process_env(comment.clone(), Some(config_path));
-        }
+        process_env_in_template(template, config_path);
     }
 
     // 处理 ignore 列表
@@ -339,41 +878,97 @@ fn process_env_in_config(config: &mut ConfigInfo, config_path: &Path) {
         *s = process_env(s.clone(), Some(config_path));
     }
 
+    // 处理 include 列表
+    for s in &mut config.include {
+        *s = process_env(s.clone(), Some(config_path));
+    }
+
     // 处理 scripts 列表
     for s in &mut config.scripts {
         *s = process_env(s.clone(), Some(config_path));
     }
 
+    // 处理图标缓存目录
+    if let Some(ref mut dir) = config.icon_cache {
+        *dir = process_env(dir.clone(), Some(config_path));
+    }
+
     // 处理 shortcut 列表
     for lnk in &mut config.shortcut {
-        lnk.exec = process_env(lnk.exec.clone(), Some(config_path));
-        if let Some(ref mut name) = lnk.name {
-            *name = process_env(name.clone(), Some(config_path));
-        }
-        if let Some(ref mut icon) = lnk.icon {
-            *icon = process_env(icon.clone(), Some(config_path));
-        }
-        if let Some(ref mut args) = lnk.args {
-            *args = process_env(args.clone(), Some(config_path));
-        }
-        if let Some(ref mut dest) = lnk.dest {
+        process_env_in_lnk(lnk, config_path);
+    }
+
+    // 处理 root 列表
+    for root in &mut config.root {
+        root.path = process_env(root.path.clone(), Some(config_path));
+        if let Some(ref mut dest) = root.dest {
             *dest = process_env(dest.clone(), Some(config_path));
         }
-        if let Some(ref mut work_dir) = lnk.work_dir {
-            *work_dir = process_env(work_dir.clone(), Some(config_path));
+        for s in &mut root.ignore {
+            *s = process_env(s.clone(), Some(config_path));
         }
-        if let Some(ref mut window_state) = lnk.window_state {
-            *window_state = process_env(window_state.clone(), Some(config_path));
-        }
-        if let Some(ref mut comment) = lnk.comment {
-            *comment = process_env(comment.clone(), Some(config_path));
-        }
-        if let Some(ref mut hotkey) = lnk.hotkey {
-            *hotkey = process_env(hotkey.clone(), Some(config_path));
+        if let Some(ref mut template) = root.template {
+            process_env_in_template(template, config_path);
         }
     }
 }
 
+/// 处理 Template 中的环境变量
+///
+/// # 参数
+/// - `template`: 要处理的 Template 结构体引用
+/// - `config_path`: 配置文件路径，用于处理环境变量中的相对路径
+fn process_env_in_template(template: &mut Template, config_path: &Path) {
+    if let Some(ref mut name) = template.name {
+        *name = process_env(name.clone(), Some(config_path));
+    }
+    if let Some(ref mut dest) = template.dest {
+        *dest = process_env(dest.clone(), Some(config_path));
+    }
+    if let Some(ref mut icon) = template.icon {
+        *icon = process_env(icon.clone(), Some(config_path));
+    }
+    if let Some(ref mut work_dir) = template.work_dir {
+        *work_dir = process_env(work_dir.clone(), Some(config_path));
+    }
+    if let Some(ref mut comment) = template.comment {
+        *comment = process_env(comment.clone(), Some(config_path));
+    }
+}
+
+/// 处理 Lnk 中的环境变量
+///
+/// # 参数
+/// - `lnk`: 要处理的 Lnk 结构体引用
+/// - `config_path`: 配置文件路径，用于处理环境变量中的相对路径
+fn process_env_in_lnk(lnk: &mut Lnk, config_path: &Path) {
+    lnk.exec = process_env(lnk.exec.clone(), Some(config_path));
+    if let Some(ref mut name) = lnk.name {
+        *name = process_env(name.clone(), Some(config_path));
+    }
+    if let Some(ref mut icon) = lnk.icon {
+        *icon = process_env(icon.clone(), Some(config_path));
+    }
+    if let Some(ref mut args) = lnk.args {
+        *args = process_env(args.clone(), Some(config_path));
+    }
+    if let Some(ref mut dest) = lnk.dest {
+        *dest = process_env(dest.clone(), Some(config_path));
+    }
+    if let Some(ref mut work_dir) = lnk.work_dir {
+        *work_dir = process_env(work_dir.clone(), Some(config_path));
+    }
+    if let Some(ref mut window_state) = lnk.window_state {
+        *window_state = process_env(window_state.clone(), Some(config_path));
+    }
+    if let Some(ref mut comment) = lnk.comment {
+        *comment = process_env(comment.clone(), Some(config_path));
+    }
+    if let Some(ref mut hotkey) = lnk.hotkey {
+        *hotkey = process_env(hotkey.clone(), Some(config_path));
+    }
+}
+
 impl Lnk {
     /// 根据指定路径查找配置文件中对应的配置信息
     ///
@@ -397,6 +992,162 @@ impl Lnk {
     }
 }
 
+/// 目录类型
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DirKind {
+    /// 分类目录
+    Category,
+    /// 单文件程序目录
+    Single,
+    /// 单文件程序、绿色软件混合目录
+    Hybrid,
+    /// 绿色软件目录
+    Green,
+}
+
+/// 目录配置（扫描目录中的 `.autoshortcut.toml`）
+///
+/// `template`、`score_ratio`、`shortcut` 合并到全局配置上，作用于所在目录及其子目录；其余配置仅作用于所在目录。
+/// `name`、`icon`、`args`、`hotkey` 等快捷方式属性只应用于指定的主程序（`main`），
+/// 未指定 `main` 的单文件目录中应用于全部程序，不会合并到全局配置。
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct DirConfig {
+    /// 忽略此目录
+    pub ignore: bool,
+
+    /// 指定目录类型
+    pub kind: Option<DirKind>,
+
+    /// 指定主程序（相对于所在目录）
+    pub main: Option<String>,
+
+    /// 主程序快捷方式名称
+    pub name: Option<String>,
+
+    /// 主程序快捷方式图标
+    pub icon: Option<String>,
+
+    /// 主程序快捷方式命令行
+    pub args: Option<String>,
+
+    /// 主程序快捷方式位置
+    pub dest: Option<String>,
+
+    /// 主程序快捷方式起始位置
+    pub work_dir: Option<String>,
+
+    /// 主程序快捷方式显示模式
+    pub window_state: Option<String>,
+
+    /// 主程序快捷方式备注
+    pub comment: Option<String>,
+
+    /// 主程序快捷方式快捷键
+    pub hotkey: Option<String>,
+
+    /// 快捷方式模版
+    pub template: Option<Template>,
+
+    /// 评分阈值
+    pub score_ratio: Option<f32>,
+
+    /// 程序信息列表（`exec` 相对于所在目录）
+    pub shortcut: Vec<Lnk>,
+}
+
+impl DirConfig {
+    /// 解析目录配置文件
+    ///
+    /// # 参数
+    /// - `path`: 目录配置文件路径
+    pub fn parse_config_file(path: &Path) -> Result<DirConfig> {
+        let config_content = read_config_content(path)?;
+        let mut config: DirConfig = toml::from_str(&config_content)?;
+
+        // 判断评分阈值是否合法
+        if let Some(ratio) = config.score_ratio
+            && ratio > 1.0
+        {
+            write_console_key(
+                ConsoleType::Warning,
+                "config.invalid_ratio",
+                &t!("config.invalid_ratio", ratio = ratio),
+            );
+            config.score_ratio = None;
+        }
+
+        // 处理内置变量，相对路径以目录配置文件所在目录为准
+        let dir = path.parent().unwrap_or(path);
+        for value in [
+            &mut config.main,
+            &mut config.name,
+            &mut config.icon,
+            &mut config.args,
+            &mut config.dest,
+            &mut config.work_dir,
+            &mut config.comment,
+            &mut config.hotkey,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *value = process_env(value.clone(), Some(path));
+        }
+        for lnk in &mut config.shortcut {
+            process_env_in_lnk(lnk, path);
+            let exec = PathBuf::from(&lnk.exec);
+            if !exec.is_absolute() {
+                lnk.exec = dir.join(exec).to_string_lossy().to_string();
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// 主程序快捷方式属性是否存在
+    pub fn has_lnk_props(&self) -> bool {
+        self.name.is_some()
+            || self.icon.is_some()
+            || self.args.is_some()
+            || self.dest.is_some()
+            || self.work_dir.is_some()
+            || self.window_state.is_some()
+            || self.comment.is_some()
+            || self.hotkey.is_some()
+    }
+
+    /// 将目录配置中的快捷方式属性合并到程序的快捷方式信息上（目录配置优先）
+    ///
+    /// # 参数
+    /// - `program_path`: 程序路径
+    /// - `lnk_info`: 全局配置中的快捷方式信息
+    pub fn merge_lnk(&self, program_path: &Path, lnk_info: Option<Lnk>) -> Option<Lnk> {
+        if !self.has_lnk_props() {
+            return lnk_info;
+        }
+
+        let mut lnk =
+            lnk_info.unwrap_or_else(|| Lnk::new(program_path.to_string_lossy().to_string()));
+        for (target, value) in [
+            (&mut lnk.name, &self.name),
+            (&mut lnk.icon, &self.icon),
+            (&mut lnk.args, &self.args),
+            (&mut lnk.dest, &self.dest),
+            (&mut lnk.work_dir, &self.work_dir),
+            (&mut lnk.window_state, &self.window_state),
+            (&mut lnk.comment, &self.comment),
+            (&mut lnk.hotkey, &self.hotkey),
+        ] {
+            if value.is_some() {
+                *target = value.clone();
+            }
+        }
+        Some(lnk)
+    }
+}
+
 impl Default for ConfigInfo {
     fn default() -> Self {
         ConfigInfo {
@@ -406,10 +1157,23 @@ impl Default for ConfigInfo {
             only_match: false,
             score_ratio: None,
             ignore: Vec::new(),
+            include: Vec::new(),
             install: false,
             install_parallel: false,
             scripts: Vec::new(),
             shortcut: Vec::new(),
+            detection: Detection::default(),
+            installers: InstallerPolicy::default(),
+            filter: ProgramFilter::default(),
+            trusted_signers: Vec::new(),
+            limits: ScanLimits::default(),
+            follow_links: FollowLinks::default(),
+            prefer_link_path: true,
+            prefer_arch: None,
+            keep_arch_variants: false,
+            hybrid_root_folder: None,
+            icon_cache: None,
+            root: Vec::new(),
             name: Table::new(),
             work_dir: Table::new(),
             args: Table::new(),
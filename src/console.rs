@@ -1,6 +1,10 @@
 use console::style;
 use rust_i18n::t;
 use std::cmp::PartialEq;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 控制台消息写入标准错误（标准输出只用于 JSON 结果）
+static USE_STDERR: AtomicBool = AtomicBool::new(false);
 
 #[derive(PartialEq)]
 pub enum ConsoleType {
@@ -14,6 +18,18 @@ pub enum ConsoleType {
     Error,
     /// 调试
     Debug,
+    /// 识别过程说明
+    Explain,
+}
+
+/// 将之后的控制台消息全部写入标准错误
+pub fn use_stderr() {
+    USE_STDERR.store(true, Ordering::Relaxed);
+}
+
+/// 控制台消息是否写入标准错误
+pub fn uses_stderr() -> bool {
+    USE_STDERR.load(Ordering::Relaxed)
 }
 
 /// 写入控制台
@@ -31,11 +47,23 @@ pub fn write_console(console_type: ConsoleType, message: &str) {
         ConsoleType::Warning => style(t!("console.warning")).yellow(),
         ConsoleType::Error => style(t!("console.error")).red(),
         ConsoleType::Debug => style(t!("console.debug")).magenta(),
+        ConsoleType::Explain => style(t!("console.explain")).blue(),
     };
 
-    if console_type == ConsoleType::Error {
+    if console_type == ConsoleType::Error || uses_stderr() {
         eprintln!("  {}      {}", &title, message);
     } else {
         println!("  {}      {}", &title, message);
     }
 }
+
+/// 写入控制台，并将警告、错误记录到扫描报告中
+///
+/// # 参数
+/// - `console_type`: 控制台类型
+/// - `key`: 消息的国际化键
+/// - `message`: 控制台消息
+pub fn write_console_key(console_type: ConsoleType, key: &str, message: &str) {
+    crate::report::record_message(&console_type, key, message);
+    write_console(console_type, message);
+}
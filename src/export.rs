@@ -0,0 +1,121 @@
+use crate::report::{self, CandidateRecord, Report};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// 目录识别结果中“未识别到主程序”的原因
+pub const MAIN_NOT_RECOGNIZED: &str = "main_not_recognized";
+
+/// 将本次扫描的结果导出为配置文件
+///
+/// # 参数
+/// - `path`: 配置文件路径
+pub fn save(path: &Path) -> Result<()> {
+    let content = report::inspect(render_config).ok_or_else(|| anyhow!("report is disabled"))?;
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// 将扫描报告转换为配置文件（`ConfigInfo` 格式，评分以注释形式写入）
+///
+/// # 参数
+/// - `report`: 扫描报告
+///
+/// # 返回值
+/// - `String`: 配置文件内容
+pub fn render_config(report: &Report) -> String {
+    let mut content = String::new();
+    writeln!(content, "# AutoShortcut {} --export-config", report.version).ok();
+    for root in &report.roots {
+        writeln!(content, "# {}", root.display()).ok();
+    }
+    content.push('\n');
+    // 字符串使用标准 TOML 写法，路径中的单引号、名称中的双引号都能正确读取
+    content.push_str("enable_escape = true\n");
+    content.push_str("only_match = true\n");
+
+    // 没有识别到主程序的目录
+    let unrecognized: Vec<_> = report
+        .directories
+        .iter()
+        .filter(|dir| dir.reason == MAIN_NOT_RECOGNIZED)
+        .collect();
+    if !unrecognized.is_empty() {
+        content.push_str("\n# No main program recognized\nignore = [\n");
+        for dir in unrecognized {
+            writeln!(content, "    {},", toml_string(&dir.path.to_string_lossy())).ok();
+        }
+        content.push_str("]\n");
+    }
+
+    // 程序所在目录中的文件名可以唯一确定程序时使用相对路径
+    let mut file_names: HashMap<String, usize> = HashMap::new();
+    for shortcut in &report.shortcuts {
+        if let Some(file_name) = shortcut.program.file_name() {
+            *file_names
+                .entry(file_name.to_string_lossy().to_lowercase())
+                .or_default() += 1;
+        }
+    }
+
+    for shortcut in &report.shortcuts {
+        content.push('\n');
+        if let Some(candidate) = report
+            .candidates
+            .iter()
+            .find(|candidate| candidate.selected && candidate.path == shortcut.program)
+        {
+            writeln!(content, "# {}", score_comment(candidate)).ok();
+        }
+
+        let file_name = shortcut
+            .program
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().to_string())
+            .filter(|file_name| file_names.get(&file_name.to_lowercase()) == Some(&1));
+        let exec = file_name.unwrap_or_else(|| shortcut.program.to_string_lossy().to_string());
+
+        content.push_str("[[shortcut]]\n");
+        writeln!(content, "exec = {}", toml_string(&exec)).ok();
+        writeln!(content, "name = {}", toml_string(&shortcut.name)).ok();
+        if let Some(icon) = &shortcut.icon {
+            // 索引为 0 时省略
+            let icon = icon.strip_suffix("#0").unwrap_or(icon);
+            writeln!(content, "icon = {}", toml_string(icon)).ok();
+        }
+        if let Some(args) = &shortcut.args {
+            writeln!(content, "args = {}", toml_string(args)).ok();
+        }
+        writeln!(
+            content,
+            "dest = {}",
+            toml_string(&shortcut.dest.to_string_lossy())
+        )
+        .ok();
+        if let Some(work_dir) = &shortcut.work_dir {
+            writeln!(content, "work_dir = {}", toml_string(work_dir)).ok();
+        }
+    }
+    content
+}
+
+/// 评分注释，如 `score 135 / 118: gui+50, icon+40`
+fn score_comment(candidate: &CandidateRecord) -> String {
+    let details = candidate
+        .breakdown
+        .iter()
+        .map(|item| format!("{}{:+}", item.metric, item.score))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "score {} / {}: {}",
+        candidate.score, candidate.threshold, details
+    )
+}
+
+/// 转换为 TOML 字符串（配置文件启用了转义）
+fn toml_string(value: &str) -> String {
+    toml::Value::String(value.to_string()).to_string()
+}
@@ -0,0 +1,423 @@
+use crate::console::{write_console, write_console_key, ConsoleType};
+use crate::pe_resource::{find_resources, read_u16, read_u32, ResourceName, RT_GROUP_ICON, RT_ICON};
+use anyhow::{anyhow, Result};
+use memmap2::Mmap;
+use rust_i18n::t;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+/// 大图标尺寸（评分时优先选择包含该尺寸图标的程序）
+pub const LARGE_ICON_SIZE: u32 = 256;
+
+/// PNG 文件签名
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// 图标组中的图标
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IconImage {
+    /// 图标资源标识（RT_ICON）
+    pub id: u16,
+    /// 宽度
+    pub width: u32,
+    /// 高度
+    pub height: u32,
+    /// 颜色数（256 色及以上为 0）
+    pub color_count: u8,
+    /// 颜色平面数
+    pub planes: u16,
+    /// 颜色深度
+    pub bit_count: u16,
+    /// 是否为 PNG 格式
+    pub is_png: bool,
+    /// 图标数据（PNG 或不含文件头的 BMP）
+    pub data: Vec<u8>,
+}
+
+/// 图标组（RT_GROUP_ICON），按顺序对应图标索引
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IconGroup {
+    /// 资源名称
+    pub name: ResourceName,
+    /// 语言标识
+    pub lang: u16,
+    /// 图标
+    pub images: Vec<IconImage>,
+}
+
+impl IconGroup {
+    /// 最大的图标尺寸
+    pub fn max_size(&self) -> u32 {
+        self.images
+            .iter()
+            .map(|image| image.width.max(image.height))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// 转换为 `.ico` 文件
+    pub fn to_ico(&self) -> Vec<u8> {
+        let count = self.images.len();
+        let mut ico = Vec::new();
+        // ICONDIR：保留、类型（1 为图标）、数量
+        ico.extend(0u16.to_le_bytes());
+        ico.extend(1u16.to_le_bytes());
+        ico.extend((count as u16).to_le_bytes());
+
+        // ICONDIRENTRY，尺寸为 256 及以上时写入 0
+        let mut offset = 6 + 16 * count;
+        for image in &self.images {
+            ico.push(if image.width >= 256 {
+                0
+            } else {
+                image.width as u8
+            });
+            ico.push(if image.height >= 256 {
+                0
+            } else {
+                image.height as u8
+            });
+            ico.push(image.color_count);
+            ico.push(0);
+            ico.extend(image.planes.to_le_bytes());
+            ico.extend(image.bit_count.to_le_bytes());
+            ico.extend((image.data.len() as u32).to_le_bytes());
+            ico.extend((offset as u32).to_le_bytes());
+            offset += image.data.len();
+        }
+        for image in &self.images {
+            ico.extend(&image.data);
+        }
+        ico
+    }
+}
+
+impl IconImage {
+    /// 转换为 PNG 图片
+    ///
+    /// # 返回值
+    /// - `Some(Vec<u8>)`: PNG 图片
+    /// - `None`: 不支持的 BMP 格式
+    pub fn to_png(&self) -> Option<Vec<u8>> {
+        if self.is_png {
+            return Some(self.data.clone());
+        }
+        let (width, height, rgba) = decode_dib(&self.data)?;
+        Some(encode_png(width, height, &rgba))
+    }
+}
+
+/// 读取 PE 文件中的全部图标组
+///
+/// # 参数
+/// - `bytes`: PE 文件内容
+///
+/// # 返回值
+/// - `Ok(Vec<IconGroup>)`: 图标组（没有图标时为空）
+/// - `Err(...)`: 不是有效的 PE 文件
+pub fn parse_icon_groups(bytes: &[u8]) -> Result<Vec<IconGroup>> {
+    let icons = find_resources(bytes, RT_ICON)?;
+    let mut groups = Vec::new();
+    for group in find_resources(bytes, RT_GROUP_ICON)? {
+        // GRPICONDIR：保留、类型、数量，之后为 14 字节的 GRPICONDIRENTRY
+        let count = read_u16(group.data, 4).unwrap_or(0) as usize;
+        let mut images = Vec::new();
+        for index in 0..count {
+            let entry = 6 + index * 14;
+            let Some(id) = read_u16(group.data, entry + 12) else {
+                break;
+            };
+            // 优先使用相同语言的图标
+            let Some(icon) = icons
+                .iter()
+                .filter(|icon| icon.name == ResourceName::Id(id))
+                .min_by_key(|icon| icon.lang != group.lang)
+            else {
+                continue;
+            };
+            let size = |value: u8| if value == 0 { 256 } else { value as u32 };
+            let mut image = IconImage {
+                id,
+                width: size(group.data[entry]),
+                height: size(group.data[entry + 1]),
+                color_count: group.data[entry + 2],
+                planes: read_u16(group.data, entry + 4).unwrap_or(1),
+                bit_count: read_u16(group.data, entry + 6).unwrap_or(0),
+                is_png: icon.data.starts_with(PNG_SIGNATURE),
+                data: icon.data.to_vec(),
+            };
+
+            // 以图标数据中的尺寸与颜色深度为准
+            if image.is_png {
+                if let (Some(width), Some(height)) =
+                    (read_u32_be(icon.data, 16), read_u32_be(icon.data, 20))
+                {
+                    image.width = width;
+                    image.height = height;
+                }
+            } else if let (Some(width), Some(height), Some(bit_count)) = (
+                read_u32(icon.data, 4),
+                read_u32(icon.data, 8),
+                read_u16(icon.data, 14),
+            ) {
+                // BMP 的高度包含掩码
+                image.width = width;
+                image.height = height / 2;
+                image.bit_count = bit_count;
+            }
+            images.push(image);
+        }
+        if !images.is_empty() {
+            groups.push(IconGroup {
+                name: group.name,
+                lang: group.lang,
+                images,
+            });
+        }
+    }
+    Ok(groups)
+}
+
+/// 读取程序中的全部图标组
+///
+/// # 参数
+/// - `program`: 程序路径
+pub fn read_icon_groups(program: &Path) -> Result<Vec<IconGroup>> {
+    let file = File::open(program)?;
+    let mmap = unsafe { Mmap::map(&file)? };
+    parse_icon_groups(&mmap)
+}
+
+/// 将图标组保存为 `.ico` 文件
+///
+/// # 参数
+/// - `program`: 程序路径
+/// - `index`: 图标索引
+/// - `dest`: `.ico` 文件路径
+pub fn extract_icon(program: &Path, index: usize, dest: &Path) -> Result<()> {
+    let groups = read_icon_groups(program)?;
+    let group = groups
+        .get(index)
+        .ok_or_else(|| anyhow!("icon index {} out of range", index))?;
+    fs::write(dest, group.to_ico())?;
+    Ok(())
+}
+
+/// 列出程序中的图标组，指定输出目录时同时保存为 `.ico` 文件与 PNG 图片
+///
+/// # 参数
+/// - `program`: 程序路径
+/// - `output`: 输出目录
+pub fn print_icons(program: &Path, output: Option<&Path>) -> Result<()> {
+    let groups = read_icon_groups(program)?;
+    if groups.is_empty() {
+        write_console_key(
+            ConsoleType::Warning,
+            "icon.none",
+            &t!("icon.none", path = program.display()),
+        );
+        return Ok(());
+    }
+    if let Some(output) = output {
+        fs::create_dir_all(output)?;
+    }
+
+    let stem = program
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    for (index, group) in groups.iter().enumerate() {
+        let name = match &group.name {
+            ResourceName::Id(id) => id.to_string(),
+            ResourceName::Name(name) => name.clone(),
+        };
+        write_console(
+            ConsoleType::Info,
+            &t!(
+                "icon.group",
+                index = index,
+                name = name,
+                count = group.images.len()
+            ),
+        );
+        for image in &group.images {
+            write_console(
+                ConsoleType::Info,
+                &t!(
+                    "icon.image",
+                    width = image.width,
+                    height = image.height,
+                    bits = image.bit_count,
+                    format = if image.is_png { " PNG" } else { "" }
+                ),
+            );
+        }
+
+        let Some(output) = output else {
+            continue;
+        };
+        let mut saved: Vec<PathBuf> = vec![output.join(format!("{}_{}.ico", stem, index))];
+        fs::write(&saved[0], group.to_ico())?;
+        for image in &group.images {
+            if let Some(png) = image.to_png() {
+                let path = output.join(format!(
+                    "{}_{}_{}x{}_{}.png",
+                    stem, index, image.width, image.height, image.bit_count
+                ));
+                fs::write(&path, png)?;
+                saved.push(path);
+            }
+        }
+        for path in saved {
+            write_console(
+                ConsoleType::Success,
+                &t!("icon.saved", path = path.display()),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// 读取大端序 `u32`
+fn read_u32_be(data: &[u8], offset: usize) -> Option<u32> {
+    read_u32(data, offset).map(u32::swap_bytes)
+}
+
+/// 将图标中的 BMP 数据（BITMAPINFOHEADER + 颜色数据 + 掩码）解码为 RGBA
+///
+/// # 返回值
+/// - `Some((宽度, 高度, RGBA))`
+/// - `None`: 不支持的格式
+fn decode_dib(data: &[u8]) -> Option<(u32, u32, Vec<u8>)> {
+    let header_size = read_u32(data, 0)? as usize;
+    let width = read_u32(data, 4)? as usize;
+    let height = (read_u32(data, 8)? / 2) as usize;
+    let bit_count = read_u16(data, 14)? as usize;
+    let colors_used = read_u32(data, 32).unwrap_or(0) as usize;
+    if width == 0 || height == 0 || width > 1024 || height > 1024 {
+        return None;
+    }
+
+    // 调色板
+    let palette_size = match bit_count {
+        1 | 4 | 8 if colors_used > 0 => colors_used,
+        1 | 4 | 8 => 1 << bit_count,
+        24 | 32 => 0,
+        _ => return None,
+    };
+    let palette = data.get(header_size..header_size + palette_size * 4)?;
+    let pixels_start = header_size + palette_size * 4;
+    let stride = (width * bit_count).div_ceil(32) * 4;
+    let mask_start = pixels_start + stride * height;
+    let mask_stride = width.div_ceil(32) * 4;
+
+    let mut rgba = vec![0u8; width * height * 4];
+    let mut has_alpha = false;
+    for y in 0..height {
+        // 自下而上存储
+        let row = pixels_start + (height - 1 - y) * stride;
+        for x in 0..width {
+            let [b, g, r, a] = match bit_count {
+                32 => {
+                    let p = data.get(row + x * 4..row + x * 4 + 4)?;
+                    [p[0], p[1], p[2], p[3]]
+                }
+                24 => {
+                    let p = data.get(row + x * 3..row + x * 3 + 3)?;
+                    [p[0], p[1], p[2], 255]
+                }
+                _ => {
+                    let bit = x * bit_count;
+                    let byte = *data.get(row + bit / 8)?;
+                    let index =
+                        (byte >> (8 - bit_count - bit % 8)) as usize & ((1 << bit_count) - 1);
+                    let p = palette.get(index * 4..index * 4 + 3)?;
+                    [p[0], p[1], p[2], 255]
+                }
+            };
+            has_alpha |= bit_count == 32 && a != 0;
+            let pixel = (y * width + x) * 4;
+            rgba[pixel..pixel + 4].copy_from_slice(&[r, g, b, a]);
+        }
+    }
+
+    // 没有 Alpha 通道时使用掩码（1 为透明）
+    if !has_alpha {
+        for y in 0..height {
+            let row = mask_start + (height - 1 - y) * mask_stride;
+            for x in 0..width {
+                let transparent = data
+                    .get(row + x / 8)
+                    .is_some_and(|byte| byte & (0x80 >> (x % 8)) != 0);
+                rgba[(y * width + x) * 4 + 3] = if transparent { 0 } else { 255 };
+            }
+        }
+    }
+    Some((width as u32, height as u32, rgba))
+}
+
+/// 将 RGBA 数据编码为 PNG 图片（不压缩）
+fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    // 每行前加上过滤类型 0
+    let row_size = width as usize * 4;
+    let mut raw = Vec::with_capacity((row_size + 1) * height as usize);
+    for row in rgba.chunks(row_size) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    // zlib 数据：不压缩的 deflate 块
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(0xFFFF).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend([1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        zlib.push(u8::from(blocks.peek().is_none()));
+        zlib.extend((block.len() as u16).to_le_bytes());
+        zlib.extend((!(block.len() as u16)).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend(adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::new();
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    // 位深度 8、RGBA、默认压缩、默认过滤、不交错
+    header.extend([8, 6, 0, 0, 0]);
+
+    let mut png = PNG_SIGNATURE.to_vec();
+    for (kind, data) in [(b"IHDR", header), (b"IDAT", zlib), (b"IEND", Vec::new())] {
+        png.extend((data.len() as u32).to_be_bytes());
+        let start = png.len();
+        png.extend(kind);
+        png.extend(&data);
+        png.extend(crc32(&png[start..]).to_be_bytes());
+    }
+    png
+}
+
+/// 计算 CRC-32
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// 计算 Adler-32
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
@@ -0,0 +1,230 @@
+use crate::console::{write_console, write_console_key, ConsoleType};
+use crate::icon::{read_icon_groups, IconGroup};
+use crate::pe_resource::ResourceName;
+use crate::utils::is_removable_or_network;
+use crate::DEBUG;
+use anyhow::Result;
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// 图标缓存索引文件名
+pub const INDEX_FILE_NAME: &str = "index.json";
+
+/// 图标缓存目录
+static CACHE_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// 本次运行是否已清理过期的缓存
+static COLLECTED: AtomicBool = AtomicBool::new(false);
+
+/// 图标缓存索引
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct CacheIndex {
+    /// 快捷方式路径与其使用的缓存图标文件名
+    #[serde(default)]
+    pub links: BTreeMap<String, String>,
+}
+
+/// 设置图标缓存目录，首次设置时清理过期的缓存
+///
+/// # 参数
+/// - `dir`: 图标缓存目录，`None` 表示不缓存图标
+pub fn configure(dir: Option<&str>) {
+    let dir = dir.map(PathBuf::from);
+    *CACHE_DIR.lock().unwrap() = dir.clone();
+
+    if let Some(dir) = dir
+        && !COLLECTED.swap(true, Ordering::Relaxed)
+    {
+        match collect_garbage(&dir) {
+            Ok(removed) => {
+                if DEBUG.load(Ordering::Relaxed) && removed > 0 {
+                    write_console(
+                        ConsoleType::Debug,
+                        &t!(
+                            "icon_cache.collected",
+                            count = removed,
+                            path = dir.display()
+                        ),
+                    );
+                }
+            }
+            Err(e) => write_console_key(
+                ConsoleType::Warning,
+                "icon_cache.failed",
+                &t!("icon_cache.failed", path = dir.display(), error = e),
+            ),
+        }
+    }
+}
+
+/// 将可移动磁盘、网络位置上的图标复制到图标缓存目录
+///
+/// # 参数
+/// - `program`: 程序路径
+/// - `icon`: 快捷方式图标，`None` 表示使用程序的第一个图标
+/// - `link`: 快捷方式路径
+///
+/// # 返回值
+/// - 缓存后的图标；未设置图标缓存目录、图标不在可移动磁盘或网络位置上、缓存失败时返回原图标
+pub fn cache_icon(
+    program: &Path,
+    icon: Option<(String, i32)>,
+    link: &Path,
+) -> Option<(String, i32)> {
+    let Some(dir) = CACHE_DIR.lock().unwrap().clone() else {
+        return icon;
+    };
+    let (source, index) = icon
+        .clone()
+        .unwrap_or_else(|| (program.to_string_lossy().to_string(), 0));
+    let source = PathBuf::from(source);
+    if !is_removable_or_network(&source) {
+        return icon;
+    }
+
+    match store(&dir, &source, index, link) {
+        Ok(Some(path)) => Some((path.to_string_lossy().to_string(), 0)),
+        Ok(None) => icon,
+        Err(e) => {
+            write_console_key(
+                ConsoleType::Warning,
+                "icon_cache.failed",
+                &t!("icon_cache.failed", path = source.display(), error = e),
+            );
+            icon
+        }
+    }
+}
+
+/// 保存图标到缓存目录，并记录使用该图标的快捷方式
+///
+/// # 返回值
+/// - `Ok(Some(PathBuf))`: 缓存的图标路径
+/// - `Ok(None)`: 没有找到图标
+fn store(dir: &Path, source: &Path, index: i32, link: &Path) -> Result<Option<PathBuf>> {
+    let Some(data) = load_icon(source, index)? else {
+        return Ok(None);
+    };
+
+    // 以内容的哈希值命名，相同的图标只保存一份
+    fs::create_dir_all(dir)?;
+    let file_name = format!("{}.ico", content_hash(&data));
+    let path = dir.join(&file_name);
+    if !path.is_file() {
+        fs::write(&path, &data)?;
+    }
+
+    // 索引无法解析时不覆盖，避免丢失其他快捷方式的记录
+    let mut cache_index = load_index(dir)?.unwrap_or_default();
+    cache_index
+        .links
+        .insert(link.to_string_lossy().to_string(), file_name);
+    save_index(dir, &cache_index)?;
+    Ok(Some(path))
+}
+
+/// 读取图标为 `.ico` 文件内容
+///
+/// # 参数
+/// - `source`: `.ico` 文件，或包含图标资源的程序、动态链接库
+/// - `index`: 图标索引，负数表示图标资源标识
+///
+/// # 返回值
+/// - `Ok(Some(Vec<u8>))`: `.ico` 文件内容
+/// - `Ok(None)`: 没有该图标
+pub fn load_icon(source: &Path, index: i32) -> Result<Option<Vec<u8>>> {
+    if source
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ico"))
+    {
+        return Ok(Some(fs::read(source)?));
+    }
+
+    let groups = read_icon_groups(source)?;
+    let group = if index < 0 {
+        let id = ResourceName::Id(index.unsigned_abs() as u16);
+        groups.iter().find(|group| group.name == id)
+    } else {
+        groups.get(index as usize)
+    };
+    Ok(group.map(IconGroup::to_ico))
+}
+
+/// 清理过期的缓存：移除快捷方式已不存在的记录，删除没有被任何快捷方式使用的图标
+///
+/// 只删除以缓存命名规则（16 位十六进制哈希值）命名的图标，目录中的其他文件保留；
+/// 没有索引时不清理
+///
+/// # 参数
+/// - `dir`: 图标缓存目录
+///
+/// # 返回值
+/// - `Ok(usize)`: 删除的图标数量
+/// - `Err(...)`: 索引无法解析，未清理
+pub fn collect_garbage(dir: &Path) -> Result<usize> {
+    let Some(mut cache_index) = load_index(dir)? else {
+        return Ok(0);
+    };
+    cache_index.links.retain(|link, _| Path::new(link).exists());
+    save_index(dir, &cache_index)?;
+
+    let used: HashSet<&str> = cache_index.links.values().map(String::as_str).collect();
+    let mut removed = 0;
+    for entry in fs::read_dir(dir)?.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if is_cache_file_name(&file_name)
+            && !used.contains(file_name.as_str())
+            && fs::remove_file(entry.path()).is_ok()
+        {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// 是否为缓存保存的图标文件名，如 `0123456789abcdef.ico`
+fn is_cache_file_name(file_name: &str) -> bool {
+    file_name.strip_suffix(".ico").is_some_and(|stem| {
+        stem.len() == 16
+            && stem
+                .chars()
+                .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+    })
+}
+
+/// 读取图标缓存索引
+///
+/// # 返回值
+/// - `Ok(Some(CacheIndex))`: 缓存索引
+/// - `Ok(None)`: 索引文件不存在
+/// - `Err(...)`: 索引文件无法读取或解析
+fn load_index(dir: &Path) -> Result<Option<CacheIndex>> {
+    let path = dir.join(INDEX_FILE_NAME);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&fs::read_to_string(path)?)?))
+}
+
+/// 保存图标缓存索引
+fn save_index(dir: &Path, cache_index: &CacheIndex) -> Result<()> {
+    fs::write(
+        dir.join(INDEX_FILE_NAME),
+        serde_json::to_string_pretty(cache_index)?,
+    )?;
+    Ok(())
+}
+
+/// 计算内容的 SHA-256 哈希值（取前 8 字节，16 位十六进制字符串）
+fn content_hash(data: &[u8]) -> String {
+    Sha256::digest(data)[..8]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
@@ -0,0 +1,296 @@
+use crate::config::{config_path_string, escape_enabled, normalize_config_content, ConfigInfo};
+use crate::console::{uses_stderr, write_console, write_console_key, ConsoleType};
+use crate::report::CandidateRecord;
+use anyhow::{bail, Result};
+use rust_i18n::t;
+use std::cmp::Reverse;
+use std::fs;
+use std::io::{stderr, stdin, stdout, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 交互模式
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// 前两名候选程序的分数相差不超过该值时，视为无法确定主程序
+pub const AMBIGUOUS_MARGIN: i32 = 10;
+
+/// 对候选程序的选择
+#[derive(Debug, PartialEq)]
+pub enum Selection {
+    /// 使用自动识别的结果
+    Default,
+    /// 不创建快捷方式，并忽略该目录
+    Ignore,
+    /// 为选择的候选程序创建快捷方式（序号从 0 开始）
+    Programs(Vec<usize>),
+}
+
+/// 对目录的处理方式
+#[derive(Debug, PartialEq)]
+pub enum Decision {
+    /// 使用自动识别的结果
+    Default,
+    /// 忽略该目录
+    Ignore,
+    /// 为指定的程序创建快捷方式（第一个为主程序）
+    Programs(Vec<PathBuf>),
+}
+
+/// 开启交互模式
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// 是否为交互模式
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// 判断候选程序是否无法确定主程序（没有选出主程序，或前两名分数接近）
+///
+/// # 参数
+/// - `candidates`: 候选程序
+pub fn is_ambiguous(candidates: &[CandidateRecord]) -> bool {
+    if !candidates.iter().any(|candidate| candidate.selected) {
+        return !candidates.is_empty();
+    }
+    let mut scores: Vec<i32> = candidates.iter().map(|candidate| candidate.score).collect();
+    scores.sort_unstable_by(|a, b| b.cmp(a));
+    scores.len() > 1 && scores[0] - scores[1] <= AMBIGUOUS_MARGIN
+}
+
+/// 解析输入的选择
+///
+/// # 参数
+/// - `input`: 输入，如 `1`、`1,3`、`0`，直接回车使用自动识别的结果
+/// - `count`: 候选程序数量
+///
+/// # 返回值
+/// - `Some(Selection)`: 解析成功
+/// - `None`: 输入无效
+pub fn parse_selection(input: &str, count: usize) -> Option<Selection> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Some(Selection::Default);
+    }
+    if input == "0" {
+        return Some(Selection::Ignore);
+    }
+
+    let mut programs = Vec::new();
+    for item in input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|item| !item.is_empty())
+    {
+        let index: usize = item.parse().ok()?;
+        if index == 0 || index > count {
+            return None;
+        }
+        if !programs.contains(&(index - 1)) {
+            programs.push(index - 1);
+        }
+    }
+    if programs.is_empty() {
+        return None;
+    }
+    Some(Selection::Programs(programs))
+}
+
+/// 无法确定主程序时，列出候选程序并询问用户，将选择记录到配置文件中
+///
+/// # 参数
+/// - `dir`: 绿色软件目录
+/// - `candidates`: 候选程序
+/// - `config_path`: 配置文件路径
+///
+/// # 返回值
+/// - `Decision`: 对目录的处理方式（非交互模式或可以确定主程序时为 `Decision::Default`）
+pub fn decide(dir: &Path, candidates: &[CandidateRecord], config_path: Option<&Path>) -> Decision {
+    if !is_enabled() || !is_ambiguous(candidates) {
+        return Decision::Default;
+    }
+
+    // 按分数从高到低排列
+    let mut ranked: Vec<&CandidateRecord> = candidates.iter().collect();
+    ranked.sort_by_key(|candidate| Reverse(candidate.score));
+
+    // 以 JSON 格式输出时，候选列表与提示写入标准错误
+    let mut output: Box<dyn Write> = if uses_stderr() {
+        Box::new(stderr())
+    } else {
+        Box::new(stdout())
+    };
+
+    write_console(
+        ConsoleType::Info,
+        &t!("interactive.ambiguous", path = dir.display()),
+    );
+    for (index, candidate) in ranked.iter().enumerate() {
+        let details = candidate
+            .breakdown
+            .iter()
+            .map(|item| format!("{}{:+}", item.metric, item.score))
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(
+            output,
+            "    {}. {}{} ({}: {})",
+            index + 1,
+            candidate
+                .path
+                .strip_prefix(dir)
+                .unwrap_or(&candidate.path)
+                .display(),
+            if candidate.selected { " *" } else { "" },
+            candidate.score,
+            details
+        )
+        .ok();
+    }
+
+    let selection = loop {
+        write!(output, "  {} ", t!("interactive.prompt")).ok();
+        output.flush().ok();
+        let mut input = String::new();
+        if stdin().read_line(&mut input).unwrap_or(0) == 0 {
+            // 输入已结束，使用自动识别的结果
+            break Selection::Default;
+        }
+        match parse_selection(&input, ranked.len()) {
+            Some(selection) => break selection,
+            None => write_console(ConsoleType::Warning, &t!("interactive.invalid")),
+        }
+    };
+
+    let decision = match selection {
+        Selection::Default => return Decision::Default,
+        Selection::Ignore => Decision::Ignore,
+        Selection::Programs(indexes) => Decision::Programs(
+            indexes
+                .into_iter()
+                .map(|index| ranked[index].path.clone())
+                .collect(),
+        ),
+    };
+
+    // 记录到配置文件中，下次运行时自动处理
+    let Some(config_path) = config_path else {
+        write_console_key(
+            ConsoleType::Warning,
+            "interactive.no_config",
+            &t!("interactive.no_config"),
+        );
+        return decision;
+    };
+    match record_decision(config_path, dir, &decision) {
+        Ok(()) => write_console(
+            ConsoleType::Success,
+            &t!("interactive.recorded", path = config_path.display()),
+        ),
+        Err(e) => write_console_key(
+            ConsoleType::Warning,
+            "interactive.record_failed",
+            &t!(
+                "interactive.record_failed",
+                path = config_path.display(),
+                error = e
+            ),
+        ),
+    }
+    decision
+}
+
+/// 将处理方式记录到配置文件中
+fn record_decision(config_path: &Path, dir: &Path, decision: &Decision) -> Result<()> {
+    let content = fs::read_to_string(config_path)?;
+    let content = match decision {
+        Decision::Default => return Ok(()),
+        Decision::Ignore => add_ignore_rule(&content, &dir.to_string_lossy())?,
+        Decision::Programs(programs) => add_shortcut_entries(&content, programs),
+    };
+    fs::write(config_path, content)?;
+    Ok(())
+}
+
+/// 配置文件使用的换行符
+fn line_ending(content: &str) -> &'static str {
+    if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+/// 在配置文件末尾追加 `[[shortcut]]` 项（保留原有的注释与格式）
+///
+/// # 参数
+/// - `content`: 配置文件内容
+/// - `programs`: 程序路径
+pub fn add_shortcut_entries(content: &str, programs: &[PathBuf]) -> String {
+    let newline = line_ending(content);
+    let mut content = content.to_string();
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push_str(newline);
+    }
+    let enable_escape = escape_enabled(&content);
+    for program in programs {
+        let exec = config_path_string(&program.to_string_lossy(), enable_escape);
+        content.push_str(&format!(
+            "{newline}[[shortcut]]{newline}exec = {}{newline}",
+            exec
+        ));
+    }
+    content
+}
+
+/// 在配置文件的 `ignore` 列表中添加规则（保留原有的注释与格式）
+///
+/// # 参数
+/// - `content`: 配置文件内容
+/// - `rule`: 忽略规则
+///
+/// # 返回值
+/// - `Ok(String)`: 修改后的配置文件内容
+/// - `Err(...)`: 无法识别 `ignore` 列表的格式
+pub fn add_ignore_rule(content: &str, rule: &str) -> Result<String> {
+    let newline = line_ending(content);
+    let value = config_path_string(rule, escape_enabled(content));
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+
+    // 仅查找第一个表之前的顶层 `ignore` 项
+    let header = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .unwrap_or(lines.len());
+    let existing = lines[..header].iter().position(|line| {
+        line.trim_start()
+            .strip_prefix("ignore")
+            .is_some_and(|rest| rest.trim_start().starts_with('='))
+    });
+    match existing {
+        Some(index) => {
+            let line = &lines[index];
+            let Some(start) = line
+                .find('=')
+                .and_then(|eq| line[eq..].find('[').map(|bracket| eq + bracket + 1))
+            else {
+                bail!("unsupported ignore list");
+            };
+            lines[index] = format!("{}{}, {}", &line[..start], value, &line[start..]);
+        }
+        None => lines.insert(header, format!("ignore = [{}]", value)),
+    }
+
+    let mut result = lines.join(newline);
+    if content.ends_with('\n') || content.is_empty() {
+        result.push_str(newline);
+    }
+
+    // 按读取配置文件的方式校验修改后的内容
+    let config: ConfigInfo = toml::from_str(&normalize_config_content(&result))?;
+    if !config.ignore.iter().any(|item| item == rule) {
+        bail!("unsupported ignore list");
+    }
+    Ok(result)
+}
@@ -0,0 +1,114 @@
+use crate::utils::resolve_link_path;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 本次运行中创建的快捷方式
+#[derive(Clone, Debug)]
+pub struct JournalEntry {
+    /// 扫描目录
+    pub root: PathBuf,
+    /// 程序路径
+    pub program: PathBuf,
+    /// 快捷方式路径
+    pub shortcut: PathBuf,
+    /// 程序比较键（解析链接后的真实路径，记录时计算）
+    program_key: String,
+}
+
+/// 快捷方式记录（多个扫描目录共用），用于检测扫描目录之间的冲突
+#[derive(Default)]
+pub struct Journal {
+    entries: Mutex<Vec<JournalEntry>>,
+}
+
+impl Journal {
+    /// 创建快捷方式记录
+    pub fn new() -> Self {
+        Journal::default()
+    }
+
+    /// 查找其他扫描目录中已创建快捷方式的同一程序
+    ///
+    /// 通过链接访问的程序按解析后的真实路径比较
+    ///
+    /// # 参数
+    /// - `root`: 当前扫描目录
+    /// - `program`: 程序路径
+    ///
+    /// # 返回值
+    /// - `Some(JournalEntry)`: 程序已由其他扫描目录创建快捷方式
+    /// - `None`: 没有冲突
+    pub fn find_program(&self, root: &Path, program: &Path) -> Option<JournalEntry> {
+        let key = program_key(program);
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|entry| entry.root != root && entry.program_key == key)
+            .cloned()
+    }
+
+    /// 记录创建的快捷方式
+    ///
+    /// # 参数
+    /// - `root`: 扫描目录
+    /// - `program`: 程序路径
+    /// - `shortcut`: 快捷方式路径
+    ///
+    /// # 返回值
+    /// - `Some(JournalEntry)`: 其他扫描目录已占用同名快捷方式（当前快捷方式因此被重命名）
+    /// - `None`: 没有冲突
+    pub fn record(&self, root: &Path, program: &Path, shortcut: &Path) -> Option<JournalEntry> {
+        let program_key = program_key(program);
+        let mut entries = self.entries.lock().unwrap();
+        let key = path_key(&base_shortcut_path(shortcut));
+        let conflict = entries
+            .iter()
+            .find(|entry| {
+                entry.root != root
+                    && entry.shortcut != shortcut
+                    && path_key(&base_shortcut_path(&entry.shortcut)) == key
+            })
+            .cloned();
+        entries.push(JournalEntry {
+            root: root.to_path_buf(),
+            program: program.to_path_buf(),
+            shortcut: shortcut.to_path_buf(),
+            program_key,
+        });
+        conflict
+    }
+
+    /// 获取全部记录
+    pub fn entries(&self) -> Vec<JournalEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+}
+
+/// 路径比较键（不区分大小写）
+fn path_key(path: &Path) -> String {
+    path.to_string_lossy().to_lowercase()
+}
+
+/// 程序比较键（通过链接访问的程序按解析后的真实路径比较）
+fn program_key(program: &Path) -> String {
+    path_key(&resolve_link_path(program))
+}
+
+/// 去除同名快捷方式的序号后缀，如 `App (2).lnk` -> `App.lnk`
+fn base_shortcut_path(shortcut: &Path) -> PathBuf {
+    let stem = shortcut
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let base = match stem.rsplit_once(" (") {
+        Some((base, n))
+            if n.strip_suffix(')')
+                .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit())) =>
+        {
+            base.to_string()
+        }
+        _ => stem,
+    };
+    shortcut.with_file_name(format!("{}.lnk", base))
+}
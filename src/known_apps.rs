@@ -0,0 +1,173 @@
+use crate::utils::{get_exe_company_name, get_exe_original_filename};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+/// 内置的常见程序信息
+const KNOWN_APPS: &str = include_str!("../resource/known_apps.toml");
+
+/// 向上查找辅助程序所属主程序的最大层数（如 `Application\<版本>\` 下的辅助程序）
+const MAX_HELPER_DEPTH: usize = 2;
+
+/// 禁用内置的常见程序信息
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// 解析后的常见程序信息
+static DATABASE: OnceLock<KnownApps> = OnceLock::new();
+
+/// 常见程序信息
+#[derive(Deserialize, Debug, Default)]
+pub struct KnownApps {
+    /// 版本
+    #[serde(default)]
+    pub version: u32,
+    /// 程序列表
+    #[serde(default)]
+    pub app: Vec<KnownApp>,
+}
+
+/// 常见程序
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct KnownApp {
+    /// 主程序文件名（靠前的优先）
+    pub exe: Vec<String>,
+    /// 原始文件名（程序被重命名时匹配）
+    #[serde(default)]
+    pub original_filename: Option<String>,
+    /// 公司名称需包含的值
+    #[serde(default)]
+    pub company: Option<String>,
+    /// 辅助程序文件名
+    #[serde(default)]
+    pub helpers: Vec<String>,
+    /// 分类
+    #[serde(default)]
+    pub category: Option<String>,
+    /// 各语言的名称
+    #[serde(default)]
+    pub name: HashMap<String, String>,
+}
+
+impl KnownApp {
+    /// 当前语言的名称（没有时依次使用英文名称、任意名称）
+    pub fn localized_name(&self) -> Option<String> {
+        let locale = rust_i18n::locale();
+        self.name
+            .get(&*locale)
+            .or_else(|| self.name.get("en"))
+            .or_else(|| self.name.values().next())
+            .cloned()
+    }
+
+    /// 主程序在同级程序中的优先级（0 为最优先）
+    ///
+    /// # 参数
+    /// - `program_path`: 程序路径
+    pub fn exe_rank(&self, program_path: &Path) -> Option<usize> {
+        let file_name = program_path.file_name()?.to_string_lossy();
+        self.exe
+            .iter()
+            .position(|exe| exe.eq_ignore_ascii_case(&file_name))
+    }
+
+    /// 判断程序的公司名称是否符合（未指定公司名称时总是成立）
+    fn company_matches(&self, program_path: &Path) -> bool {
+        self.company.as_ref().is_none_or(|company| {
+            get_exe_company_name(program_path)
+                .ok()
+                .flatten()
+                .is_some_and(|name| name.to_lowercase().contains(&company.to_lowercase()))
+        })
+    }
+}
+
+impl KnownApps {
+    /// 解析常见程序信息
+    ///
+    /// # 参数
+    /// - `content`: TOML 格式的常见程序信息
+    pub fn parse(content: &str) -> Result<KnownApps, toml::de::Error> {
+        toml::from_str(content)
+    }
+
+    /// 查找程序对应的常见程序（先按文件名，再按原始文件名匹配）
+    ///
+    /// # 参数
+    /// - `program_path`: 程序路径
+    pub fn find(&self, program_path: &Path) -> Option<&KnownApp> {
+        if let Some(app) = self
+            .app
+            .iter()
+            .find(|app| app.exe_rank(program_path).is_some() && app.company_matches(program_path))
+        {
+            return Some(app);
+        }
+
+        // 仅在存在指定原始文件名的程序时读取版本信息
+        if !self.app.iter().any(|app| app.original_filename.is_some()) {
+            return None;
+        }
+        let original = get_exe_original_filename(program_path).ok().flatten()?;
+        self.app.iter().find(|app| {
+            app.original_filename
+                .as_ref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(original.trim()))
+                && app.company_matches(program_path)
+        })
+    }
+
+    /// 判断程序是否为常见程序的辅助程序（所在目录或上级目录中存在对应的主程序）
+    ///
+    /// # 参数
+    /// - `program_path`: 程序路径
+    pub fn is_helper(&self, program_path: &Path) -> bool {
+        let Some(file_name) = program_path.file_name().map(|s| s.to_string_lossy()) else {
+            return false;
+        };
+        self.app
+            .iter()
+            .filter(|app| {
+                app.helpers
+                    .iter()
+                    .any(|h| h.eq_ignore_ascii_case(&file_name))
+            })
+            .any(|app| {
+                program_path
+                    .ancestors()
+                    .skip(1)
+                    .take(MAX_HELPER_DEPTH + 1)
+                    .any(|dir| app.exe.iter().any(|exe| dir.join(exe).is_file()))
+            })
+    }
+}
+
+/// 禁用内置的常见程序信息
+pub fn disable() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// 内置的常见程序信息（禁用时返回 `None`）
+pub fn database() -> Option<&'static KnownApps> {
+    if DISABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+    Some(DATABASE.get_or_init(|| KnownApps::parse(KNOWN_APPS).unwrap_or_default()))
+}
+
+/// 查找程序对应的常见程序
+///
+/// # 参数
+/// - `program_path`: 程序路径
+pub fn find(program_path: &Path) -> Option<&'static KnownApp> {
+    database()?.find(program_path)
+}
+
+/// 判断程序是否为常见程序的辅助程序
+///
+/// # 参数
+/// - `program_path`: 程序路径
+pub fn is_helper(program_path: &Path) -> bool {
+    database().is_some_and(|db| db.is_helper(program_path))
+}
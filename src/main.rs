@@ -3,23 +3,53 @@
 // 禁用未使用代码警告
 #![allow(dead_code)]
 
+mod authenticode;
 mod cli;
 mod config;
 mod console;
+mod export;
+mod icon;
+mod icon_cache;
+mod interactive;
+mod journal;
+mod known_apps;
+mod manifest;
+mod overrides;
+mod pe_resource;
+mod portable;
+mod report;
+mod rules;
 mod template;
+mod template_parser;
 mod utils;
+mod version_info;
 
 #[cfg(test)]
 mod test;
 
-use crate::config::{ConfigInfo, Lnk, Template, DEFAULT_NAME_TEMPLATE};
-use crate::console::{write_console, ConsoleType};
-use crate::template::process_template;
+use crate::cli::OutputFormat;
+use crate::config::{
+    Arch, ConfigInfo, Detection, DirKind, FollowLinks, InstallerPolicy, Lnk, RootConfig,
+    ScanLimits, Template, DEFAULT_NAME_TEMPLATE,
+};
+use crate::console::{use_stderr, write_console, write_console_key, ConsoleType};
+use crate::interactive::Decision;
+use crate::journal::Journal;
+use crate::overrides::{DirOverrides, OVERRIDE_FILE_NAMES};
+use crate::portable::PortableAppInfo;
+use crate::report::{CandidateRecord, ScoreItem, ShortcutRecord};
+use crate::rules::{IgnoreRules, IGNORE_FILE_NAME};
+use crate::authenticode::Signature;
+use crate::icon::{read_icon_groups, LARGE_ICON_SIZE};
+use crate::manifest::Manifest;
+use crate::template::ProgramContext;
+use crate::version_info::preferred_langs;
 use crate::utils::{
-    create_shortcut, exe_has_signature, get_exe_description, get_native_arch, get_program_arch,
-    get_shortcut_target, has_icon_in_program, is_gui_program, is_running_under_wow64,
-    launched_from_explorer, matches_glob, normalize_app_name, parse_hotkey, parse_icon_spec,
-    replace_ignore_case, resolve_relative_path, validate_shortcut_name_for_config,
+    create_shortcut, detect_installer, detect_runtime, get_dotnet_main_assembly, is_runtime_helper,
+    AppRuntime, get_native_arch_kind, ArchVariant, ArchVariants, get_file_id, is_cloud_placeholder,
+    resolve_link_path, get_exe_description, get_program_arch, get_shortcut_target, is_gui_program,
+    is_running_under_wow64, launched_from_explorer, matches_glob, normalize_app_name, parse_hotkey,
+    parse_icon_spec, replace_ignore_case, resolve_relative_path, validate_shortcut_name_for_config,
 };
 use anyhow::{anyhow, Result};
 use clap::Parser;
@@ -30,19 +60,28 @@ use std::os::windows::process::CommandExt;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::{env, fs};
 use sys_locale::get_locale;
 use walkdir::WalkDir;
-use windows::Win32::System::SystemInformation::{
-    PROCESSOR_ARCHITECTURE_AMD64, PROCESSOR_ARCHITECTURE_ARM64, PROCESSOR_ARCHITECTURE_INTEL,
-};
 
 /// 调试模式
 static DEBUG: AtomicBool = AtomicBool::new(false);
 
+/// 识别过程说明模式
+static EXPLAIN: AtomicBool = AtomicBool::new(false);
+
+/// 以 JSON 格式输出
+static JSON_FORMAT: AtomicBool = AtomicBool::new(false);
+
+/// 开始运行的时间（用于扫描时间限制）
+static SCAN_START: OnceLock<Instant> = OnceLock::new();
+
+/// 已超出扫描时间限制
+static TIME_BUDGET_EXCEEDED: AtomicBool = AtomicBool::new(false);
+
 // 国际化
 rust_i18n::i18n!("locales");
 
@@ -64,238 +103,531 @@ fn main() -> Result<()> {
 
     // 处理命令行
     let cli = crate::cli::Cli::parse();
+    SCAN_START.get_or_init(Instant::now);
     if cli.debug {
         DEBUG.store(true, Ordering::Relaxed);
     }
+    if cli.explain {
+        EXPLAIN.store(true, Ordering::Relaxed);
+    }
+    if cli.format == OutputFormat::Json {
+        JSON_FORMAT.store(true, Ordering::Relaxed);
+        use_stderr();
+    }
+    if cli.report.is_some() || cli.export_config.is_some() || cli.format == OutputFormat::Json {
+        report::enable();
+    }
+    if cli.interactive {
+        interactive::enable();
+    }
+    if cli.no_known_apps {
+        known_apps::disable();
+    }
+
+    // 列出程序中的图标
+    if let Some(program) = &cli.icons {
+        return icon::print_icons(program, cli.icon_output.as_deref());
+    }
+
+    // 扫描选项
+    let options = ScanOptions {
+        only_match: cli.only_match,
+        create_dir: cli.create_dir,
+        install_script: cli.install,
+        install_parallel: cli.install_parallel,
+        start: cli.start,
+        list_mode: cli.list,
+        use_filename: cli.use_filename,
+        score_ratio: cli.score_ratio,
+    };
+
+    // 读取配置文件（各扫描目录共用）
+    let config_info = match cli
+        .config
+        .as_deref()
+        .map(ConfigInfo::parse_config_file)
+        .transpose()
+    {
+        Ok(config) => config,
+        Err(e) => {
+            write_console_key(
+                ConsoleType::Error,
+                "config.parse_failed",
+                &format!("{}: {}", &t!("config.parse_failed"), e),
+            );
+            return Err(anyhow!("Configuration file parsing failed"));
+        }
+    };
+
+    // 多个扫描目录模式：命令行参数、配置文件中的 [[root]]、命令行 --root
+    let config_roots = config_info
+        .as_ref()
+        .map(|config| config.root.clone())
+        .unwrap_or_default();
+    if !cli.root.is_empty() || (cli.targetPath.is_none() && !config_roots.is_empty()) {
+        let mut roots = Vec::new();
+        if let Some(target_path) = &cli.targetPath {
+            roots.push(RootConfig {
+                path: target_path.to_string_lossy().to_string(),
+                ..Default::default()
+            });
+        }
+        roots.extend(config_roots);
+        roots.extend(cli.root.iter().cloned());
+
+        let journal = Journal::new();
+        for root in &roots {
+            let root_path = Path::new(&root.path);
+            if !root_path.is_dir() {
+                write_console_key(
+                    ConsoleType::Warning,
+                    "journal.root_not_found",
+                    &t!("journal.root_not_found", path = root_path.display()),
+                );
+                continue;
+            }
+            if !cli.list {
+                write_console(
+                    ConsoleType::Info,
+                    &t!("journal.root", path = root_path.display()),
+                );
+            }
+            report::record_root(root_path);
+
+            auto_shortcut(
+                &ScanRoot {
+                    path: root_path,
+                    lnk_path: root
+                        .dest
+                        .as_deref()
+                        .map(Path::new)
+                        .or(cli.lnkPath.as_deref()),
+                    config: Some(root),
+                    journal: &journal,
+                },
+                cli.config.as_deref(),
+                config_info.as_ref(),
+                ScanOptions {
+                    create_dir: root.create_dir.unwrap_or(cli.create_dir),
+                    ..options
+                },
+            )?;
+        }
+
+        if !cli.list {
+            write_console(
+                ConsoleType::Info,
+                &t!(
+                    "journal.summary",
+                    count = journal.entries().len(),
+                    roots = roots.len()
+                ),
+            );
+        }
+        return write_report(cli.report.as_deref(), cli.export_config.as_deref());
+    }
 
     // 配置文件模式
-    if cli.config.is_some() && cli.targetPath.is_none() && cli.lnkPath.is_none() {
-        let cfg = cli.config.unwrap();
+    if cli.targetPath.is_none()
+        && cli.lnkPath.is_none()
+        && let Some(config_info) = config_info
+    {
         config_shortcut(
-            cfg,
+            config_info,
             cli.install,
             cli.install_parallel,
             cli.start,
             cli.use_filename,
         )?;
-        return Ok(());
+        return write_report(cli.report.as_deref(), cli.export_config.as_deref());
     }
 
     // 自动搜索模式
+    let target_path = cli.targetPath.unwrap();
+    report::record_root(&target_path);
     auto_shortcut(
-        &cli.targetPath.unwrap(),
-        cli.lnkPath.as_deref(),
+        &ScanRoot {
+            path: &target_path,
+            lnk_path: cli.lnkPath.as_deref(),
+            config: None,
+            journal: &Journal::new(),
+        },
         cli.config.as_deref(),
-        cli.only_match,
-        cli.create_dir,
-        cli.install,
-        cli.install_parallel,
-        cli.start,
-        cli.list,
-        cli.use_filename,
-        cli.score_ratio,
+        config_info.as_ref(),
+        options,
     )?;
+    write_report(cli.report.as_deref(), cli.export_config.as_deref())
+}
+
+/// 保存扫描报告与导出的配置文件，以 JSON 格式输出时同时输出到控制台
+///
+/// # 参数
+/// - `report_path`: 报告文件路径
+/// - `export_path`: 导出的配置文件路径
+fn write_report(report_path: Option<&Path>, export_path: Option<&Path>) -> Result<()> {
+    if let Some(path) = report_path {
+        report::save(path)?;
+    }
+    if let Some(path) = export_path {
+        export::save(path)?;
+        write_console(
+            ConsoleType::Success,
+            &t!("export.saved", path = path.display()),
+        );
+    }
+    if JSON_FORMAT.load(Ordering::Relaxed) {
+        println!("{}", report::to_json()?);
+    }
     Ok(())
 }
 
+/// 列出程序路径（以 JSON 格式输出时仅记录到扫描报告中）
+///
+/// # 参数
+/// - `program`: 程序路径
+/// - `installer`: 安装程序类型
+fn list_program(program: &Path, installer: Option<&str>) {
+    report::record_program(program, installer);
+    if JSON_FORMAT.load(Ordering::Relaxed) {
+        return;
+    }
+    match installer {
+        Some(kind) => println!("[{}] {}", kind, program.display()),
+        None => println!("{}", program.display()),
+    }
+}
+
+/// 扫描目录
+#[derive(Clone, Copy)]
+pub struct ScanRoot<'a> {
+    /// 扫描目录路径
+    pub path: &'a Path,
+    /// 快捷方式路径
+    pub lnk_path: Option<&'a Path>,
+    /// 扫描目录配置（多个扫描目录模式）
+    pub config: Option<&'a RootConfig>,
+    /// 快捷方式记录（多个扫描目录共用）
+    pub journal: &'a Journal,
+}
+
+/// 自动创建快捷方式的选项（来自命令行）
+#[derive(Clone, Copy)]
+pub struct ScanOptions {
+    /// 是否为仅配置文件模式
+    pub only_match: bool,
+    /// 是否创建目录
+    pub create_dir: bool,
+    /// 是否运行脚本
+    pub install_script: bool,
+    /// 是否并行运行脚本
+    pub install_parallel: bool,
+    /// 是否运行程序
+    pub start: bool,
+    /// 是否仅列出快捷方式路径
+    pub list_mode: bool,
+    /// 使用原始文件名
+    pub use_filename: bool,
+    /// 评分阈值百分比
+    pub score_ratio: Option<f32>,
+}
+
 /// 自动创建快捷方式
 ///
 /// # 参数
-/// - `target_path`: 程序路径
-/// - `lnk_path`: 快捷方式路径
+/// - `root`: 扫描目录
 /// - `config_path`: 配置文件路径
-/// - `only_match`: 是否为仅配置文件模式
-/// - `create_dir`: 是否创建目录
-/// - `install_script`: 是否运行脚本
-/// - `install_parallel`: 是否并行运行脚本
-/// - `use_filename`: 使用原始文件名
-/// - `list_mode`: 是否仅列出快捷方式路径
+/// - `config`: 配置文件信息（由调用方读取）
+/// - `options`: 扫描选项
 ///
 /// # 返回值
 /// - `Ok(())`: 创建成功
 /// - `Err(...)`：失败则返回错误
 pub fn auto_shortcut(
-    target_path: &Path,
-    lnk_path: Option<&Path>,
+    root: &ScanRoot,
     config_path: Option<&Path>,
-    only_match: bool,
-    create_dir: bool,
-    install_script: bool,
-    install_parallel: bool,
-    start: bool,
-    list_mode: bool,
-    use_filename: bool,
-    score_ratio: Option<f32>,
+    config: Option<&ConfigInfo>,
+    options: ScanOptions,
 ) -> Result<()> {
+    let ScanRoot {
+        path: target_path,
+        lnk_path,
+        config: root_config,
+        journal,
+    } = *root;
+    let ScanOptions {
+        only_match,
+        create_dir,
+        install_script,
+        install_parallel,
+        start,
+        list_mode,
+        use_filename,
+        score_ratio,
+    } = options;
+
     // 评分阈值百分比
     let mut score_ratio = score_ratio.unwrap_or(0.3);
 
-    // 读取配置文件信息
+    // 配置文件信息
     let mut config_info = None;
     let mut only_match = only_match;
     let mut use_filename = use_filename;
     let mut install_script = install_script;
     let mut install_parallel = install_parallel;
 
-    if let Some(config) = config_path {
-        match ConfigInfo::parse_config_file(config) {
-            Ok(mut config) => {
-                if config.only_match {
-                    only_match = true;
-                }
-
-                if config.use_filename {
-                    use_filename = true;
-                }
+    if let Some(config) = config {
+        // 扫描目录配置
+        let mut config = match root_config {
+            Some(root) => root.apply(config),
+            None => config.clone(),
+        };
 
-                if config.install {
-                    install_script = true;
-                }
+        icon_cache::configure(config.icon_cache.as_deref());
 
-                if config.install_parallel {
-                    install_parallel = true;
-                }
+        if config.only_match {
+            only_match = true;
+        }
 
-                // 判断评分阈值是否合法
-                if let Some(ratio) = config.score_ratio {
-                    if ratio > 1.0 {
-                        write_console(
-                            ConsoleType::Warning,
-                            &t!("config.invalid_ratio", ratio = ratio),
-                        );
-                        config.score_ratio = None;
-                    }
-                }
+        if config.use_filename {
+            use_filename = true;
+        }
 
-                // 指定评分阈值百分比
-                if let Some(ratio) = config.score_ratio {
-                    score_ratio = ratio;
-                }
+        if config.install {
+            install_script = true;
+        }
 
-                // 验证配置文件中快捷方式名称是否合法
-                for ln in &config.shortcut {
-                    if let Some(ref provided_name) = ln.name {
-                        if !validate_shortcut_name_for_config(provided_name) {
-                            write_console(
-                                ConsoleType::Warning,
-                                &t!("config.invalid_name", name = provided_name),
-                            );
-                        }
-                    }
-                }
+        if config.install_parallel {
+            install_parallel = true;
+        }
 
-                config_info = Some(config.clone());
-            }
-            Err(e) => {
-                write_console(
-                    ConsoleType::Error,
-                    &format!("{}: {}", &t!("config.parse_failed"), e),
+        // 判断评分阈值是否合法
+        if let Some(ratio) = config.score_ratio {
+            if ratio > 1.0 {
+                write_console_key(
+                    ConsoleType::Warning,
+                    "config.invalid_ratio",
+                    &t!("config.invalid_ratio", ratio = ratio),
                 );
-                return Err(anyhow!("Configuration file parsing failed"));
+                config.score_ratio = None;
             }
         }
+
+        // 指定评分阈值百分比
+        if let Some(ratio) = config.score_ratio {
+            score_ratio = ratio;
+        }
+
+        // 验证配置文件中快捷方式名称是否合法
+        for ln in &config.shortcut {
+            if let Some(ref provided_name) = ln.name {
+                if !validate_shortcut_name_for_config(provided_name) {
+                    write_console_key(
+                        ConsoleType::Warning,
+                        "config.invalid_name",
+                        &t!("config.invalid_name", name = provided_name),
+                    );
+                }
+            }
+        }
+
+        config_info = Some(config);
     }
 
-    let identified_app_roots: Arc<Mutex<HashSet<PathBuf>>> = Arc::new(Mutex::new(HashSet::new()));
+    // 目录结构识别规则
+    let detection = config_info
+        .as_ref()
+        .map(|config| config.detection.clone())
+        .unwrap_or_default();
+
+    // 安装程序处理策略
+    let installers = config_info
+        .as_ref()
+        .map(|config| config.installers)
+        .unwrap_or_default();
 
-    // 排除系统目录
-    const SYSTEM_EXCLUDED_DIRS: &[&str] = &[
-        "$RECYCLE.BIN",
-        "System Volume Information",
-        "Recovery",
-        "Config.Msi",
-        "MSOCache",
-    ];
+    // 架构变体处理策略
+    let prefer_arch = config_info.as_ref().and_then(|config| config.prefer_arch);
+    let keep_arch_variants = config_info
+        .as_ref()
+        .is_some_and(|config| config.keep_arch_variants);
+
+    // 扫描限制
+    let limits = config_info
+        .as_ref()
+        .map(|config| config.limits.clone())
+        .unwrap_or_default();
+    let limits = &limits;
+    let deadline = limits
+        .time_budget
+        .map(|secs| *SCAN_START.get_or_init(Instant::now) + Duration::from_secs(secs));
+
+    // 符号链接、目录联接处理策略
+    let follow_links = config_info
+        .as_ref()
+        .map(|config| config.follow_links)
+        .unwrap_or_default();
+    let prefer_link_path = config_info
+        .as_ref()
+        .is_none_or(|config| config.prefer_link_path);
+    let canonical_root = &target_path
+        .canonicalize()
+        .unwrap_or_else(|_| target_path.to_path_buf());
+
+    // 快捷方式目标：链接路径或解析后的真实路径
+    let shortcut_target = |path: &Path| {
+        if prefer_link_path {
+            path.to_path_buf()
+        } else {
+            resolve_link_path(path)
+        }
+    };
 
-    let mut all_excluded = Vec::new();
-    all_excluded.extend(SYSTEM_EXCLUDED_DIRS.iter().map(|s| s.to_string()));
+    let identified_app_roots: Arc<Mutex<HashSet<PathBuf>>> = Arc::new(Mutex::new(HashSet::new()));
+    // 已处理的混合目录（根目录中的程序已按单文件程序处理）
+    let mut hybrid_dirs: HashSet<PathBuf> = HashSet::new();
 
-    // 配置文件自身路径
+    // 忽略、包含规则（系统目录、配置文件自身、配置文件中的规则）
+    let mut rules = IgnoreRules::new(target_path, config_info.as_ref());
     if let Some(config) = config_path {
-        all_excluded.push(config.to_string_lossy().to_string());
+        rules.add_path(config);
     }
+    let rules = &rules;
 
-    // 配置文件中的排除路径
-    if let Some(config) = &config_info {
-        all_excluded.extend(config.ignore.clone());
-    }
+    // 扫描中发现的目录配置（合并到全局配置上）
+    let overrides = &DirOverrides::new(target_path);
+    let global_config = config_info.as_ref();
+
+    // 架构变体（每个目录只读取一次）
+    let arch_variants = &ArchVariants::new();
 
     // 主循环: 遍历所有文件（包括子目录）
-    for entry_result in WalkDir::new(target_path).into_iter().filter_entry({
-        let roots_for_filter = Arc::clone(&identified_app_roots);
-        let config_info = config_info.clone();
+    for entry_result in WalkDir::new(target_path)
+        .follow_links(follow_links != FollowLinks::Never)
+        .into_iter()
+        .filter_entry({
+            let roots_for_filter = Arc::clone(&identified_app_roots);
+            // 已访问目录的唯一标识，用于检测链接循环
+            let mut visited_dirs = HashSet::new();
+
+            move |entry| {
+                let path = entry.path();
+
+                // 排除: 忽略规则
+                if rules.is_ignored(path, entry.file_type().is_dir()) {
+                    if entry.file_type().is_dir() {
+                        report::record_directory(path, "skipped", "ignore_rule");
+                    }
+                    if DEBUG.load(Ordering::Relaxed) {
+                        write_console(
+                            ConsoleType::Debug,
+                            &t!("scan.ignore", path = path.display()),
+                        );
+                    }
+                    return false;
+                }
 
-        move |entry| {
-            let path = entry.path();
+                // 排除: 链接指向扫描目录外部
+                if follow_links == FollowLinks::WithinRoot
+                    && entry.path_is_symlink()
+                    && !path
+                        .canonicalize()
+                        .is_ok_and(|target| target.starts_with(canonical_root))
+                {
+                    report::record_directory(path, "skipped", "link_outside_root");
+                    if explain_enabled() {
+                        write_console(
+                            ConsoleType::Explain,
+                            &t!("link.outside_root", path = path.display()),
+                        );
+                    }
+                    return false;
+                }
 
-            // 排除：特殊目录
-            if entry.file_type().is_dir() {
-                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                    if SYSTEM_EXCLUDED_DIRS
-                        .iter()
-                        .any(|&ex| ex.eq_ignore_ascii_case(name))
-                    {
-                        if DEBUG.load(Ordering::Relaxed) {
-                            write_console(
-                                ConsoleType::Debug,
-                                &t!("scan.ignore", path = path.display()),
-                            );
-                        }
-                        return false;
+                // 排除: 通过链接重复访问的目录（链接循环）
+                if follow_links != FollowLinks::Never
+                    && entry.file_type().is_dir()
+                    && let Ok(id) = get_file_id(path)
+                    && !visited_dirs.insert(id)
+                {
+                    report::record_directory(path, "skipped", "link_cycle");
+                    if explain_enabled() {
+                        write_console(
+                            ConsoleType::Explain,
+                            &t!("link.cycle", path = path.display()),
+                        );
                     }
+                    return false;
                 }
-            }
 
-            // 排除: 自定义排除
-            if let Some(cfg) = &config_info {
-                let file_name = path.file_name().map(|n| n.to_string_lossy().to_lowercase());
-
-                // 检查是否需要忽略此文件或目录
-                if file_name.is_some_and(|name| {
-                    cfg.ignore.iter().any(|kw| {
-                        // 尝试将关键词解析为绝对路径
-                        if let Ok(keyword_path) = PathBuf::from(kw).canonicalize() {
-                            // 对当前完整路径进行规范化并比较
-                            if let Ok(current_path) = path.canonicalize() {
-                                current_path == keyword_path
-                            } else {
-                                // 如果无法规范化当前路径，回退到文件名匹配
-                                name.contains(kw.to_lowercase().as_str())
-                            }
-                        } else {
-                            // 不是绝对路径，使用文件名包含匹配
-                            name.contains(kw.to_lowercase().as_str())
-                        }
-                    })
-                }) {
-                    if DEBUG.load(Ordering::Relaxed) {
+                // 排除: 目录配置中忽略的目录
+                if entry.file_type().is_dir()
+                    && overrides
+                        .load(path)
+                        .is_some_and(|dir_override| dir_override.config.ignore)
+                {
+                    report::record_directory(path, "skipped", "override_ignore");
+                    if explain_enabled() {
                         write_console(
-                            ConsoleType::Debug,
-                            &t!("scan.ignore", path = path.display()),
+                            ConsoleType::Explain,
+                            &t!("override.ignored", path = path.display()),
                         );
                     }
                     return false;
                 }
-            }
 
-            // 剪枝算法
-            let roots = roots_for_filter.lock().unwrap();
-            if roots.iter().any(|root| path.starts_with(root)) {
-                if DEBUG.load(Ordering::Relaxed) {
-                    write_console(ConsoleType::Debug, &t!("scan.prune", path = path.display()));
+                // 剪枝算法
+                let roots = roots_for_filter.lock().unwrap();
+                if roots.iter().any(|root| path.starts_with(root)) {
+                    if entry.file_type().is_dir() {
+                        report::record_directory(path, "skipped", "pruned");
+                    }
+                    if DEBUG.load(Ordering::Relaxed) {
+                        write_console(ConsoleType::Debug, &t!("scan.prune", path = path.display()));
+                    }
+                    return false;
                 }
-                return false;
-            }
+                drop(roots);
 
-            true
-        }
-    }) {
+                // 排除: 超出扫描限制的目录
+                if entry.file_type().is_dir() {
+                    let limit = if deadline.is_some_and(|deadline| Instant::now() > deadline) {
+                        if !TIME_BUDGET_EXCEEDED.swap(true, Ordering::Relaxed) {
+                            write_console_key(
+                                ConsoleType::Warning,
+                                "limits.time_budget",
+                                &t!("limits.time_budget", path = path.display()),
+                            );
+                        }
+                        Some("time_budget")
+                    } else {
+                        limits.check_dir(path, entry.depth())
+                    };
+                    if let Some(limit) = limit {
+                        report::record_directory(path, "skipped", limit);
+                        if explain_enabled() {
+                            write_console(
+                                ConsoleType::Explain,
+                                &t!("limits.skipped", path = path.display(), limit = limit),
+                            );
+                        }
+                        return false;
+                    }
+                }
+
+                true
+            }
+        })
+    {
         // 判断是否正常访问路径
         let entry = match entry_result {
             Ok(e) => e,
             Err(e) => {
                 if !list_mode {
-                    write_console(ConsoleType::Warning, &t!("file.access_failed", error = e));
+                    write_console_key(
+                        ConsoleType::Warning,
+                        "file.access_failed",
+                        &t!("file.access_failed", error = e),
+                    );
                 }
                 continue;
             }
@@ -307,30 +639,168 @@ pub fn auto_shortcut(
             continue;
         }
 
+        // 目录配置：合并到全局配置上，作用于所在目录及其子目录
+        let scope_dir = if entry.file_type().is_dir() {
+            file_path
+        } else {
+            file_path.parent().unwrap_or(file_path)
+        };
+        let scoped_config = overrides.scoped_config(global_config, scope_dir);
+        let config_info = scoped_config.as_deref().or(global_config);
+        let score_ratio = config_info
+            .and_then(|config| config.score_ratio)
+            .unwrap_or(score_ratio);
+
+        // 目录配置：仅作用于所在目录
+        let dir_override = if entry.file_type().is_dir() {
+            overrides.load(file_path)
+        } else {
+            None
+        };
+        if let Some(dir_override) = &dir_override
+            && explain_enabled()
+        {
+            write_console(
+                ConsoleType::Explain,
+                &t!("override.loaded", path = dir_override.path.display()),
+            );
+            for (key, value, source) in overrides.explain(global_config, file_path) {
+                write_console(
+                    ConsoleType::Explain,
+                    &t!(
+                        "override.setting",
+                        key = key,
+                        value = value,
+                        source = source
+                    ),
+                );
+            }
+        }
+        // PortableApps.com 格式的目录视为绿色软件目录
+        let portable_app = if entry.file_type().is_dir() {
+            PortableAppInfo::load(file_path)
+        } else {
+            None
+        };
+        // 混合目录中的子目录作为独立的单文件程序目录或绿色软件目录识别
+        let in_hybrid = entry.file_type().is_dir()
+            && file_path
+                .parent()
+                .is_some_and(|parent| hybrid_dirs.contains(parent));
+        let forced_kind = dir_override
+            .as_ref()
+            .and_then(|o| o.kind())
+            .or(portable_app.as_ref().map(|_| DirKind::Green))
+            .or_else(|| {
+                in_hybrid.then(|| {
+                    if is_single_file_dir(file_path, Some(rules), &detection) {
+                        DirKind::Single
+                    } else {
+                        DirKind::Green
+                    }
+                })
+            });
+        // 目录类型的识别依据（用于扫描报告）
+        let kind_reason = if dir_override.as_ref().and_then(|o| o.kind()).is_some() {
+            "override"
+        } else if portable_app.is_some() {
+            "portable"
+        } else if in_hybrid {
+            "hybrid_subdir"
+        } else {
+            "detected"
+        };
+
         // 自动识别主程序逻辑
         if entry.file_type().is_dir() {
-            if is_category_dir(file_path) {
-                if !list_mode {
+            // 排除: 文件数超出扫描限制的目录
+            let Some(dir_entries) = limits.read_entries(file_path, entry.depth()) else {
+                report::record_directory(file_path, "skipped", "max_entries_per_dir");
+                if explain_enabled() {
                     write_console(
-                        ConsoleType::Info,
-                        &t!("directory.category", path = file_path.display()),
+                        ConsoleType::Explain,
+                        &t!(
+                            "limits.skipped",
+                            path = file_path.display(),
+                            limit = "max_entries_per_dir"
+                        ),
                     );
                 }
+                identified_app_roots
+                    .lock()
+                    .unwrap()
+                    .insert(file_path.to_path_buf());
                 continue;
-            }
+            };
 
-            // 判断是否为单文件目录
-            if is_single_file_dir(file_path, Some(&all_excluded)) {
-                // 单文件程序目录
+            if match forced_kind {
+                Some(kind) => kind == DirKind::Category,
+                None => is_category_dir(&dir_entries, entry.depth(), &detection, limits),
+            } {
                 if !list_mode {
                     write_console(
                         ConsoleType::Info,
-                        &t!("directory.single_file", path = file_path.display()),
+                        &t!("directory.category", path = file_path.display()),
                     );
                 }
+                report::record_directory(file_path, "category", kind_reason);
+                continue;
+            }
+
+            // 判断是否为单文件目录、单文件软件与绿色软件混合目录
+            let is_single = match forced_kind {
+                Some(kind) => kind == DirKind::Single,
+                None => is_single_file_dir(file_path, Some(rules), &detection),
+            };
+            let is_hybrid = !is_single
+                && match forced_kind {
+                    Some(kind) => kind == DirKind::Hybrid,
+                    None => is_hybrid_software_dir(
+                        &dir_entries,
+                        entry.depth(),
+                        rules,
+                        &detection,
+                        limits,
+                    ),
+                };
+            if is_single || is_hybrid {
+                if is_single {
+                    // 单文件程序目录
+                    if !list_mode {
+                        write_console(
+                            ConsoleType::Info,
+                            &t!("directory.single_file", path = file_path.display()),
+                        );
+                    }
+                    report::record_directory(file_path, "single", kind_reason);
+                } else {
+                    // 单文件软件、绿色软件混合目录：根目录中的程序按单文件程序处理，子目录继续识别
+                    if !list_mode {
+                        write_console(
+                            ConsoleType::Info,
+                            &t!("directory.hybrid", path = file_path.display()),
+                        );
+                    }
+                    report::record_directory(file_path, "hybrid", kind_reason);
+                }
+
+                // 混合目录根目录中的程序放在指定的子文件夹中
+                let root_folder = config_info
+                    .and_then(|cfg| cfg.hybrid_root_folder.clone())
+                    .filter(|_| is_hybrid);
+                let root_lnk_path = match &root_folder {
+                    Some(folder) => lnk_path.map(|path| path.join(folder)),
+                    None => lnk_path.map(Path::to_path_buf),
+                };
 
                 let mut roots = identified_app_roots.lock().unwrap();
-                if roots.insert(file_path.to_path_buf()) {
+                // 混合目录不剪枝，子目录作为独立的软件继续识别
+                let first_visit = if is_hybrid {
+                    hybrid_dirs.insert(file_path.to_path_buf())
+                } else {
+                    roots.insert(file_path.to_path_buf())
+                };
+                if first_visit {
                     // println!("剪枝: {}", file_path.display());
                     for entry in WalkDir::new(file_path)
                         .max_depth(1)
@@ -338,39 +808,58 @@ pub fn auto_shortcut(
                         .filter_map(Result::ok)
                         .filter(|file| {
                             file.path().is_file()
-                                && file
+                                && (file
                                     .path()
                                     .extension()
                                     .unwrap_or_default()
                                     .eq_ignore_ascii_case("exe")
+                                    || is_msi_file(file.path()))
                         })
                     {
                         let path = entry.path();
 
-                        // 单文件排除: 自定义排除
-                        if let Some(cfg) = &config_info {
-                            // 如果文件名包含任一 ignore 关键字，就跳过（返回 false）
-                            if path
-                                .file_name()
-                                .map(|n| n.to_string_lossy().to_lowercase())
-                                .is_some_and(|name| {
-                                    cfg.ignore
-                                        .iter()
-                                        .any(|kw| name.contains(&kw.to_lowercase()))
-                                })
-                            {
-                                if DEBUG.load(Ordering::Relaxed) {
-                                    write_console(
-                                        ConsoleType::Debug,
-                                        &t!("scan.ignore", path = path.display()),
-                                    );
-                                }
-                                continue;
+                        // 单文件排除: 忽略规则、包含列表
+                        if rules.is_ignored(path, false) || !rules.is_included(path) {
+                            if DEBUG.load(Ordering::Relaxed) {
+                                write_console(
+                                    ConsoleType::Debug,
+                                    &t!("scan.ignore", path = path.display()),
+                                );
                             }
+                            continue;
+                        }
+
+                        // 云文件占位符
+                        if skip_cloud_placeholder(path) {
+                            continue;
+                        }
+
+                        // 安装程序（Windows Installer 安装包只按安装程序处理）
+                        if handle_installer(path, config_info, installers, list_mode)
+                            || is_msi_file(path)
+                        {
+                            continue;
+                        }
+
+                        // 架构变体
+                        let arch_variant =
+                            arch_variants.choose(path, prefer_arch, keep_arch_variants);
+                        if skip_arch_variant(path, &arch_variant) {
+                            continue;
+                        }
+
+                        // 常见程序的辅助程序
+                        if skip_known_helper(path, config_info) {
+                            continue;
+                        }
+
+                        // 过滤条件
+                        if skip_filtered_program(path, config_info) {
+                            continue;
                         }
 
                         if list_mode {
-                            println!("{}", path.display());
+                            list_program(path, None);
                         }
 
                         // 运行程序
@@ -388,7 +877,6 @@ pub fn auto_shortcut(
                             // 如果命令行没有指定 lnk_path，且配置里也没有对应的 dest，则跳过后续处理
                             if lnk_path.is_none()
                                 && config_info
-                                    .as_ref()
                                     .and_then(|cfg| Lnk::get_lnk_info(path, &cfg.shortcut))
                                     .as_ref()
                                     .and_then(|li| li.dest.as_ref())
@@ -403,32 +891,34 @@ pub fn auto_shortcut(
                         }
 
                         // 创建快捷方式
-                        let lnk_info = config_info
-                            .as_ref()
-                            .and_then(|cfg| Lnk::get_lnk_info(path, &cfg.shortcut));
-                        let template = config_info.as_ref().and_then(|cfg| cfg.template.clone());
+                        let lnk_info =
+                            config_info.and_then(|cfg| Lnk::get_lnk_info(path, &cfg.shortcut));
+                        let lnk_info = match dir_override.as_ref().filter(|o| o.is_main(path)) {
+                            Some(o) => o.config.merge_lnk(path, lnk_info),
+                            None => lnk_info,
+                        };
+                        let mut template = config_info.and_then(|cfg| cfg.template.clone());
+                        if let Some(folder) = &root_folder
+                            && let Some(dest) = template.as_mut().and_then(|t| t.dest.as_mut())
+                        {
+                            *dest = Path::new(dest.as_str())
+                                .join(folder)
+                                .to_string_lossy()
+                                .to_string();
+                        }
 
-                        match create_program_shortcut(
+                        create_journaled_shortcut(
+                            journal,
+                            target_path,
                             path,
-                            lnk_path,
+                            &shortcut_target(path),
+                            root_lnk_path.as_deref(),
                             lnk_info,
                             template,
                             use_filename,
                             create_dir,
-                        ) {
-                            Ok((name, _path)) => write_console(
-                                ConsoleType::Success,
-                                &t!(
-                                    "shortcut.create_success",
-                                    name = name,
-                                    path = path.display()
-                                ),
-                            ),
-                            Err(_) => write_console(
-                                ConsoleType::Error,
-                                &t!("shortcut.create_failed", path = path.display()),
-                            ),
-                        };
+                            arch_variant.suffix(),
+                        );
                     }
                 } else {
                     if DEBUG.load(Ordering::Relaxed) {
@@ -438,15 +928,6 @@ pub fn auto_shortcut(
                         );
                     }
                 };
-            } else if is_hybrid_software_dir(file_path, &all_excluded) {
-                // 单文件软件、绿色软件混合目录
-                if !list_mode {
-                    write_console(
-                        ConsoleType::Info,
-                        &t!("directory.hybrid", path = file_path.display()),
-                    );
-                }
-                continue;
             } else {
                 // 绿色软件目录
                 if !list_mode {
@@ -455,33 +936,135 @@ pub fn auto_shortcut(
                         &t!("directory.green", path = file_path.display()),
                     );
                 }
+                report::record_directory(file_path, "green", kind_reason);
 
                 let mut roots_guard = identified_app_roots.lock().unwrap();
 
-                // 遍历全部exe进行打分
-                if let Some((suggested_app_root, exe_path)) = find_software_best_exe(
-                    file_path,
-                    config_info.clone().as_ref(),
-                    target_path,
-                    score_ratio,
-                    list_mode,
-                ) {
+                // 同一软件中需要创建快捷方式的其他程序（交互模式中选择）
+                let mut extra_programs: Vec<PathBuf> = Vec::new();
+
+                // 目录配置指定主程序，否则遍历全部exe进行打分
+                let best_exe = match dir_override.as_ref().and_then(|o| o.main_path()) {
+                    Some(main) if main.is_file() => {
+                        if explain_enabled() {
+                            write_console(
+                                ConsoleType::Explain,
+                                &t!("override.main", path = main.display()),
+                            );
+                        }
+                        Some((file_path.to_path_buf(), main))
+                    }
+                    pinned => {
+                        if let Some(main) = pinned {
+                            write_console_key(
+                                ConsoleType::Warning,
+                                "override.main_not_found",
+                                &t!("override.main_not_found", path = main.display()),
+                            );
+                        }
+
+                        // PortableApps.com 格式：使用 appinfo.ini 指定的启动器
+                        let launcher = portable_app.as_ref().and_then(|app| {
+                            let launcher = app.launcher();
+                            if launcher.is_none()
+                                && let Some(start) = &app.start
+                            {
+                                write_console_key(
+                                    ConsoleType::Warning,
+                                    "portable.launcher_not_found",
+                                    &t!(
+                                        "portable.launcher_not_found",
+                                        path = file_path.join(start).display()
+                                    ),
+                                );
+                            }
+                            launcher
+                        });
+                        match launcher {
+                            Some(launcher) => {
+                                if explain_enabled() {
+                                    write_console(
+                                        ConsoleType::Explain,
+                                        &t!("portable.launcher", path = launcher.display()),
+                                    );
+                                }
+                                Some((file_path.to_path_buf(), launcher))
+                            }
+                            None => {
+                                let (best_exe, candidates) = score_software_exes(
+                                    file_path,
+                                    config_info,
+                                    rules,
+                                    score_ratio,
+                                    list_mode,
+                                );
+                                // 交互模式：无法确定主程序时由用户选择
+                                match interactive::decide(file_path, &candidates, config_path) {
+                                    Decision::Default => best_exe,
+                                    Decision::Ignore => {
+                                        roots_guard.insert(file_path.to_path_buf());
+                                        continue;
+                                    }
+                                    Decision::Programs(programs) => {
+                                        let mut programs = programs.into_iter();
+                                        let main = programs.next();
+                                        extra_programs.extend(programs);
+                                        match main {
+                                            Some(main) => Some((file_path.to_path_buf(), main)),
+                                            None => {
+                                                roots_guard.insert(file_path.to_path_buf());
+                                                continue;
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                };
+                // 混合目录中的子目录：软件根目录不超出子目录
+                let best_exe = best_exe.map(|(app_root, exe_path)| {
+                    if in_hybrid && !app_root.starts_with(file_path) {
+                        (file_path.to_path_buf(), exe_path)
+                    } else {
+                        (app_root, exe_path)
+                    }
+                });
+                if let Some((suggested_app_root, exe_path)) = best_exe {
                     // 找到了最佳EXE，并且它的根目录是新的（没有被处理过）
                     if roots_guard.insert(suggested_app_root.clone()) {
                         // 运行安装脚本
                         if install_script {
                             run_install_scripts(
                                 &suggested_app_root,
-                                config_info
-                                    .as_ref()
-                                    .map(|config| config.scripts.clone())
-                                    .as_deref(),
+                                config_info.map(|config| config.scripts.clone()).as_deref(),
                                 install_parallel,
                             );
                         }
 
+                        // 配置文件中以绝对路径指定的、同一软件中的其他程序
+                        let root_key = suggested_app_root.to_string_lossy().to_lowercase();
+                        for lnk in config_info.iter().flat_map(|cfg| &cfg.shortcut) {
+                            let program = PathBuf::from(&lnk.exec);
+                            let key = program.to_string_lossy().to_lowercase();
+                            if program.is_absolute()
+                                && program.is_file()
+                                && Path::new(&key).starts_with(&root_key)
+                                && !rules.is_ignored(&program, false)
+                                && extra_programs
+                                    .iter()
+                                    .chain([&exe_path])
+                                    .all(|p| p.to_string_lossy().to_lowercase() != key)
+                            {
+                                extra_programs.push(program);
+                            }
+                        }
+
                         if list_mode {
-                            println!("{}", exe_path.display());
+                            list_program(&exe_path, None);
+                            for program in &extra_programs {
+                                list_program(program, None);
+                            }
                             continue;
                         }
 
@@ -500,7 +1083,6 @@ pub fn auto_shortcut(
                             // 如果命令行没有指定 lnk_path，且配置里也没有对应的 dest，则跳过后续处理
                             if lnk_path.is_none()
                                 && config_info
-                                    .as_ref()
                                     .and_then(|cfg| Lnk::get_lnk_info(&exe_path, &cfg.shortcut))
                                     .as_ref()
                                     .and_then(|li| li.dest.as_ref())
@@ -515,32 +1097,50 @@ pub fn auto_shortcut(
                         }
 
                         // 创建快捷方式
-                        let lnk_info = config_info
-                            .as_ref()
-                            .and_then(|cfg| Lnk::get_lnk_info(&exe_path, &cfg.shortcut));
-                        let template = config_info.as_ref().and_then(|cfg| cfg.template.clone());
+                        let lnk_info =
+                            config_info.and_then(|cfg| Lnk::get_lnk_info(&exe_path, &cfg.shortcut));
+                        let app_override = overrides
+                            .load(&suggested_app_root)
+                            .or_else(|| dir_override.clone());
+                        let lnk_info = match app_override.filter(|o| o.is_main(&exe_path)) {
+                            Some(o) => o.config.merge_lnk(&exe_path, lnk_info),
+                            None => lnk_info,
+                        };
+                        let lnk_info = match PortableAppInfo::for_program(&exe_path) {
+                            Some(app) => app.merge_lnk(&exe_path, lnk_info),
+                            None => lnk_info,
+                        };
+                        let template = config_info.and_then(|cfg| cfg.template.clone());
 
-                        match create_program_shortcut(
+                        create_journaled_shortcut(
+                            journal,
+                            target_path,
                             &exe_path,
+                            &shortcut_target(&exe_path),
                             lnk_path,
                             lnk_info,
-                            template,
+                            template.clone(),
                             use_filename,
                             create_dir,
-                        ) {
-                            Ok((name, _path)) => write_console(
-                                ConsoleType::Success,
-                                &t!(
-                                    "shortcut.create_success",
-                                    name = name,
-                                    path = exe_path.display()
-                                ),
-                            ),
-                            Err(_) => write_console(
-                                ConsoleType::Error,
-                                &t!("shortcut.create_failed", path = exe_path.display()),
-                            ),
-                        };
+                            None,
+                        );
+
+                        for program in &extra_programs {
+                            let lnk_info = config_info
+                                .and_then(|cfg| Lnk::get_lnk_info(program, &cfg.shortcut));
+                            create_journaled_shortcut(
+                                journal,
+                                target_path,
+                                program,
+                                &shortcut_target(program),
+                                lnk_path,
+                                lnk_info,
+                                template.clone(),
+                                use_filename,
+                                create_dir,
+                                None,
+                            );
+                        }
                     } else {
                         // 已处理软件根目录
                         if DEBUG.load(Ordering::Relaxed) {
@@ -553,100 +1153,310 @@ pub fn auto_shortcut(
                 } else {
                     // 绿色软件目录中，根据评分规则没有识别到主程序
                     if !list_mode {
-                        write_console(
+                        write_console_key(
                             ConsoleType::Warning,
+                            "scan.main_not_recognized",
                             &t!("scan.main_not_recognized", path = file_path.display()),
                         );
                     }
+                    report::record_directory(file_path, "skipped", export::MAIN_NOT_RECOGNIZED);
                     roots_guard.insert(file_path.to_path_buf());
                 }
             }
         } else if entry.file_type().is_file()
-            && file_path
+            && (file_path
                 .extension()
                 .is_some_and(|ext| ext.eq_ignore_ascii_case("exe"))
+                || is_msi_file(file_path))
         {
+            // 混合目录根目录中的程序已按单文件程序处理
+            if file_path
+                .parent()
+                .is_some_and(|parent| hybrid_dirs.contains(parent))
+            {
+                continue;
+            }
+
             // 匹配配置文件模式
-            if let Some(cfg) = &config_info {
+            if let Some(cfg) = config_info {
                 if only_match && Lnk::get_lnk_info(file_path, &cfg.shortcut).is_none() {
                     continue;
                 }
             }
 
+            // 包含列表
+            if !rules.is_included(file_path) {
+                if DEBUG.load(Ordering::Relaxed) {
+                    write_console(
+                        ConsoleType::Debug,
+                        &t!("scan.ignore", path = file_path.display()),
+                    );
+                }
+                continue;
+            }
+
+            // 云文件占位符
+            if skip_cloud_placeholder(file_path) {
+                continue;
+            }
+
+            // 安装程序（Windows Installer 安装包只按安装程序处理）
+            if handle_installer(file_path, config_info, installers, list_mode)
+                || is_msi_file(file_path)
+            {
+                continue;
+            }
+
+            // 架构变体
+            let arch_variant = arch_variants.choose(file_path, prefer_arch, keep_arch_variants);
+            if skip_arch_variant(file_path, &arch_variant) {
+                continue;
+            }
+
+            // 常见程序的辅助程序
+            if skip_known_helper(file_path, config_info) {
+                continue;
+            }
+
+            // 过滤条件
+            if skip_filtered_program(file_path, config_info) {
+                continue;
+            }
+
             // 情况1: “绿色软件”打分失败，识别为可能的绿色根目录，却又在 collect_and_score_best_exe_in_root() 里因为所有 EXE 分数都低于阈值而拿不出一个“最佳主程序”
             // 情况2: 所有其他未被剪枝、又没被当作应用根的 exe,在深层子目录里有临时 exe、测试文件、脚本等，
 
             if list_mode {
-                println!("{}", file_path.display());
+                list_program(file_path, None);
+            }
+
+            // 运行程序
+            if start {
+                write_console(
+                    ConsoleType::Info,
+                    &t!("shortcut.start", path = file_path.display()),
+                );
+                Command::new(file_path)
+                    .creation_flags(0x08000000)
+                    .current_dir(file_path.parent().unwrap())
+                    .spawn()
+                    .ok();
+
+                // 如果命令行没有指定 lnk_path，且配置里也没有对应的 dest，则跳过后续处理
+                if lnk_path.is_none()
+                    && config_info
+                        .and_then(|cfg| Lnk::get_lnk_info(file_path, &cfg.shortcut))
+                        .as_ref()
+                        .and_then(|li| li.dest.as_ref())
+                        .is_none()
+                {
+                    continue;
+                }
+            }
+
+            if list_mode {
+                continue;
             }
 
-            // 运行程序
-            if start {
-                write_console(
-                    ConsoleType::Info,
-                    &t!("shortcut.start", path = file_path.display()),
-                );
-                Command::new(file_path)
-                    .creation_flags(0x08000000)
-                    .current_dir(file_path.parent().unwrap())
-                    .spawn()
-                    .ok();
+            // 创建快捷方式
+            let lnk_info = config_info.and_then(|cfg| Lnk::get_lnk_info(file_path, &cfg.shortcut));
+            let template = config_info.and_then(|cfg| cfg.template.clone());
+
+            create_journaled_shortcut(
+                journal,
+                target_path,
+                file_path,
+                &shortcut_target(file_path),
+                lnk_path,
+                lnk_info,
+                template,
+                use_filename,
+                create_dir,
+                arch_variant.suffix(),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// 跳过非首选架构的架构变体
+///
+/// # 参数
+/// - `program`: 程序路径
+/// - `arch_variant`: 架构变体的处理方式
+///
+/// # 返回值
+/// - `true`: 跳过此程序
+fn skip_arch_variant(program: &Path, arch_variant: &ArchVariant) -> bool {
+    let ArchVariant::Skip(preferred) = arch_variant else {
+        return false;
+    };
+    if explain_enabled() {
+        write_console(
+            ConsoleType::Explain,
+            &t!(
+                "arch.skipped",
+                path = program.display(),
+                preferred = preferred.display()
+            ),
+        );
+    }
+    true
+}
+
+/// 判断程序是否为配置文件中指定的程序
+///
+/// # 参数
+/// - `program`: 程序路径
+/// - `config_info`: 配置信息
+fn is_config_program(program: &Path, config_info: Option<&ConfigInfo>) -> bool {
+    config_info.is_some_and(|cfg| Lnk::get_lnk_info(program, &cfg.shortcut).is_some())
+}
+
+/// 跳过常见程序的辅助程序（配置文件中指定的程序除外）
+///
+/// # 参数
+/// - `program`: 程序路径
+/// - `config_info`: 配置信息
+///
+/// # 返回值
+/// - `true`: 跳过此程序
+fn skip_known_helper(program: &Path, config_info: Option<&ConfigInfo>) -> bool {
+    if !known_apps::is_helper(program) || is_config_program(program, config_info) {
+        return false;
+    }
+    if explain_enabled() {
+        write_console(
+            ConsoleType::Explain,
+            &t!("known_apps.helper", path = program.display()),
+        );
+    }
+    true
+}
+
+/// 跳过不满足过滤条件的程序（配置文件中指定的程序除外）
+///
+/// # 参数
+/// - `program`: 程序路径
+/// - `config_info`: 配置信息
+///
+/// # 返回值
+/// - `true`: 跳过此程序
+fn skip_filtered_program(program: &Path, config_info: Option<&ConfigInfo>) -> bool {
+    let Some(config) = config_info else {
+        return false;
+    };
+    if Lnk::get_lnk_info(program, &config.shortcut).is_some() {
+        return false;
+    }
+    let Some(filter) = config.filter.check(program, &config.trusted_signers) else {
+        return false;
+    };
+    report::record_excluded(program, filter);
+    if explain_enabled() {
+        write_console(
+            ConsoleType::Explain,
+            &t!("filter.excluded", path = program.display(), filter = filter),
+        );
+    }
+    true
+}
+
+/// 是否输出识别过程说明（调试模式同样输出）
+fn explain_enabled() -> bool {
+    EXPLAIN.load(Ordering::Relaxed) || DEBUG.load(Ordering::Relaxed)
+}
+
+/// 跳过云文件占位符，避免读取程序信息时触发下载或访问失败
+///
+/// # 参数
+/// - `program`: 程序路径
+///
+/// # 返回值
+/// - `true`: 程序为云文件占位符，调用方应跳过
+/// - `false`: 不是云文件占位符
+fn skip_cloud_placeholder(program: &Path) -> bool {
+    if !fs::metadata(program).is_ok_and(|metadata| is_cloud_placeholder(&metadata)) {
+        return false;
+    }
+
+    if explain_enabled() {
+        write_console(
+            ConsoleType::Explain,
+            &t!("scan.placeholder", path = program.display()),
+        );
+    }
+    true
+}
 
-                // 如果命令行没有指定 lnk_path，且配置里也没有对应的 dest，则跳过后续处理
-                if lnk_path.is_none()
-                    && config_info
-                        .as_ref()
-                        .and_then(|cfg| Lnk::get_lnk_info(file_path, &cfg.shortcut))
-                        .as_ref()
-                        .and_then(|li| li.dest.as_ref())
-                        .is_none()
-                {
-                    continue;
-                }
-            }
+/// 判断文件是否为 Windows Installer 安装包（`.msi`）
+fn is_msi_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("msi"))
+}
 
-            if list_mode {
-                continue;
-            }
+/// 按安装程序处理策略处理程序（配置文件中指定的程序除外）
+///
+/// # 参数
+/// - `program`: 程序路径
+/// - `config_info`: 配置信息
+/// - `policy`: 安装程序处理策略
+/// - `list_mode`: 是否仅列出快捷方式路径
+///
+/// # 返回值
+/// - `true`: 程序为安装程序，已按策略处理，调用方应跳过
+/// - `false`: 不是安装程序，或策略为视为普通程序
+fn handle_installer(
+    program: &Path,
+    config_info: Option<&ConfigInfo>,
+    policy: InstallerPolicy,
+    list_mode: bool,
+) -> bool {
+    if policy == InstallerPolicy::Shortcut || is_config_program(program, config_info) {
+        return false;
+    }
 
-            // 创建快捷方式
-            let lnk_info = config_info
-                .as_ref()
-                .and_then(|cfg| Lnk::get_lnk_info(file_path, &cfg.shortcut));
-            let template = config_info.as_ref().and_then(|cfg| cfg.template.clone());
+    let kind = match detect_installer(program) {
+        Ok(Some(kind)) => kind,
+        _ => return false,
+    };
 
-            match create_program_shortcut(
-                file_path,
-                lnk_path,
-                lnk_info,
-                template,
-                use_filename,
-                create_dir,
-            ) {
-                Ok((name, _path)) => write_console(
-                    ConsoleType::Success,
+    match policy {
+        InstallerPolicy::List if list_mode => {
+            list_program(program, Some(kind.label()));
+        }
+        InstallerPolicy::List => {
+            write_console(
+                ConsoleType::Info,
+                &t!(
+                    "installer.found",
+                    kind = kind.label(),
+                    path = program.display()
+                ),
+            );
+        }
+        _ => {
+            if explain_enabled() {
+                write_console(
+                    ConsoleType::Explain,
                     &t!(
-                        "shortcut.create_success",
-                        name = name,
-                        path = file_path.display()
+                        "installer.skip",
+                        kind = kind.label(),
+                        path = program.display()
                     ),
-                ),
-                Err(_) => write_console(
-                    ConsoleType::Error,
-                    &t!("shortcut.create_failed", path = file_path.display()),
-                ),
-            };
+                );
+            }
         }
     }
-
-    Ok(())
+    true
 }
 
 /// 仅通过配置文件创建快捷方式
 ///
 /// # 参数
 ///
-/// - `config_path` - 配置文件路径
+/// - `config_info` - 配置信息
 /// - `install` - 是否执行安装脚本
 /// - `install_parallel` - 是否并行执行安装脚本
 /// - `start` - 是否运行程序
@@ -656,30 +1466,24 @@ pub fn auto_shortcut(
 ///
 /// 如果创建快捷方式成功，返回 `Ok(())`；否则返回 `Err`。
 fn config_shortcut(
-    config_path: PathBuf,
+    config_info: ConfigInfo,
     install: bool,
     install_parallel: bool,
     start: bool,
     use_name: bool,
 ) -> Result<()> {
-    // 读取配置文件信息
-    let config_info = match ConfigInfo::parse_config_file(&config_path) {
-        Ok(config) => config,
-        Err(e) => {
-            write_console(
-                ConsoleType::Error,
-                &format!("{}: {}", &t!("config.parse_failed"), e),
-            );
-            return Err(anyhow!("Configuration file parsing failed"));
-        }
-    };
+    icon_cache::configure(config_info.icon_cache.as_deref());
 
     // 执行安装脚本
     if install {
         for pat in config_info.scripts.iter() {
             let file_path = PathBuf::from(pat);
             if !file_path.exists() {
-                write_console(ConsoleType::Warning, &t!("file.not_found", path = pat));
+                write_console_key(
+                    ConsoleType::Warning,
+                    "file.not_found",
+                    &t!("file.not_found", path = pat),
+                );
                 continue;
             }
 
@@ -722,13 +1526,15 @@ fn config_shortcut(
             config_info.template.clone(),
             use_name,
             false,
+            None,
         ) {
             Ok((name, _path)) => write_console(
                 ConsoleType::Success,
                 &t!("shortcut.create_success", name = name, path = lnk.exec),
             ),
-            Err(_) => write_console(
+            Err(_) => write_console_key(
                 ConsoleType::Error,
+                "shortcut.create_failed",
                 &t!("shortcut.create_failed", path = lnk.exec),
             ),
         };
@@ -744,11 +1550,12 @@ fn config_shortcut(
 /// # 参数
 ///
 /// - `dir_path` - 要检查的目录路径
+/// - `detection` - 目录结构识别规则
 ///
 /// # 返回值
 ///
 /// 如果目录符合应用程序结构条件，返回 `true`；否则返回 `false`。
-fn contains_app_structure_lightweight(dir_path: &Path) -> bool {
+fn contains_app_structure_lightweight(dir_path: &Path, detection: &Detection) -> bool {
     if let Ok(entries) = fs::read_dir(dir_path) {
         let mut has_exe = false;
         let mut has_support_files = false; //.dll,.ini,.json,.xml,.dat,.cfg,.conf
@@ -769,68 +1576,30 @@ fn contains_app_structure_lightweight(dir_path: &Path) -> bool {
 
             if file_type.is_file() {
                 if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
-                    let lower_ext = ext.to_ascii_lowercase();
-                    if lower_ext == "exe" {
+                    if ext.eq_ignore_ascii_case("exe") {
                         has_exe = true;
                         exe_count += 1;
-                    } else if [
-                        "dll", "pak", "ini", "json", "xml", "yaml", "dat", "cfg", "conf", "log",
-                        "reg", "key", "cupf",
-                    ]
-                    .contains(&lower_ext.as_str())
-                    {
+                    } else if Detection::list_contains(&detection.support_exts, ext) {
                         has_support_files = true;
-                    } else if ["txt", "md", "pdf"].contains(&lower_ext.as_str()) {
+                    } else if Detection::list_contains(&detection.doc_exts, ext) {
                         if let Some(file_stem) = path.file_stem().and_then(|s| s.to_str()) {
-                            let lower_stem = file_stem.to_ascii_lowercase();
-                            if ["readme", "license", "eula", "changelog"]
-                                .contains(&lower_stem.as_str())
-                            {
+                            if Detection::list_contains(&detection.doc_stems, file_stem) {
                                 has_doc_files = true;
                             }
                         }
                     }
                 }
             } else if file_type.is_dir() {
-                if let Some(dir_name) = path.file_name().and_then(|s| s.to_str()) {
-                    let lower_dir_name = dir_name.to_ascii_lowercase();
-                    // 增加更多常见的应用程序子目录 [1, 2, 3]
-                    if [
-                        "bin",
-                        "lib",
-                        "data",
-                        "program",
-                        "assets",
-                        "resources",
-                        "content",
-                        "modules",
-                        "plugins",
-                        "drivers",
-                    ]
-                    .contains(&lower_dir_name.as_str())
-                    {
-                        has_common_subdirs = true;
-                    }
+                // 常见的应用程序子目录
+                if Detection::dir_matches(&detection.common_subdirs, &path) {
+                    has_common_subdirs = true;
                 }
             }
         }
 
         // 判断当前目录是否是常见的组件文件夹名
-        let is_component_folder_name = dir_path.file_name().is_some_and(|n| {
-            let lower_name = n.to_string_lossy().to_ascii_lowercase();
-            // 常见的组件目录名，这些通常不是应用程序的最高层根目录
-            [
-                "bin",
-                "program",
-                "executables",
-                "x64",
-                "win64",
-                "modules",
-                "plugins",
-                "drivers",
-            ]
-            .contains(&lower_name.as_str())
-        });
+        // 常见的组件目录名，这些通常不是应用程序的最高层根目录
+        let is_component_folder_name = Detection::dir_matches(&detection.component_dirs, dir_path);
 
         // 规则组合：
         // 1. 包含EXE，且有支持文件或常见子目录 (最常见的多文件应用)
@@ -852,8 +1621,8 @@ fn contains_app_structure_lightweight(dir_path: &Path) -> bool {
 /// # 参数
 ///
 /// - `app_root_path` - 绿色软件目录的根路径
-/// - `config_info` - 可选的配置信息，用于忽略某些文件
-/// - `initial_scan_root` - 初始扫描的根路径，用于确定扫描范围
+/// - `config_info` - 可选的配置信息
+/// - `rules` - 扫描目录的忽略、包含规则
 /// - `score_ratio` - 评分比例，用于调整评分权重
 /// - `list_mode` - 是否以列表模式运行，用于控制输出
 ///
@@ -863,21 +1632,47 @@ fn contains_app_structure_lightweight(dir_path: &Path) -> bool {
 fn find_software_best_exe(
     app_root_path: &Path,
     config_info: Option<&ConfigInfo>,
-    initial_scan_root: &Path,
+    rules: &IgnoreRules,
     score_ratio: f32,
     list_mode: bool,
 ) -> Option<(PathBuf, PathBuf)> {
+    score_software_exes(app_root_path, config_info, rules, score_ratio, list_mode).0
+}
+
+/// 在绿色软件目录中收集所有EXE并评分，同时返回候选程序的评分记录
+///
+/// 参数与 `find_software_best_exe` 相同，评分记录仅在记录扫描报告或交互模式下收集。
+fn score_software_exes(
+    app_root_path: &Path,
+    config_info: Option<&ConfigInfo>,
+    rules: &IgnoreRules,
+    score_ratio: f32,
+    list_mode: bool,
+) -> (Option<(PathBuf, PathBuf)>, Vec<CandidateRecord>) {
     let mut best_candidate: Option<(PathBuf, PathBuf)> = None;
     let mut best_score = 0;
-
-    // 局部扫描：扫描当前目录及子目录（最大两层）
-    for entry_result in WalkDir::new(app_root_path).max_depth(2).into_iter() {
+    // 候选程序评分记录（用于扫描报告）
+    let mut candidates: Vec<CandidateRecord> = Vec::new();
+
+    // 目录结构识别规则
+    let default_detection = Detection::default();
+    let detection = config_info.map_or(&default_detection, |config| &config.detection);
+
+    // 局部扫描：扫描当前目录及子目录（默认最大两层）
+    for entry_result in WalkDir::new(app_root_path)
+        .max_depth(detection.scan_depth)
+        .into_iter()
+    {
         //
         let entry = match entry_result {
             Ok(e) => e,
             Err(e) => {
                 if !list_mode {
-                    write_console(ConsoleType::Warning, &t!("file.access_failed", error = e));
+                    write_console_key(
+                        ConsoleType::Warning,
+                        "file.access_failed",
+                        &t!("file.access_failed", error = e),
+                    );
                 }
                 continue;
             }
@@ -892,12 +1687,16 @@ fn find_software_best_exe(
         {
             /// 最高分
             const MAX_SCORE: i32 = 100  // 配置匹配
+                + 40   // 常见程序的主程序
                 + 40   //文件名与父目录名匹配
                 + 50   // GUI
                 + 40   // 图标
+                + 10   // 256 像素图标
                 + 30   // 描述
                 + 45   // 架构
                 + 60   // 数字签名
+                + 20   // 受信任的签名者
+                + 10   // 支持高 DPI
                 + 30; // 文件体积分
 
             // 当前分数
@@ -905,38 +1704,43 @@ fn find_software_best_exe(
             // 分数明细记录 (metric_name, delta)
             let mut breakdown: Vec<(&str, i32)> = Vec::new();
 
-            // 应用配置中的忽略列表
-            if let Some(cfg) = &config_info {
-                let file_name = file_path
-                    .file_name()
-                    .map(|n| n.to_string_lossy().to_lowercase());
-
-                // 检查是否需要忽略此文件或目录
-                if file_name.is_some_and(|name| {
-                    cfg.ignore.iter().any(|kw| {
-                        // 尝试将关键词解析为绝对路径
-                        if let Ok(keyword_path) = PathBuf::from(kw).canonicalize() {
-                            // 对当前完整路径进行规范化并比较
-                            if let Ok(current_path) = file_path.canonicalize() {
-                                current_path == keyword_path
-                            } else {
-                                // 如果无法规范化当前路径，回退到文件名匹配
-                                name.contains(kw.to_lowercase().as_str())
-                            }
-                        } else {
-                            // 不是绝对路径，使用文件名包含匹配
-                            name.contains(kw.to_lowercase().as_str())
-                        }
-                    })
-                }) {
-                    if DEBUG.load(Ordering::Relaxed) {
-                        write_console(
-                            ConsoleType::Debug,
-                            &t!("scan.ignore_in_config", path = file_path.display()),
-                        );
-                    }
-                    continue;
+            // 应用配置中的忽略规则、包含列表
+            if rules.is_ignored(file_path, false) || !rules.is_included(file_path) {
+                if DEBUG.load(Ordering::Relaxed) {
+                    write_console(
+                        ConsoleType::Debug,
+                        &t!("scan.ignore_in_config", path = file_path.display()),
+                    );
+                }
+                continue;
+            }
+
+            // 云文件占位符
+            if skip_cloud_placeholder(file_path) {
+                continue;
+            }
+
+            // 过滤条件
+            if skip_filtered_program(file_path, config_info) {
+                continue;
+            }
+
+            // 安装程序不参与主程序评分（配置文件中指定的程序除外）
+            if config_info.is_none_or(|cfg| cfg.installers != InstallerPolicy::Shortcut)
+                && !is_config_program(file_path, config_info)
+                && let Ok(Some(kind)) = detect_installer(file_path)
+            {
+                if explain_enabled() {
+                    write_console(
+                        ConsoleType::Explain,
+                        &t!(
+                            "installer.excluded",
+                            kind = kind.label(),
+                            path = file_path.display()
+                        ),
+                    );
                 }
+                continue;
             }
 
             // 配置文件名指定程序文件
@@ -955,6 +1759,35 @@ fn find_software_best_exe(
                 }
             }
 
+            // 运行时附带的辅助程序（Electron 的 Update.exe、Java 运行时中的 java.exe 等）
+            let runtime = detect_runtime(file_path);
+            if is_runtime_helper(file_path, runtime) {
+                score -= 80;
+                breakdown.push(("runtime_helper", -80));
+            }
+
+            // 内置的常见程序信息：主程序加分（同级程序中靠前的优先），辅助程序减分
+            if let Some(app) = known_apps::find(file_path) {
+                let known_score = if app.exe_rank(file_path) == Some(0) {
+                    40
+                } else {
+                    30
+                };
+                score += known_score;
+                breakdown.push(("known_app", known_score));
+            } else if known_apps::is_helper(file_path) {
+                score -= 80;
+                breakdown.push(("known_helper", -80));
+            }
+
+            // .NET apphost 体积很小且可能没有版本信息，改用同名主程序集
+            let main_assembly = if runtime == AppRuntime::DotNet {
+                get_dotnet_main_assembly(file_path)
+            } else {
+                None
+            };
+            let info_path = main_assembly.as_deref().unwrap_or(file_path);
+
             // 文件名与父目录名匹配
             if let Some(parent_dir_name) = file_path
                 .parent()
@@ -985,49 +1818,70 @@ fn find_software_best_exe(
                 }
             }
 
-            // 判断是否有图标
-            if has_icon_in_program(file_path) {
+            // 判断是否有图标，主图标包含 256 像素大图标时额外加分
+            if let Ok(groups) = read_icon_groups(file_path)
+                && let Some(group) = groups.first()
+            {
                 score += 40;
                 breakdown.push(("icon", 40));
+                if group.max_size() >= LARGE_ICON_SIZE {
+                    score += 10;
+                    breakdown.push(("icon_256", 10));
+                }
             }
 
             // 判断是否有程序描述信息
-            if let Ok(Some(_description)) = get_exe_description(file_path) {
+            if matches!(get_exe_description(file_path), Ok(Some(_)))
+                || (main_assembly.is_some()
+                    && matches!(get_exe_description(info_path), Ok(Some(_))))
+            {
                 score += 30;
                 breakdown.push(("description", 30));
             }
 
-            // 判断程序位数是否与系统相匹配
+            // 判断程序位数是否与首选架构（默认为系统架构）相匹配
             if let Ok(program_arch_code) = get_program_arch(file_path) {
-                let system_arch_code = get_native_arch();
-                if match program_arch_code {
-                    0x014c => {
-                        // IMAGE_FILE_MACHINE_I386 (x86 程序)
-                        system_arch_code == PROCESSOR_ARCHITECTURE_INTEL.0 // 匹配 x86 系统
-                    }
-                    0x8664 => {
-                        // IMAGE_FILE_MACHINE_AMD64 (x64 程序)
-                        system_arch_code == PROCESSOR_ARCHITECTURE_AMD64.0 // 匹配 x64 系统
-                    }
-                    0xAA64 => {
-                        // IMAGE_FILE_MACHINE_ARM64 (ARM64 程序)
-                        system_arch_code == PROCESSOR_ARCHITECTURE_ARM64.0 // 匹配 ARM64 系统
-                    }
-                    _ => false, // 遇到未知或不常见的程序架构，默认不匹配
-                } {
+                let preferred_arch = config_info
+                    .and_then(|cfg| cfg.prefer_arch)
+                    .or_else(get_native_arch_kind);
+                // 遇到未知或不常见的程序架构，默认不匹配
+                if Arch::from_machine(program_arch_code)
+                    .is_some_and(|arch| Some(arch) == preferred_arch)
+                {
                     score += 45;
                     breakdown.push(("arch", 45));
                 }
             }
 
-            // 判断是否有数字签名
-            if exe_has_signature(file_path).unwrap_or(false) {
+            // 判断是否有有效的数字签名，签名者受信任时额外加分
+            if let Ok(Some(signature)) = Signature::read(file_path)
+                && signature.verified
+            {
                 score += 60;
                 breakdown.push(("signature", 60));
+                let trusted_signers = config_info
+                    .map(|cfg| cfg.trusted_signers.as_slice())
+                    .unwrap_or_default();
+                if signature.is_trusted(trusted_signers) {
+                    score += 20;
+                    breakdown.push(("trusted_signer", 20));
+                }
+            }
+
+            // 程序清单：声明支持高 DPI 的多为界面主程序，要求管理员权限的多为更新、卸载等辅助程序
+            if let Ok(Some(manifest)) = Manifest::read(file_path) {
+                if manifest.is_dpi_aware() {
+                    score += 10;
+                    breakdown.push(("dpi_aware", 10));
+                }
+                if manifest.requires_admin() {
+                    score -= 20;
+                    breakdown.push(("require_admin", -20));
+                }
             }
 
             // 获取程序大小
-            if let Ok(metadata) = file_path.metadata() {
+            if let Ok(metadata) = info_path.metadata() {
                 // 转换为 MB
                 let file_size_mb = metadata.len() / (1024 * 1024);
 
@@ -1051,14 +1905,14 @@ fn find_software_best_exe(
             //     }
             // }
 
-            if DEBUG.load(Ordering::Relaxed) {
+            if explain_enabled() {
                 let details = breakdown
                     .iter()
                     .map(|(k, v)| format!("{}{:+}", k, v))
                     .collect::<Vec<_>>()
                     .join(", ");
                 write_console(
-                    ConsoleType::Debug,
+                    ConsoleType::Explain,
                     &t!(
                         "scan.score_calculation",
                         file = file_path.file_name().unwrap().to_string_lossy(),
@@ -1068,8 +1922,25 @@ fn find_software_best_exe(
                 );
             }
 
+            let threshold = (MAX_SCORE as f32 * score_ratio).round() as i32;
+            if report::is_enabled() || interactive::is_enabled() {
+                candidates.push(CandidateRecord {
+                    path: file_path.to_path_buf(),
+                    score,
+                    threshold,
+                    breakdown: breakdown
+                        .iter()
+                        .map(|(metric, score)| ScoreItem {
+                            metric: metric.to_string(),
+                            score: *score,
+                        })
+                        .collect(),
+                    selected: false,
+                });
+            }
+
             // 只有得分超过阈值才继续
-            if score <= (MAX_SCORE as f32 * score_ratio).round() as i32 {
+            if score <= threshold {
                 // println!("[局部扫描][非主程序] {} 得分过低 ({})，跳过", file_path.display(), score);
                 continue;
             }
@@ -1080,11 +1951,10 @@ fn find_software_best_exe(
                 .map_or_else(|| file_path.to_path_buf(), |p| p.to_path_buf());
             let mut final_app_root = current_root_candidate.clone();
             let mut depth_checked = 0;
-            let max_upward_depth = 3; // 向上回溯的最大层数
 
             while let Some(parent) = current_root_candidate.parent() {
-                if depth_checked >= max_upward_depth
-                    || parent == initial_scan_root
+                if depth_checked >= detection.max_upward_depth
+                    || parent == rules.root()
                     || parent.parent().is_none()
                 {
                     break;
@@ -1097,43 +1967,22 @@ fn find_software_best_exe(
                         let entry_path = entry_in_parent.path();
                         if entry_path.is_file() {
                             if let Some(ext) = entry_path.extension().and_then(|s| s.to_str()) {
-                                let lower_ext = ext.to_ascii_lowercase();
-                                if lower_ext == "dll"
-                                    || ["ini", "json", "xml", "dat", "cfg", "conf"]
-                                        .contains(&lower_ext.as_str())
-                                {
-                                    parent_has_app_structure = true;
-                                    break;
-                                }
-                            }
-                        } else if entry_path.is_dir() {
-                            if let Some(dir_name) = entry_path.file_name().and_then(|s| s.to_str())
-                            {
-                                let lower_dir_name = dir_name.to_ascii_lowercase();
-                                if [
-                                    "bin",
-                                    "lib",
-                                    "data",
-                                    "program",
-                                    "assets",
-                                    "resources",
-                                    "content",
-                                ]
-                                .contains(&lower_dir_name.as_str())
-                                {
+                                if Detection::list_contains(&detection.root_marker_exts, ext) {
                                     parent_has_app_structure = true;
                                     break;
                                 }
                             }
+                        } else if entry_path.is_dir()
+                            && Detection::dir_matches(&detection.root_marker_subdirs, &entry_path)
+                        {
+                            parent_has_app_structure = true;
+                            break;
                         }
                     }
                 }
 
-                let current_dir_name_lower = current_root_candidate
-                    .file_name()
-                    .map_or("".to_string(), |n| n.to_string_lossy().to_ascii_lowercase());
-                let is_component_folder = ["bin", "program", "executables", "x64", "win64"]
-                    .contains(&current_dir_name_lower.as_str());
+                let is_component_folder =
+                    Detection::dir_matches(&detection.root_component_dirs, &current_root_candidate);
 
                 if parent_has_app_structure || is_component_folder {
                     final_app_root = parent.to_path_buf();
@@ -1153,7 +2002,28 @@ fn find_software_best_exe(
             }
         }
     }
-    best_candidate
+
+    if !candidates.is_empty() {
+        if let Some((_, best_exe)) = &best_candidate {
+            for candidate in &mut candidates {
+                candidate.selected = &candidate.path == best_exe;
+            }
+        }
+        report::record_candidates(candidates.clone());
+    }
+    (best_candidate, candidates)
+}
+
+/// 是否为目录配置文件或忽略规则文件，识别目录结构时不计入
+fn is_scan_config_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| {
+            name.eq_ignore_ascii_case(IGNORE_FILE_NAME)
+                || OVERRIDE_FILE_NAMES
+                    .iter()
+                    .any(|file_name| name.eq_ignore_ascii_case(file_name))
+        })
 }
 
 /// 判断程序目录是否为单文件程序目录
@@ -1165,12 +2035,13 @@ fn find_software_best_exe(
 /// # 参数
 ///
 /// - `app_root` - 要检查的程序目录路径
-/// - `exclude_keyword` - 排除的关键词列表，用于过滤文件
+/// - `rules` - 忽略规则，用于过滤文件
+/// - `detection` - 目录结构识别规则
 ///
 /// # 返回值
 ///
 /// 如果目录符合单文件程序目录条件，返回 `true`；否则返回 `false`。
-fn is_single_file_dir(app_root: &Path, exclude_keyword: Option<&[String]>) -> bool {
+fn is_single_file_dir(app_root: &Path, rules: Option<&IgnoreRules>, detection: &Detection) -> bool {
     if let Ok(entries) = fs::read_dir(app_root) {
         let mut exe_count = 0;
         let mut other_file_count = 0;
@@ -1178,38 +2049,26 @@ fn is_single_file_dir(app_root: &Path, exclude_keyword: Option<&[String]>) -> bo
 
         for entry in entries.filter_map(|e| e.ok()) {
             let path = entry.path();
-            // 检查文件名是否包含排除关键词
-            if let Some(exclude_keyword) = exclude_keyword {
-                if exclude_keyword.iter().any(|k| {
-                    if let Ok(keyword_path) = PathBuf::from(k).canonicalize() {
-                        // 判断绝对路径是否匹配
-                        if let Ok(current_path) = path.canonicalize() {
-                            current_path == keyword_path
-                        } else {
-                            path.display().to_string().to_lowercase().contains(k)
-                        }
-                    } else {
-                        // 不是绝对路径，使用现有的包含匹配逻辑
-                        path.display().to_string().to_lowercase().contains(k)
-                    }
-                }) {
-                    if DEBUG.load(Ordering::Relaxed) {
-                        write_console(
-                            ConsoleType::Debug,
-                            &t!("scan.ignore", path = path.display()),
-                        );
-                    }
-                    continue;
+            // 检查是否被忽略规则排除
+            if rules.is_some_and(|rules| rules.is_ignored(&path, path.is_dir())) {
+                if DEBUG.load(Ordering::Relaxed) {
+                    write_console(
+                        ConsoleType::Debug,
+                        &t!("scan.ignore", path = path.display()),
+                    );
                 }
+                continue;
+            }
+            if is_scan_config_file(&path) {
+                continue;
             }
 
             if path.is_file() {
                 if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
-                    let lower_ext = ext.to_ascii_lowercase();
-                    if lower_ext == "exe" {
+                    if ext.eq_ignore_ascii_case("exe") {
                         exe_count += 1;
-                    } else if lower_ext != "ico" {
-                        // 允许.ico 文件存在
+                    } else if !Detection::list_contains(&detection.allowed_exts, ext) {
+                        // 允许辅助文件（默认 .ico）存在
                         other_file_count += 1;
                     }
                 } else {
@@ -1219,7 +2078,7 @@ fn is_single_file_dir(app_root: &Path, exclude_keyword: Option<&[String]>) -> bo
                 dir_count += 1;
             }
         }
-        // 单文件程序：只有exe，没有其他文件（排除ico），没有子目录
+        // 单文件程序：只有exe，没有其他文件（排除辅助文件），没有子目录
         return exe_count > 0 && other_file_count == 0 && dir_count == 0;
     }
     false
@@ -1233,21 +2092,27 @@ fn is_single_file_dir(app_root: &Path, exclude_keyword: Option<&[String]>) -> bo
 ///
 /// # 参数
 ///
-/// - `dir` - 要检查的目录路径
-/// - `exclude_keyword` - 排除的关键词列表，用于过滤目录
+/// - `entries` - 目录的一级条目
+/// - `depth` - 目录相对于扫描目录的深度
+/// - `rules` - 忽略规则，用于过滤目录
+/// - `detection` - 目录结构识别规则
+/// - `limits` - 扫描限制，超出限制的子目录不读取
 ///
 /// # 返回值
 ///
 /// 如果目录符合混合目录条件，返回 `true`；否则返回 `false`。
-fn is_hybrid_software_dir(dir: &Path, exclude_keyword: &[String]) -> bool {
-    /// 是否把某些文件扩展名视为“允许的辅助文件”，不会导致拒绝混合目录判定
-    const ALLOWED_ROOT_FILE_EXT: &[&str] = &["ico"];
-
-    /// 根目录中允许的“其它不认识文件”最大数量比例（例如 0.3 表示最多 30% 的根文件为未知类型）
-    const ROOT_UNKNOWN_FILE_RATIO_ALLOWED: f32 = 0.3;
-
-    /// 子目录中被识别为“应用子包”的占比阈值（例如 0.6 表示 >=60% 子目录是应用子包就认定为混合）
-    const SUBDIR_APP_RATIO_THRESHOLD: f32 = 0.8;
+fn is_hybrid_software_dir(
+    entries: &[fs::DirEntry],
+    depth: usize,
+    rules: &IgnoreRules,
+    detection: &Detection,
+    limits: &ScanLimits,
+) -> bool {
+    // 根目录中允许的“其它不认识文件”最大数量比例（例如 0.3 表示最多 30% 的根文件为未知类型）
+    let root_unknown_file_ratio_allowed = detection.hybrid_unknown_ratio;
+
+    // 子目录中被识别为“应用子包”的占比阈值（例如 0.8 表示 >=80% 子目录是应用子包就认定为混合）
+    let subdir_app_ratio_threshold = detection.hybrid_app_ratio;
 
     // 收集根目录一级 entries
     let mut root_exe_count = 0usize;
@@ -1255,45 +2120,31 @@ fn is_hybrid_software_dir(dir: &Path, exclude_keyword: &[String]) -> bool {
     let mut root_allowed_file_count = 0usize;
     let mut subdirs: Vec<PathBuf> = Vec::new();
 
-    if let Ok(entries) = fs::read_dir(dir) {
-        for entry in entries.filter_map(Result::ok) {
-            let p = entry.path();
-            if exclude_keyword.iter().any(|k| {
-                if let Ok(keyword_path) = PathBuf::from(k).canonicalize() {
-                    // 判断绝对路径
-                    if let Ok(current_path) = p.canonicalize() {
-                        current_path == keyword_path
-                    } else {
-                        p.display().to_string().to_lowercase().contains(k)
-                    }
-                } else {
-                    // 不是绝对路径，使用现有的包含匹配
-                    p.display().to_string().to_lowercase().contains(k)
-                }
-            }) {
-                continue;
-            }
-            if p.is_file() {
-                // extension 的处理要小心无扩展名的文件
-                if let Some(ext) = p
-                    .extension()
-                    .and_then(|s| s.to_str())
-                    .map(|s| s.to_ascii_lowercase())
-                {
-                    if ext == "exe" {
-                        root_exe_count += 1;
-                    } else if ALLOWED_ROOT_FILE_EXT.contains(&ext.as_str()) {
-                        root_allowed_file_count += 1;
-                    } else {
-                        root_unknown_file_count += 1;
-                    }
+    for entry in entries {
+        let p = entry.path();
+        if rules.is_ignored(&p, p.is_dir()) || is_scan_config_file(&p) {
+            continue;
+        }
+        if p.is_file() {
+            // extension 的处理要小心无扩展名的文件
+            if let Some(ext) = p
+                .extension()
+                .and_then(|s| s.to_str())
+                .map(|s| s.to_ascii_lowercase())
+            {
+                if ext == "exe" {
+                    root_exe_count += 1;
+                } else if Detection::list_contains(&detection.allowed_exts, &ext) {
+                    root_allowed_file_count += 1;
                 } else {
-                    // 无扩展名的文件视为未知
                     root_unknown_file_count += 1;
                 }
-            } else if p.is_dir() {
-                subdirs.push(p);
+            } else {
+                // 无扩展名的文件视为未知
+                root_unknown_file_count += 1;
             }
+        } else if p.is_dir() {
+            subdirs.push(p);
         }
     }
 
@@ -1310,8 +2161,14 @@ fn is_hybrid_software_dir(dir: &Path, exclude_keyword: &[String]) -> bool {
     // 统计子目录被识别为绿色软件或单文件程序的数量
     let mut app_subdirs = 0usize;
     for sd in &subdirs {
+        // 超出扫描限制的子目录（如 node_modules）不读取
+        if limits.check_dir(sd, depth + 1).is_some() {
+            continue;
+        }
         // 对每个子目录使用已有的轻量检测函数（它们本身要足够稳健）
-        if contains_app_structure_lightweight(sd) || is_single_file_dir(sd, Some(exclude_keyword)) {
+        if contains_app_structure_lightweight(sd, detection)
+            || is_single_file_dir(sd, Some(rules), detection)
+        {
             app_subdirs += 1;
         }
     }
@@ -1326,13 +2183,13 @@ fn is_hybrid_software_dir(dir: &Path, exclude_keyword: &[String]) -> bool {
     } else {
         0.0
     };
-    if unknown_ratio > ROOT_UNKNOWN_FILE_RATIO_ALLOWED {
+    if unknown_ratio > root_unknown_file_ratio_allowed {
         // 根目录里有过多未知文件，保守认为不是混合软件目录
         return false;
     }
 
     // 最终判定：子目录中大多数为应用包，或至少有足够数量的 app 子目录
-    if appdir_ratio >= SUBDIR_APP_RATIO_THRESHOLD || app_subdirs >= 1 {
+    if appdir_ratio >= subdir_app_ratio_threshold || app_subdirs >= 1 {
         return true;
     }
 
@@ -1343,25 +2200,31 @@ fn is_hybrid_software_dir(dir: &Path, exclude_keyword: &[String]) -> bool {
 ///
 /// # 参数
 ///
-/// - `dir` - 要检查的目录路径
+/// - `entries` - 目录的一级条目
+/// - `depth` - 目录相对于扫描目录的深度
+/// - `detection` - 目录结构识别规则
+/// - `limits` - 扫描限制，超出限制的子目录不读取
 ///
 /// # 返回值
 ///
 /// 如果目录符合分类目录条件，返回 `true`；否则返回 `false`。
-fn is_category_dir(dir: &Path) -> bool {
+fn is_category_dir(
+    entries: &[fs::DirEntry],
+    depth: usize,
+    detection: &Detection,
+    limits: &ScanLimits,
+) -> bool {
     // 根目录下有没有顶层 exe
     let mut has_exe = false;
 
-    if let Ok(entries) = fs::read_dir(dir) {
-        for entry in entries.filter_map(Result::ok) {
-            let p = entry.path();
-            if p.is_file() {
-                if let Some(ext) = p.extension().and_then(|e| e.to_str()) {
-                    let ext = ext.to_ascii_lowercase();
-                    if ext == "exe" {
-                        has_exe = true;
-                        break;
-                    }
+    for entry in entries {
+        let p = entry.path();
+        if p.is_file() {
+            if let Some(ext) = p.extension().and_then(|e| e.to_str()) {
+                let ext = ext.to_ascii_lowercase();
+                if ext == "exe" {
+                    has_exe = true;
+                    break;
                 }
             }
         }
@@ -1373,25 +2236,27 @@ fn is_category_dir(dir: &Path) -> bool {
     }
 
     // 列出一级子目录
-    let sub_dirs: Vec<_> = match fs::read_dir(dir) {
-        Ok(rd) => rd
-            // 跳过读取出错的条目
-            .filter_map(Result::ok)
-            .filter_map(|e| {
-                // 跳过 file_type 出错的条目
-                match e.file_type() {
-                    Ok(ft) if ft.is_dir() => Some(e.path()),
-                    _ => None,
-                }
-            })
-            .collect(),
-        Err(_) => return false,
-    };
+    let sub_dirs: Vec<_> = entries
+        .iter()
+        .filter_map(|e| {
+            // 跳过 file_type 出错的条目
+            match e.file_type() {
+                Ok(ft) if ft.is_dir() => Some(e.path()),
+                _ => None,
+            }
+        })
+        .collect();
 
     // 子目录中，至少有一个是真正的“应用子包”（单文件 或 绿色软件）
     let mut has_app_subdir = false;
     for sd in &sub_dirs {
-        if is_single_file_dir(sd, None) || contains_app_structure_lightweight(sd) {
+        // 超出扫描限制的子目录（如 node_modules）不读取
+        if limits.check_dir(sd, depth + 1).is_some() {
+            continue;
+        }
+        if is_single_file_dir(sd, None, detection)
+            || contains_app_structure_lightweight(sd, detection)
+        {
             has_app_subdir = true;
             break;
         } else {
@@ -1487,6 +2352,82 @@ fn run_install_scripts(dir: &Path, scripts: Option<&[String]>, install_parallel:
     }
 }
 
+/// 创建程序快捷方式并写入快捷方式记录
+///
+/// 跳过其他扫描目录中已创建快捷方式的程序，同名快捷方式被其他扫描目录占用时输出警告
+///
+/// # 参数
+/// - `journal`: 快捷方式记录
+/// - `root`: 扫描目录
+/// - `program_path`: 扫描到的程序路径
+/// - `shortcut_target`: 快捷方式目标路径
+/// - 其余参数同 `create_program_shortcut`
+#[allow(clippy::too_many_arguments)]
+fn create_journaled_shortcut(
+    journal: &Journal,
+    root: &Path,
+    program_path: &Path,
+    shortcut_target: &Path,
+    link_path: Option<&Path>,
+    lnk_info: Option<Lnk>,
+    template: Option<Template>,
+    use_filename: bool,
+    create_dir: bool,
+    name_suffix: Option<String>,
+) {
+    // 同一程序已由其他扫描目录处理（扫描目录重叠或通过链接访问）
+    if let Some(entry) = journal.find_program(root, program_path) {
+        write_console_key(
+            ConsoleType::Warning,
+            "journal.duplicate_program",
+            &t!(
+                "journal.duplicate_program",
+                path = program_path.display(),
+                root = entry.root.display()
+            ),
+        );
+        return;
+    }
+
+    match create_program_shortcut(
+        shortcut_target,
+        link_path,
+        lnk_info,
+        template,
+        use_filename,
+        create_dir,
+        name_suffix,
+    ) {
+        Ok((name, path)) => {
+            write_console(
+                ConsoleType::Success,
+                &t!(
+                    "shortcut.create_success",
+                    name = name,
+                    path = program_path.display()
+                ),
+            );
+            if let Some(entry) = journal.record(root, program_path, &path) {
+                write_console_key(
+                    ConsoleType::Warning,
+                    "journal.name_conflict",
+                    &t!(
+                        "journal.name_conflict",
+                        path = path.display(),
+                        program = entry.program.display(),
+                        root = entry.root.display()
+                    ),
+                );
+            }
+        }
+        Err(_) => write_console_key(
+            ConsoleType::Error,
+            "shortcut.create_failed",
+            &t!("shortcut.create_failed", path = program_path.display()),
+        ),
+    };
+}
+
 /// 创建程序快捷方式
 ///
 /// # 参数
@@ -1494,6 +2435,7 @@ fn run_install_scripts(dir: &Path, scripts: Option<&[String]>, install_parallel:
 /// - `link_path`: 快捷方式保存路径
 /// - `use_filename`: 是否使用原始文件名
 /// - `link_info`: 快捷方式信息
+/// - `name_suffix`: 快捷方式名称后缀（如架构变体的架构）
 ///
 /// # 返回值
 /// - `Ok(())`: 创建成功
@@ -1505,21 +2447,24 @@ fn create_program_shortcut(
     template: Option<Template>,
     use_filename: bool,
     create_dir: bool,
+    name_suffix: Option<String>,
 ) -> Result<(String, PathBuf)> {
     // 判断程序是否存在
     if let Ok(true) = is_running_under_wow64() {
         let alt = replace_ignore_case(&program_path.to_string_lossy(), "\\System32", "\\SysNative");
 
         if !Path::new(&alt).exists() {
-            write_console(
+            write_console_key(
                 ConsoleType::Warning,
+                "file.not_found",
                 &t!("file.not_found", path = program_path.display()),
             );
             return Err(anyhow!(t!("file.not_found", path = program_path.display())));
         }
     } else if !program_path.exists() {
-        write_console(
+        write_console_key(
             ConsoleType::Warning,
+            "file.not_found",
             &t!("file.not_found", path = program_path.display()),
         );
         return Err(anyhow!(t!("file.not_found", path = program_path.display())));
@@ -1542,8 +2487,9 @@ fn create_program_shortcut(
     let mut dest = if let Some(dest) = dest {
         dest.to_path_buf()
     } else {
-        write_console(
+        write_console_key(
             ConsoleType::Warning,
+            "config.miss_dest",
             &t!("config.miss_dest", path = program_path.display()),
         );
         return Err(anyhow!("configuration missing `dest`"));
@@ -1562,6 +2508,14 @@ fn create_program_shortcut(
         create_dir_all(&dest)?;
     }
 
+    // 读取版本信息时优先使用的语言
+    let name_langs = template
+        .as_ref()
+        .map(|t| t.name_language.lang_ids())
+        .unwrap_or_else(preferred_langs);
+    // 名称、图标、备注模板共用的变量，首次使用时才读取程序
+    let context = ProgramContext::new(program_path, &name_langs);
+
     // 快捷方式名称
     let mut name = {
         let stem = program_path
@@ -1572,6 +2526,15 @@ fn create_program_shortcut(
             .as_ref()
             .and_then(|t| t.name.as_deref())
             .unwrap_or(DEFAULT_NAME_TEMPLATE);
+        // 模板渲染结果为空（如模板有语法错误）时使用文件名
+        let render_name = || {
+            let name = context.render(name_template);
+            if name.trim().is_empty() {
+                stem.to_string()
+            } else {
+                name
+            }
+        };
 
         // 指定使用原始文件名
         if use_filename {
@@ -1582,20 +2545,24 @@ fn create_program_shortcut(
                 if validate_shortcut_name_for_config(name) {
                     name.to_string()
                 } else {
-                    write_console(
+                    write_console_key(
                         ConsoleType::Warning,
+                        "config.invalid_name",
                         &t!("config.invalid_name", name = name),
                     );
                     return Err(anyhow!(t!("config.invalid_name", name = name)));
                 }
             } else {
-                process_template(program_path, name_template)
+                render_name()
             }
         } else {
             // 没有配置文件，使用全局模板
-            process_template(program_path, name_template)
+            render_name()
         }
     };
+    if let Some(suffix) = name_suffix {
+        name = format!("{} {}", name, suffix);
+    }
 
     // 命令行
     let args = lnk_info.as_ref().and_then(|li| li.args.clone());
@@ -1629,8 +2596,9 @@ fn create_program_shortcut(
             }
 
             // 如果找不到就警告
-            write_console(
+            write_console_key(
                 ConsoleType::Warning,
+                "file.icon_not_found",
                 &t!("file.icon_not_found", path = file_part),
             );
             None
@@ -1639,12 +2607,13 @@ fn create_program_shortcut(
         .or_else(|| {
             template.as_ref().and_then(|t| {
                 t.icon.as_ref().and_then(|s| {
-                    let rendered = process_template(program_path, s);
+                    let rendered = context.render(s);
                     if Path::new(&rendered).exists() {
                         Some((rendered, 0))
                     } else {
-                        write_console(
+                        write_console_key(
                             ConsoleType::Warning,
+                            "file.icon_not_found",
                             &t!("file.icon_not_found", path = rendered),
                         );
                         None
@@ -1681,17 +2650,10 @@ fn create_program_shortcut(
     let window_state = lnk_info.as_ref().and_then(|li| li.window_state.clone());
 
     // 备注：优先配置项，如有模板则使用模板
-    let comment: Option<String> =
-        lnk_info
-            .as_ref()
-            .and_then(|li| li.comment.clone())
-            .or_else(|| {
-                template.and_then(|t| {
-                    t.comment
-                        .as_ref()
-                        .map(|tmpl| process_template(program_path, tmpl))
-                })
-            });
+    let comment: Option<String> = lnk_info
+        .as_ref()
+        .and_then(|li| li.comment.clone())
+        .or_else(|| template.and_then(|t| t.comment.as_ref().map(|tmpl| context.render(tmpl))));
 
     // 快捷键解析
     let hotkey: Option<u16> = lnk_info
@@ -1700,8 +2662,9 @@ fn create_program_shortcut(
         .and_then(|hotkey_str| match parse_hotkey(hotkey_str) {
             Ok(hk) => Some(hk),
             Err(_e) => {
-                write_console(
+                write_console_key(
                     ConsoleType::Warning,
+                    "config.invalid_hotkey",
                     &t!("config.invalid_hotkey", hotkey = hotkey_str),
                 );
                 None
@@ -1764,9 +2727,25 @@ fn create_program_shortcut(
         }
     }
 
+    // 可移动磁盘、网络位置上的图标使用缓存
+    let link_file = dest.join(format!("{}.lnk", name));
+    let icon = icon_cache::cache_icon(program_path, icon, &link_file);
+
+    // 扫描报告
+    let record = report::is_enabled().then(|| ShortcutRecord {
+        program: program_path.to_path_buf(),
+        name: name.clone(),
+        dest: dest.clone(),
+        icon: icon
+            .as_ref()
+            .map(|(path, index)| format!("{}#{}", path, index)),
+        args: args.clone(),
+        work_dir: work_dir.clone(),
+    });
+
     create_shortcut(
         program_path,
-        &dest.join(format!("{}.lnk", name)),
+        &link_file,
         args,
         icon,
         work_dir,
@@ -1774,5 +2753,8 @@ fn create_program_shortcut(
         comment,
         hotkey,
     )?;
-    Ok((name.clone(), dest.join(format!("{}.lnk", name))))
+    if let Some(record) = record {
+        report::record_shortcut(record);
+    }
+    Ok((name, link_file))
 }
@@ -0,0 +1,165 @@
+use crate::pe_resource::{find_resources, ResourceName, RT_MANIFEST};
+use anyhow::Result;
+use memmap2::Mmap;
+use roxmltree::{Document, Node};
+use std::fs::File;
+use std::path::Path;
+
+/// 项目模板中的默认程序标识名称，不能作为程序名称
+const PLACEHOLDER_NAMES: [&str; 4] = [
+    "CompanyName.ProductName.YourApplication",
+    "MyApplication.app",
+    "Application",
+    "*",
+];
+
+/// 程序清单（RT_MANIFEST）中的信息
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Manifest {
+    /// 程序标识名称（`assemblyIdentity` 的 `name`）
+    pub name: Option<String>,
+    /// 程序标识版本（`assemblyIdentity` 的 `version`）
+    pub version: Option<String>,
+    /// 处理器架构（`assemblyIdentity` 的 `processorArchitecture`）
+    pub processor_architecture: Option<String>,
+    /// 请求的执行级别：`asInvoker`、`highestAvailable`、`requireAdministrator`
+    pub execution_level: Option<String>,
+    /// `dpiAware` 设置，如 `true`、`true/pm`、`per monitor`
+    pub dpi_aware: Option<String>,
+    /// `dpiAwareness` 设置，如 `PerMonitorV2, PerMonitor`
+    pub dpi_awareness: Option<String>,
+}
+
+impl Manifest {
+    /// 读取程序的清单
+    ///
+    /// # 参数
+    /// - `path`: 程序路径
+    ///
+    /// # 返回值
+    /// - `Ok(Some(Manifest))`: 程序清单
+    /// - `Ok(None)`: 没有清单或清单无法解析
+    /// - `Err(...)`: 无法读取程序或不是有效的 PE 文件
+    pub fn read(path: impl AsRef<Path>) -> Result<Option<Manifest>> {
+        let file = File::open(path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+        Manifest::from_pe(&mmap)
+    }
+
+    /// 从 PE 文件内容中读取清单，优先使用程序清单（资源标识 1）
+    pub fn from_pe(bytes: &[u8]) -> Result<Option<Manifest>> {
+        let resources = find_resources(bytes, RT_MANIFEST)?;
+        let resource = resources
+            .iter()
+            .find(|resource| resource.name == ResourceName::Id(1))
+            .or_else(|| resources.first());
+        Ok(resource.and_then(|resource| Manifest::parse(resource.data)))
+    }
+
+    /// 解析清单 XML
+    ///
+    /// # 参数
+    /// - `data`: 清单内容（UTF-8 或带 BOM 的 UTF-16）
+    pub fn parse(data: &[u8]) -> Option<Manifest> {
+        let text = decode(data);
+        // 资源数据末尾可能有填充的空字符
+        let text = text.trim_end_matches(['\0', ' ', '\t', '\r', '\n']);
+        let document = Document::parse(text).ok()?;
+        let root = document.root_element();
+        if root.tag_name().name() != "assembly" {
+            return None;
+        }
+
+        // 程序自身的标识，依赖项中的标识在 dependency 元素内
+        let identity = root
+            .children()
+            .find(|node| node.tag_name().name() == "assemblyIdentity");
+        let identity_attr = |name: &str| {
+            identity
+                .and_then(|node| attribute(node, name))
+                .map(str::to_string)
+        };
+        let text_of = |name: &str| {
+            root.descendants()
+                .find(|node| node.tag_name().name() == name)
+                .and_then(|node| node.text())
+                .map(str::trim)
+                .filter(|text| !text.is_empty())
+                .map(str::to_string)
+        };
+
+        Some(Manifest {
+            name: identity_attr("name"),
+            version: identity_attr("version"),
+            processor_architecture: identity_attr("processorArchitecture"),
+            execution_level: root
+                .descendants()
+                .find(|node| node.tag_name().name() == "requestedExecutionLevel")
+                .and_then(|node| attribute(node, "level"))
+                .map(str::to_string),
+            dpi_aware: text_of("dpiAware"),
+            dpi_awareness: text_of("dpiAwareness"),
+        })
+    }
+
+    /// 可用作程序名称的标识名称
+    ///
+    /// 取以 `.` 分隔的最后一段（如 `Microsoft.Windows.Notepad` 为 `Notepad`），忽略项目模板中的默认名称
+    pub fn display_name(&self) -> Option<&str> {
+        let name = self.name.as_deref()?.trim();
+        if PLACEHOLDER_NAMES
+            .iter()
+            .any(|placeholder| placeholder.eq_ignore_ascii_case(name))
+        {
+            return None;
+        }
+        name.rsplit('.')
+            .find(|segment| {
+                !segment.is_empty()
+                    && !segment.chars().all(|c| c.is_ascii_digit())
+                    && !["exe", "app"].contains(&segment.to_ascii_lowercase().as_str())
+            })
+            .map(str::trim)
+    }
+
+    /// 是否要求以管理员身份运行
+    pub fn requires_admin(&self) -> bool {
+        self.execution_level
+            .as_deref()
+            .is_some_and(|level| level.eq_ignore_ascii_case("requireAdministrator"))
+    }
+
+    /// 是否声明支持高 DPI，`dpiAwareness` 优先于 `dpiAware`
+    pub fn is_dpi_aware(&self) -> bool {
+        if let Some(awareness) = &self.dpi_awareness {
+            return awareness
+                .split(',')
+                .map(str::trim)
+                .any(|value| !value.is_empty() && !value.eq_ignore_ascii_case("unaware"));
+        }
+        self.dpi_aware.as_deref().is_some_and(|value| {
+            let value = value.trim().to_ascii_lowercase();
+            value.starts_with("true") || value.starts_with("per monitor")
+        })
+    }
+}
+
+/// 读取元素属性（忽略命名空间）
+fn attribute<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes()
+        .find(|attr| attr.name() == name)
+        .map(|attr| attr.value())
+}
+
+/// 将清单内容解码为文本
+fn decode(data: &[u8]) -> String {
+    if let Some(data) = data.strip_prefix(&[0xFF, 0xFE]) {
+        let units: Vec<u16> = data
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        return String::from_utf16_lossy(&units);
+    }
+    let data = data.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(data);
+    String::from_utf8_lossy(data).to_string()
+}
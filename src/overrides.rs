@@ -0,0 +1,268 @@
+use crate::config::{ConfigInfo, DirConfig, DirKind};
+use crate::console::{write_console_key, ConsoleType};
+use rust_i18n::t;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// 目录配置文件名（按顺序查找）
+pub const OVERRIDE_FILE_NAMES: &[&str] = &[".autoshortcut.toml", "autoshortcut.toml"];
+
+/// 扫描中发现的目录配置
+pub struct DirOverride {
+    /// 目录配置文件路径
+    pub path: PathBuf,
+    /// 目录配置
+    pub config: DirConfig,
+}
+
+impl DirOverride {
+    /// 目录配置所在目录
+    pub fn dir(&self) -> &Path {
+        self.path.parent().unwrap_or(&self.path)
+    }
+
+    /// 指定的主程序路径
+    pub fn main_path(&self) -> Option<PathBuf> {
+        self.config.main.as_ref().map(|main| {
+            let main = PathBuf::from(main);
+            if main.is_absolute() {
+                main
+            } else {
+                self.dir().join(main)
+            }
+        })
+    }
+
+    /// 判断程序是否为目录配置的主程序，未指定主程序时总是成立
+    pub fn is_main(&self, program_path: &Path) -> bool {
+        self.main_path().is_none_or(|main| {
+            main.to_string_lossy().to_lowercase() == program_path.to_string_lossy().to_lowercase()
+        })
+    }
+
+    /// 指定的目录类型，指定主程序时视为绿色软件目录
+    pub fn kind(&self) -> Option<DirKind> {
+        self.config
+            .kind
+            .or(self.config.main.as_ref().map(|_| DirKind::Green))
+    }
+}
+
+/// 目录配置
+///
+/// 优先级：所在目录的配置 > 上级目录的配置 > 全局配置文件
+pub struct DirOverrides {
+    /// 扫描根目录
+    root: PathBuf,
+    /// 目录配置缓存
+    cache: Mutex<HashMap<PathBuf, Option<Arc<DirOverride>>>>,
+    /// 合并后的配置缓存
+    scoped: Mutex<HashMap<PathBuf, Option<Arc<ConfigInfo>>>>,
+}
+
+impl DirOverrides {
+    /// 创建目录配置
+    ///
+    /// # 参数
+    /// - `root`: 扫描根目录
+    pub fn new(root: &Path) -> Self {
+        DirOverrides {
+            root: root.to_path_buf(),
+            cache: Mutex::new(HashMap::new()),
+            scoped: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 读取目录中的目录配置（带缓存）
+    ///
+    /// # 参数
+    /// - `dir`: 目录路径
+    ///
+    /// # 返回值
+    /// - `Some(DirOverride)`: 目录中存在有效的目录配置
+    /// - `None`: 目录中没有目录配置，或解析失败
+    pub fn load(&self, dir: &Path) -> Option<Arc<DirOverride>> {
+        let mut cache = self.cache.lock().unwrap();
+        cache
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                let path = OVERRIDE_FILE_NAMES
+                    .iter()
+                    .map(|name| dir.join(name))
+                    .find(|path| path.is_file())?;
+                match DirConfig::parse_config_file(&path) {
+                    Ok(config) => Some(Arc::new(DirOverride { path, config })),
+                    Err(e) => {
+                        write_console_key(
+                            ConsoleType::Warning,
+                            "override.parse_failed",
+                            &t!("override.parse_failed", path = path.display(), error = e),
+                        );
+                        None
+                    }
+                }
+            })
+            .clone()
+    }
+
+    /// 获取从扫描根目录到指定目录（含）的全部目录配置
+    fn chain(&self, dir: &Path) -> Vec<Arc<DirOverride>> {
+        let Ok(rel) = dir.strip_prefix(&self.root) else {
+            return Vec::new();
+        };
+
+        let mut current = self.root.clone();
+        let mut chain = Vec::new();
+        chain.extend(self.load(&current));
+        for component in rel.components() {
+            current.push(component);
+            chain.extend(self.load(&current));
+        }
+        chain
+    }
+
+    /// 将目录配置合并到全局配置上，得到作用于指定目录的配置（按目录缓存）
+    ///
+    /// 同一扫描目录中的全局配置不变，因此缓存只按目录区分
+    ///
+    /// # 参数
+    /// - `global`: 全局配置
+    /// - `dir`: 目录路径
+    ///
+    /// # 返回值
+    /// - `Some(ConfigInfo)`: 合并后的配置
+    /// - `None`: 没有作用于该目录的目录配置，直接使用全局配置
+    pub fn scoped_config(
+        &self,
+        global: Option<&ConfigInfo>,
+        dir: &Path,
+    ) -> Option<Arc<ConfigInfo>> {
+        if let Some(scoped) = self.scoped.lock().unwrap().get(dir) {
+            return scoped.clone();
+        }
+        let scoped = self.merge_config(global, dir).map(Arc::new);
+        self.scoped
+            .lock()
+            .unwrap()
+            .insert(dir.to_path_buf(), scoped.clone());
+        scoped
+    }
+
+    /// 将目录配置合并到全局配置上
+    fn merge_config(&self, global: Option<&ConfigInfo>, dir: &Path) -> Option<ConfigInfo> {
+        let chain = self.chain(dir);
+        if chain.is_empty() {
+            return None;
+        }
+
+        let mut config = global.cloned().unwrap_or_default();
+        for item in &chain {
+            if item.config.template.is_some() {
+                config.template = item.config.template.clone();
+            }
+            if item.config.score_ratio.is_some() {
+                config.score_ratio = item.config.score_ratio;
+            }
+            // 内层目录的程序信息优先匹配
+            config
+                .shortcut
+                .splice(0..0, item.config.shortcut.iter().cloned());
+        }
+        Some(config)
+    }
+
+    /// 获取目录中各项配置的值与来源，用于输出识别过程说明
+    ///
+    /// # 参数
+    /// - `global`: 全局配置
+    /// - `dir`: 目录路径
+    ///
+    /// # 返回值
+    /// - `Vec<(配置项, 值, 来源)>`
+    pub fn explain(
+        &self,
+        global: Option<&ConfigInfo>,
+        dir: &Path,
+    ) -> Vec<(String, String, String)> {
+        let chain = self.chain(dir);
+        let mut settings = Vec::new();
+
+        // 仅作用于所在目录的配置
+        if let Some(own) = chain.last().filter(|item| item.dir() == dir) {
+            let source = own.path.display().to_string();
+            let config = &own.config;
+            if config.ignore {
+                settings.push(("ignore".to_string(), "true".to_string(), source.clone()));
+            }
+            if let Some(kind) = config.kind {
+                let kind = format!("{:?}", kind).to_lowercase();
+                settings.push(("kind".to_string(), kind, source.clone()));
+            }
+            for (key, value) in [
+                ("main", &config.main),
+                ("name", &config.name),
+                ("icon", &config.icon),
+                ("args", &config.args),
+                ("dest", &config.dest),
+                ("work_dir", &config.work_dir),
+                ("window_state", &config.window_state),
+                ("comment", &config.comment),
+                ("hotkey", &config.hotkey),
+            ] {
+                if let Some(value) = value {
+                    settings.push((key.to_string(), format!("{:?}", value), source.clone()));
+                }
+            }
+        }
+
+        // 作用于子目录的配置：取最内层的来源
+        let global_source = t!("override.source_global").to_string();
+        let template_name = chain
+            .iter()
+            .rev()
+            .find_map(|item| {
+                item.config
+                    .template
+                    .as_ref()
+                    .map(|template| (template.name.clone(), item.path.display().to_string()))
+            })
+            .or_else(|| {
+                global
+                    .and_then(|config| config.template.as_ref())
+                    .map(|template| (template.name.clone(), global_source.clone()))
+            });
+        if let Some((Some(name), source)) = template_name {
+            settings.push(("template.name".to_string(), format!("{:?}", name), source));
+        }
+
+        let score_ratio = chain
+            .iter()
+            .rev()
+            .find_map(|item| {
+                item.config
+                    .score_ratio
+                    .map(|ratio| (ratio, item.path.display().to_string()))
+            })
+            .or_else(|| {
+                global
+                    .and_then(|config| config.score_ratio)
+                    .map(|ratio| (ratio, global_source.clone()))
+            });
+        if let Some((ratio, source)) = score_ratio {
+            settings.push(("score_ratio".to_string(), ratio.to_string(), source));
+        }
+
+        for item in chain.iter().rev() {
+            if !item.config.shortcut.is_empty() {
+                settings.push((
+                    "shortcut".to_string(),
+                    item.config.shortcut.len().to_string(),
+                    item.path.display().to_string(),
+                ));
+            }
+        }
+
+        settings
+    }
+}
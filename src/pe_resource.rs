@@ -0,0 +1,153 @@
+use anyhow::{anyhow, Result};
+use goblin::pe::options::ParseOptions;
+use goblin::pe::PE;
+
+/// 图标资源类型
+pub const RT_ICON: u16 = 3;
+
+/// 图标组资源类型
+pub const RT_GROUP_ICON: u16 = 14;
+
+/// 版本信息资源类型
+pub const RT_VERSION: u16 = 16;
+
+/// 程序清单资源类型
+pub const RT_MANIFEST: u16 = 24;
+
+/// 资源目录项的标识
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceName {
+    /// 数字标识
+    Id(u16),
+    /// 字符串标识
+    Name(String),
+}
+
+/// PE 文件中的资源
+#[derive(Debug, Clone)]
+pub struct Resource<'a> {
+    /// 资源名称
+    pub name: ResourceName,
+    /// 语言标识
+    pub lang: u16,
+    /// 资源数据
+    pub data: &'a [u8],
+}
+
+/// 读取 PE 文件中指定类型的全部资源
+///
+/// # 参数
+/// - `bytes`: PE 文件内容
+/// - `resource_type`: 资源类型，如 `RT_VERSION`
+///
+/// # 返回值
+/// - `Ok(Vec<Resource>)`: 资源列表（没有该类型的资源时为空）
+/// - `Err(...)`: 不是有效的 PE 文件
+pub fn find_resources(bytes: &[u8], resource_type: u16) -> Result<Vec<Resource<'_>>> {
+    let mut options = ParseOptions::default();
+    options.parse_attribute_certificates = false;
+    options.parse_tls_data = false;
+    let pe = PE::parse_with_opts(bytes, &options)?;
+
+    let Some(table) = pe
+        .header
+        .optional_header
+        .and_then(|header| header.data_directories.get_resource_table().copied())
+    else {
+        return Ok(Vec::new());
+    };
+
+    // 将 RVA 转换为文件偏移
+    let sections = &pe.sections;
+    let to_offset = |rva: u32| -> Option<usize> {
+        sections.iter().find_map(|section| {
+            let size = section.virtual_size.max(section.size_of_raw_data);
+            (rva >= section.virtual_address && rva < section.virtual_address.saturating_add(size))
+                .then(|| (rva - section.virtual_address + section.pointer_to_raw_data) as usize)
+        })
+    };
+    let root = to_offset(table.virtual_address).ok_or_else(|| anyhow!("invalid resource table"))?;
+    let directory = bytes
+        .get(root..)
+        .ok_or_else(|| anyhow!("invalid resource table"))?;
+
+    // 资源目录共三层：类型 / 名称 / 语言
+    let mut resources = Vec::new();
+    for (type_name, type_offset) in read_directory(directory, 0) {
+        if type_name != ResourceName::Id(resource_type) {
+            continue;
+        }
+        for (name, name_offset) in read_directory(directory, type_offset) {
+            for (lang, data_offset) in read_directory(directory, name_offset) {
+                // 数据项：RVA、大小、代码页、保留
+                let Some(rva) = read_u32(directory, data_offset) else {
+                    continue;
+                };
+                let Some(size) = read_u32(directory, data_offset + 4) else {
+                    continue;
+                };
+                let data = to_offset(rva)
+                    .and_then(|offset| bytes.get(offset..offset.checked_add(size as usize)?));
+                if let Some(data) = data {
+                    resources.push(Resource {
+                        name: name.clone(),
+                        lang: match lang {
+                            ResourceName::Id(id) => id,
+                            ResourceName::Name(_) => 0,
+                        },
+                        data,
+                    });
+                }
+            }
+        }
+    }
+    Ok(resources)
+}
+
+/// 读取资源目录中的目录项
+///
+/// # 返回值
+/// - `Vec<(标识, 子目录或数据项的偏移)>`
+fn read_directory(directory: &[u8], offset: usize) -> Vec<(ResourceName, usize)> {
+    let (Some(named), Some(ids)) = (
+        read_u16(directory, offset + 12),
+        read_u16(directory, offset + 14),
+    ) else {
+        return Vec::new();
+    };
+
+    let mut entries = Vec::new();
+    for index in 0..(named as usize + ids as usize) {
+        let entry = offset + 16 + index * 8;
+        let (Some(name), Some(target)) =
+            (read_u32(directory, entry), read_u32(directory, entry + 4))
+        else {
+            break;
+        };
+        let name = if name & 0x8000_0000 != 0 {
+            // 字符串标识：长度 + UTF-16 字符
+            let start = (name & 0x7FFF_FFFF) as usize;
+            let len = read_u16(directory, start).unwrap_or(0) as usize;
+            let units: Vec<u16> = (0..len)
+                .map_while(|i| read_u16(directory, start + 2 + i * 2))
+                .collect();
+            ResourceName::Name(String::from_utf16_lossy(&units))
+        } else {
+            ResourceName::Id(name as u16)
+        };
+        entries.push((name, (target & 0x7FFF_FFFF) as usize));
+    }
+    entries
+}
+
+/// 读取小端序 `u16`
+pub fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset.checked_add(2)?)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+/// 读取小端序 `u32`
+pub fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset.checked_add(4)?)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
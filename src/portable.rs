@@ -0,0 +1,154 @@
+use crate::config::Lnk;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// PortableApps.com 格式的程序信息文件（相对于软件根目录）
+pub const APP_INFO_PATH: &str = r"App\AppInfo\appinfo.ini";
+
+/// PortableApps.com 格式的程序图标（相对于软件根目录）
+pub const APP_ICON_PATH: &str = r"App\AppInfo\appicon.ico";
+
+/// 向上查找程序信息文件的最大层数（启动器位于根目录，程序本体位于 `App\Xxx\` 下）
+const MAX_UPWARD_DEPTH: usize = 3;
+
+/// PortableApps.com 格式的程序信息（`App\AppInfo\appinfo.ini`）
+#[derive(Clone, Debug, Default)]
+pub struct PortableAppInfo {
+    /// 软件根目录
+    pub root: PathBuf,
+    /// 程序名称（`[Details] Name`）
+    pub name: Option<String>,
+    /// 发布者（`[Details] Publisher`）
+    pub publisher: Option<String>,
+    /// 分类（`[Details] Category`）
+    pub category: Option<String>,
+    /// 启动器（`[Control] Start`，相对于软件根目录）
+    pub start: Option<String>,
+    /// 图标数量（`[Control] Icons`）
+    pub icons: Option<u32>,
+}
+
+impl PortableAppInfo {
+    /// 读取软件根目录中的程序信息
+    ///
+    /// # 参数
+    /// - `root`: 软件根目录
+    ///
+    /// # 返回值
+    /// - `Some(PortableAppInfo)`: 目录为 PortableApps.com 格式
+    /// - `None`: 不存在程序信息文件，或读取失败
+    pub fn load(root: &Path) -> Option<PortableAppInfo> {
+        let bytes = fs::read(root.join(APP_INFO_PATH)).ok()?;
+        let sections = parse_ini(&decode_text(&bytes));
+        let get = |section: &str, key: &str| {
+            sections
+                .get(&section.to_lowercase())
+                .and_then(|values| values.get(&key.to_lowercase()))
+                .filter(|value| !value.is_empty())
+                .cloned()
+        };
+
+        Some(PortableAppInfo {
+            root: root.to_path_buf(),
+            name: get("Details", "Name"),
+            publisher: get("Details", "Publisher"),
+            category: get("Details", "Category"),
+            start: get("Control", "Start"),
+            icons: get("Control", "Icons").and_then(|icons| icons.parse().ok()),
+        })
+    }
+
+    /// 查找程序所属的 PortableApps.com 软件（程序所在目录及上级目录）
+    ///
+    /// # 参数
+    /// - `program_path`: 程序路径
+    pub fn for_program(program_path: &Path) -> Option<PortableAppInfo> {
+        program_path
+            .ancestors()
+            .skip(1)
+            .take(MAX_UPWARD_DEPTH)
+            .find_map(PortableAppInfo::load)
+    }
+
+    /// 启动器路径（不存在时返回 `None`）
+    pub fn launcher(&self) -> Option<PathBuf> {
+        self.start
+            .as_ref()
+            .map(|start| self.root.join(start))
+            .filter(|path| path.is_file())
+    }
+
+    /// 程序图标路径（不存在时返回 `None`）
+    pub fn icon(&self) -> Option<PathBuf> {
+        if self.icons == Some(0) {
+            return None;
+        }
+        Some(self.root.join(APP_ICON_PATH)).filter(|path| path.is_file())
+    }
+
+    /// 判断程序是否为启动器
+    pub fn is_launcher(&self, program_path: &Path) -> bool {
+        self.launcher().is_some_and(|launcher| {
+            launcher.to_string_lossy().to_lowercase()
+                == program_path.to_string_lossy().to_lowercase()
+        })
+    }
+
+    /// 将程序信息中的名称、图标合并到启动器的快捷方式信息上（配置文件优先）
+    ///
+    /// # 参数
+    /// - `program_path`: 程序路径
+    /// - `lnk_info`: 配置文件中的快捷方式信息
+    pub fn merge_lnk(&self, program_path: &Path, lnk_info: Option<Lnk>) -> Option<Lnk> {
+        if !self.is_launcher(program_path) {
+            return lnk_info;
+        }
+
+        let mut lnk =
+            lnk_info.unwrap_or_else(|| Lnk::new(program_path.to_string_lossy().to_string()));
+        if lnk.name.is_none() {
+            lnk.name = self.name.clone();
+        }
+        if lnk.icon.is_none() {
+            lnk.icon = self.icon().map(|icon| icon.to_string_lossy().to_string());
+        }
+        Some(lnk)
+    }
+}
+
+/// 解码文本（支持 UTF-8、带 BOM 的 UTF-8 与 UTF-16 LE）
+fn decode_text(bytes: &[u8]) -> String {
+    if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        let units: Vec<u16> = rest
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        return String::from_utf16_lossy(&units);
+    }
+    let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+    String::from_utf8_lossy(bytes).to_string()
+}
+
+/// 解析 ini 文件（节名与键名转为小写）
+fn parse_ini(content: &str) -> HashMap<String, HashMap<String, String>> {
+    let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut current = String::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            current = section.trim().to_lowercase();
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            sections
+                .entry(current.clone())
+                .or_default()
+                .insert(key.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+    sections
+}
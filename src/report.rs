@@ -0,0 +1,234 @@
+use crate::console::ConsoleType;
+use anyhow::Result;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 扫描报告（仅在指定 `--report` 或 `--format json` 时记录）
+static REPORT: Mutex<Option<Report>> = Mutex::new(None);
+
+/// 扫描报告
+///
+/// 不包含时间等每次运行都会变化的信息，便于比较两次运行的报告
+#[derive(Serialize, Default, Debug)]
+pub struct Report {
+    /// 程序版本
+    pub version: String,
+    /// 扫描目录
+    pub roots: Vec<PathBuf>,
+    /// 访问的目录及其识别结果
+    pub directories: Vec<DirectoryRecord>,
+    /// 绿色软件主程序候选及其评分明细
+    pub candidates: Vec<CandidateRecord>,
+    /// 列出的程序（`--list` 模式）
+    pub programs: Vec<ProgramRecord>,
+    /// 被过滤条件排除的程序
+    pub excluded: Vec<ExcludedRecord>,
+    /// 创建的快捷方式
+    pub shortcuts: Vec<ShortcutRecord>,
+    /// 警告、错误信息
+    pub messages: Vec<MessageRecord>,
+}
+
+/// 目录识别结果
+#[derive(Serialize, Debug)]
+pub struct DirectoryRecord {
+    /// 目录路径
+    pub path: PathBuf,
+    /// 识别结果：`category`、`single`、`hybrid`、`green`、`skipped`（含未识别到主程序的目录）
+    pub kind: String,
+    /// 原因
+    pub reason: String,
+}
+
+/// 评分项
+#[derive(Serialize, Clone, Debug)]
+pub struct ScoreItem {
+    /// 评分项名称
+    pub metric: String,
+    /// 分数
+    pub score: i32,
+}
+
+/// 绿色软件主程序候选
+#[derive(Serialize, Clone, Debug)]
+pub struct CandidateRecord {
+    /// 程序路径
+    pub path: PathBuf,
+    /// 总分
+    pub score: i32,
+    /// 阈值（得分需超过阈值）
+    pub threshold: i32,
+    /// 评分明细
+    pub breakdown: Vec<ScoreItem>,
+    /// 是否被选为主程序
+    pub selected: bool,
+}
+
+/// 列出的程序
+#[derive(Serialize, Debug)]
+pub struct ProgramRecord {
+    /// 程序路径
+    pub path: PathBuf,
+    /// 安装程序类型（仅安装程序）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installer: Option<String>,
+}
+
+/// 被过滤条件排除的程序
+#[derive(Serialize, Debug)]
+pub struct ExcludedRecord {
+    /// 程序路径
+    pub path: PathBuf,
+    /// 过滤条件名称
+    pub filter: String,
+}
+
+/// 创建的快捷方式
+#[derive(Serialize, Debug)]
+pub struct ShortcutRecord {
+    /// 程序路径
+    pub program: PathBuf,
+    /// 快捷方式名称
+    pub name: String,
+    /// 快捷方式位置
+    pub dest: PathBuf,
+    /// 图标（`path#index`）
+    pub icon: Option<String>,
+    /// 命令行参数
+    pub args: Option<String>,
+    /// 工作目录
+    pub work_dir: Option<String>,
+}
+
+/// 警告、错误信息
+#[derive(Serialize, Debug)]
+pub struct MessageRecord {
+    /// 级别：`warning`、`error`
+    pub level: String,
+    /// 国际化键
+    pub key: String,
+    /// 消息
+    pub message: String,
+}
+
+/// 开始记录扫描报告
+pub fn enable() {
+    *REPORT.lock().unwrap() = Some(Report {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        ..Default::default()
+    });
+}
+
+/// 是否记录扫描报告
+pub fn is_enabled() -> bool {
+    REPORT.lock().unwrap().is_some()
+}
+
+/// 修改扫描报告（未开始记录时忽略）
+fn update(f: impl FnOnce(&mut Report)) {
+    if let Some(report) = REPORT.lock().unwrap().as_mut() {
+        f(report);
+    }
+}
+
+/// 读取扫描报告（未开始记录时返回 `None`）
+pub fn inspect<R>(f: impl FnOnce(&Report) -> R) -> Option<R> {
+    REPORT.lock().unwrap().as_ref().map(f)
+}
+
+/// 记录扫描目录
+pub fn record_root(path: &Path) {
+    update(|report| report.roots.push(path.to_path_buf()));
+}
+
+/// 记录目录识别结果
+///
+/// # 参数
+/// - `path`: 目录路径
+/// - `kind`: 识别结果
+/// - `reason`: 原因
+pub fn record_directory(path: &Path, kind: &str, reason: &str) {
+    update(|report| {
+        report.directories.push(DirectoryRecord {
+            path: path.to_path_buf(),
+            kind: kind.to_string(),
+            reason: reason.to_string(),
+        })
+    });
+}
+
+/// 记录绿色软件主程序候选
+pub fn record_candidates(candidates: Vec<CandidateRecord>) {
+    update(|report| report.candidates.extend(candidates));
+}
+
+/// 记录列出的程序
+///
+/// # 参数
+/// - `path`: 程序路径
+/// - `installer`: 安装程序类型
+pub fn record_program(path: &Path, installer: Option<&str>) {
+    update(|report| {
+        report.programs.push(ProgramRecord {
+            path: path.to_path_buf(),
+            installer: installer.map(str::to_string),
+        })
+    });
+}
+
+/// 记录被过滤条件排除的程序
+///
+/// # 参数
+/// - `path`: 程序路径
+/// - `filter`: 过滤条件名称
+pub fn record_excluded(path: &Path, filter: &str) {
+    update(|report| {
+        report.excluded.push(ExcludedRecord {
+            path: path.to_path_buf(),
+            filter: filter.to_string(),
+        })
+    });
+}
+
+/// 记录创建的快捷方式
+pub fn record_shortcut(shortcut: ShortcutRecord) {
+    update(|report| report.shortcuts.push(shortcut));
+}
+
+/// 记录警告、错误信息
+///
+/// # 参数
+/// - `console_type`: 控制台类型（仅记录警告、错误）
+/// - `key`: 国际化键
+/// - `message`: 消息
+pub fn record_message(console_type: &ConsoleType, key: &str, message: &str) {
+    let level = match console_type {
+        ConsoleType::Warning => "warning",
+        ConsoleType::Error => "error",
+        _ => return,
+    };
+    update(|report| {
+        report.messages.push(MessageRecord {
+            level: level.to_string(),
+            key: key.to_string(),
+            message: message.to_string(),
+        })
+    });
+}
+
+/// 将扫描报告转换为 JSON
+pub fn to_json() -> Result<String> {
+    let report = REPORT.lock().unwrap();
+    Ok(serde_json::to_string_pretty(&*report)?)
+}
+
+/// 保存扫描报告
+///
+/// # 参数
+/// - `path`: 报告文件路径
+pub fn save(path: &Path) -> Result<()> {
+    fs::write(path, to_json()?)?;
+    Ok(())
+}
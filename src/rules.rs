@@ -0,0 +1,384 @@
+use crate::config::ConfigInfo;
+use crate::console::{write_console_key, ConsoleType};
+use glob::{MatchOptions, Pattern};
+use rust_i18n::t;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf, Prefix};
+use std::sync::{Arc, Mutex};
+
+/// 目录级忽略规则文件名
+pub const IGNORE_FILE_NAME: &str = ".autoshortcutignore";
+
+/// 默认排除的系统目录
+const SYSTEM_EXCLUDED_DIRS: &[&str] = &[
+    "$RECYCLE.BIN",
+    "System Volume Information",
+    "Recovery",
+    "Config.Msi",
+    "MSOCache",
+];
+
+/// 匹配选项：不区分大小写，`*` 不匹配路径分隔符
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// 规则匹配方式
+enum Matcher {
+    /// 匹配文件名（规则中不含 `/`），不含通配符时也匹配不含扩展名的文件名
+    Name { pattern: Pattern, literal: bool },
+    /// 匹配相对于规则所在目录的路径
+    Relative(Pattern),
+    /// 匹配绝对路径（规范化后的路径组件）
+    Absolute(Vec<String>),
+}
+
+/// 单条忽略、包含规则
+struct Rule {
+    /// 规则所在目录（相对路径以此为基准）
+    base: PathBuf,
+    /// 匹配方式
+    matcher: Matcher,
+    /// 取反规则（`!`）
+    negated: bool,
+    /// 仅匹配目录（以 `/` 结尾）
+    dir_only: bool,
+}
+
+impl Rule {
+    /// 解析单条规则
+    ///
+    /// # 参数
+    /// - `line`: 规则文本
+    /// - `base`: 规则所在目录
+    ///
+    /// # 返回值
+    /// - `Ok(Some(Rule))`: 解析成功
+    /// - `Ok(None)`: 空行或注释
+    /// - `Err(...)`：通配符语法错误
+    fn parse(line: &str, base: &Path) -> Result<Option<Rule>, glob::PatternError> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(None);
+        }
+
+        // `\!`、`\#` 表示以 `!`、`#` 开头的文件名
+        let (negated, line) = if let Some(rest) = line.strip_prefix('!') {
+            (true, rest)
+        } else if line.starts_with("\\!") || line.starts_with("\\#") {
+            (false, &line[1..])
+        } else {
+            (false, line)
+        };
+
+        // 绝对路径：仅在解析时规范化一次
+        let path = Path::new(line);
+        if path.is_absolute() {
+            let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+            return Ok(Some(Rule {
+                base: base.to_path_buf(),
+                matcher: Matcher::Absolute(path_key(&path)),
+                negated,
+                dir_only: false,
+            }));
+        }
+
+        let pattern = line.replace('\\', "/");
+        let dir_only = pattern.ends_with('/');
+        let pattern = pattern.trim_end_matches('/');
+        if pattern.is_empty() {
+            return Ok(None);
+        }
+
+        // 以 `/` 开头或包含 `/` 的规则相对于规则所在目录
+        let matcher = if let Some(anchored) = pattern.strip_prefix('/') {
+            Matcher::Relative(Pattern::new(anchored)?)
+        } else if pattern.contains('/') {
+            Matcher::Relative(Pattern::new(pattern)?)
+        } else {
+            Matcher::Name {
+                pattern: Pattern::new(pattern)?,
+                literal: !pattern.contains(['*', '?', '[']),
+            }
+        };
+
+        Ok(Some(Rule {
+            base: base.to_path_buf(),
+            matcher,
+            negated,
+            dir_only,
+        }))
+    }
+
+    /// 判断路径是否匹配规则
+    ///
+    /// # 参数
+    /// - `path`: 待匹配路径
+    /// - `key`: 待匹配路径规范化后的路径组件
+    /// - `is_dir`: 是否为目录
+    fn matches(&self, path: &Path, key: &[String], is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+
+        match &self.matcher {
+            Matcher::Name { pattern, literal } => {
+                let Some(name) = path.file_name() else {
+                    return false;
+                };
+                if pattern.matches_with(&name.to_string_lossy(), MATCH_OPTIONS) {
+                    return true;
+                }
+                *literal
+                    && !is_dir
+                    && path.file_stem().is_some_and(|stem| {
+                        pattern.matches_with(&stem.to_string_lossy(), MATCH_OPTIONS)
+                    })
+            }
+            Matcher::Relative(pattern) => path.strip_prefix(&self.base).is_ok_and(|rel| {
+                let rel = rel
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                !rel.is_empty() && pattern.matches_with(&rel, MATCH_OPTIONS)
+            }),
+            Matcher::Absolute(rule_key) => key.starts_with(rule_key),
+        }
+    }
+}
+
+/// 路径组件（小写），用于不区分大小写地比较绝对路径
+fn path_key(path: &Path) -> Vec<String> {
+    path.components()
+        .filter(|c| !matches!(c, Component::RootDir | Component::CurDir))
+        .map(|c| match c {
+            // `\\?\D:` 与 `D:` 视为相同
+            Component::Prefix(prefix) => match prefix.kind() {
+                Prefix::VerbatimDisk(disk) | Prefix::Disk(disk) => {
+                    format!("{}:", disk.to_ascii_lowercase() as char)
+                }
+                _ => prefix.as_os_str().to_string_lossy().to_lowercase(),
+            },
+            _ => c.as_os_str().to_string_lossy().to_lowercase(),
+        })
+        .collect()
+}
+
+/// 解析规则列表，忽略无效规则
+fn parse_rules<'a>(lines: impl IntoIterator<Item = &'a str>, base: &Path) -> Vec<Rule> {
+    lines
+        .into_iter()
+        .filter_map(|line| match Rule::parse(line, base) {
+            Ok(rule) => rule,
+            Err(e) => {
+                write_console_key(
+                    ConsoleType::Warning,
+                    "config.invalid_rule",
+                    &t!("config.invalid_rule", rule = line, error = e),
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+/// 忽略、包含规则
+///
+/// 规则语法与 `.gitignore` 相同：
+/// - 不含 `/` 的规则匹配任意层级的文件名或目录名，如 `*.old.exe`
+/// - 以 `/` 开头或包含 `/` 的规则匹配相对于扫描根目录（或规则文件所在目录）的路径，如 `/Tools/Temp`、`**/Temp/**`
+/// - 以 `/` 结尾的规则仅匹配目录
+/// - 以 `!` 开头的规则为取反规则，后面的规则优先
+/// - 绝对路径匹配该路径及其中的文件
+///
+/// 扫描目录中的 `.autoshortcutignore` 文件会追加到配置文件规则之后，仅作用于其所在目录。
+pub struct IgnoreRules {
+    /// 扫描根目录
+    root: PathBuf,
+    /// 规范化后的扫描根目录
+    canonical_root: PathBuf,
+    /// 忽略规则
+    ignore: Vec<Rule>,
+    /// 包含规则
+    include: Vec<Rule>,
+    /// 目录级规则缓存
+    dir_rules: Mutex<HashMap<PathBuf, Arc<Vec<Rule>>>>,
+}
+
+impl IgnoreRules {
+    /// 根据配置文件创建规则
+    ///
+    /// # 参数
+    /// - `root`: 扫描根目录
+    /// - `config_info`: 配置文件信息
+    pub fn new(root: &Path, config_info: Option<&ConfigInfo>) -> Self {
+        let system_dirs = SYSTEM_EXCLUDED_DIRS
+            .iter()
+            .map(|dir| format!("{}/", dir))
+            .collect::<Vec<_>>();
+        let mut ignore = parse_rules(system_dirs.iter().map(String::as_str), root);
+        let mut include = Vec::new();
+
+        if let Some(config) = config_info {
+            ignore.extend(parse_rules(config.ignore.iter().map(String::as_str), root));
+            include = parse_rules(config.include.iter().map(String::as_str), root);
+        }
+
+        IgnoreRules {
+            root: root.to_path_buf(),
+            canonical_root: root.canonicalize().unwrap_or_else(|_| root.to_path_buf()),
+            ignore,
+            include,
+            dir_rules: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 扫描根目录
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// 忽略指定的路径（如配置文件自身）
+    ///
+    /// # 参数
+    /// - `path`: 需要忽略的路径
+    pub fn add_path(&mut self, path: &Path) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.ignore.push(Rule {
+            base: self.root.clone(),
+            matcher: Matcher::Absolute(path_key(&path)),
+            negated: false,
+            dir_only: false,
+        });
+    }
+
+    /// 判断文件或目录是否被忽略
+    ///
+    /// 与 `.gitignore` 相同，父目录被忽略时，其中的文件无法通过取反规则重新包含。
+    ///
+    /// # 参数
+    /// - `path`: 文件或目录路径
+    /// - `is_dir`: 是否为目录
+    ///
+    /// # 返回值
+    /// - `true`: 被忽略
+    /// - `false`: 未被忽略
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        if let Ok(rel) = path.strip_prefix(&self.root) {
+            // 扫描根目录本身不会被忽略
+            let components = rel.components().collect::<Vec<_>>();
+            if components.is_empty() {
+                return false;
+            }
+
+            let mut ancestor = self.root.clone();
+            for component in &components[..components.len() - 1] {
+                ancestor.push(component);
+                if self.evaluate(&ancestor, true) {
+                    return true;
+                }
+            }
+        }
+
+        self.evaluate(path, is_dir)
+    }
+
+    /// 判断程序是否在包含列表中，未配置包含列表时总是包含
+    ///
+    /// 程序自身或其任一上级目录（扫描根目录内）匹配即视为包含。
+    ///
+    /// # 参数
+    /// - `path`: 程序路径
+    pub fn is_included(&self, path: &Path) -> bool {
+        if self.include.is_empty() {
+            return true;
+        }
+
+        let mut candidates = vec![(path.to_path_buf(), false)];
+        if let Ok(rel) = path.strip_prefix(&self.root) {
+            let mut ancestor = self.root.clone();
+            for component in rel.parent().into_iter().flat_map(Path::components) {
+                ancestor.push(component);
+                candidates.push((ancestor.clone(), true));
+            }
+        }
+        let keys = candidates
+            .iter()
+            .map(|(p, _)| self.path_key(p))
+            .collect::<Vec<_>>();
+
+        let mut included = false;
+        for rule in &self.include {
+            if candidates
+                .iter()
+                .zip(&keys)
+                .any(|((p, is_dir), key)| rule.matches(p, key, *is_dir))
+            {
+                included = !rule.negated;
+            }
+        }
+        included
+    }
+
+    /// 依次应用配置文件规则和目录级规则，最后匹配的规则生效
+    fn evaluate(&self, path: &Path, is_dir: bool) -> bool {
+        let key = self.path_key(path);
+        let dir_rules = self.dir_rules_for(path);
+
+        let mut ignored = false;
+        for rule in self
+            .ignore
+            .iter()
+            .chain(dir_rules.iter().flat_map(|rules| rules.iter()))
+        {
+            if rule.matches(path, &key, is_dir) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+
+    /// 扫描根目录内的路径通过规范化的根目录计算，避免逐个规范化
+    fn path_key(&self, path: &Path) -> Vec<String> {
+        match path.strip_prefix(&self.root) {
+            Ok(rel) => path_key(&self.canonical_root.join(rel)),
+            Err(_) => path_key(path),
+        }
+    }
+
+    /// 获取从扫描根目录到路径所在目录的全部目录级规则
+    fn dir_rules_for(&self, path: &Path) -> Vec<Arc<Vec<Rule>>> {
+        let Some(rel) = path
+            .parent()
+            .and_then(|parent| parent.strip_prefix(&self.root).ok())
+        else {
+            return Vec::new();
+        };
+
+        let mut dir = self.root.clone();
+        let mut rules = vec![self.load_dir_rules(&dir)];
+        for component in rel.components() {
+            dir.push(component);
+            rules.push(self.load_dir_rules(&dir));
+        }
+        rules
+    }
+
+    /// 读取目录中的 `.autoshortcutignore` 文件（带缓存）
+    fn load_dir_rules(&self, dir: &Path) -> Arc<Vec<Rule>> {
+        let mut cache = self.dir_rules.lock().unwrap();
+        cache
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                let rules = fs::read_to_string(dir.join(IGNORE_FILE_NAME))
+                    .map(|content| parse_rules(content.lines(), dir))
+                    .unwrap_or_default();
+                Arc::new(rules)
+            })
+            .clone()
+    }
+}
@@ -1,40 +1,170 @@
-use crate::utils::{
-    get_exe_company_name, get_exe_copyright, get_exe_description, get_exe_file_version,
-    get_exe_original_filename, get_exe_product_name, get_program_arch, sanitize_description,
-    sanitize_orig_filename,
-};
+use crate::authenticode::Signature;
+use crate::console::{write_console_key, ConsoleType};
+use crate::known_apps;
+use crate::manifest::Manifest;
+use crate::portable::PortableAppInfo;
+use crate::template_parser::{CompareOp, Expr, Filter, FilterKind, Segment, Template};
+use crate::utils::{detect_runtime, get_program_arch, sanitize_description, sanitize_orig_filename};
+use crate::version_info::{lang_tag, lang_tags, VersionInfo};
 use chrono::{DateTime, Local, NaiveDateTime};
+use goblin::pe::options::ParseOptions;
+use goblin::pe::PE;
+use memmap2::Mmap;
+use rust_i18n::t;
 use serde::Serialize;
-use serde_json::Value;
+use serde_json::{json, Map, Value};
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::path::Path;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// 模板变量的来源
+pub trait TemplateContext {
+    /// 读取变量，支持以 `.` 访问对象成员，如 `version.major`
+    ///
+    /// # 返回值
+    /// - `Some(Value)`: 变量值
+    /// - `None`: 没有该变量
+    fn lookup(&self, name: &str) -> Option<Value>;
+}
 
-/// 渲染模板
-///
-/// # 参数
-///
-/// - `path` - 程序路径
-/// - `template` - 模板字符串
-///
-/// # 返回值
-///
-/// 渲染后的字符串
-pub fn process_template(path: &Path, template: &str) -> String {
-    let mut engine = TemplateEngine::new();
-    engine.render(template, &render_var(path)).unwrap()
+impl TemplateContext for Value {
+    fn lookup(&self, name: &str) -> Option<Value> {
+        let mut current = self;
+        for part in name.split('.') {
+            current = current.as_object()?.get(part)?;
+        }
+        Some(current.clone())
+    }
 }
 
-/// 渲染变量
-///
-/// # 参数
-///
-/// - `path` - 程序路径
-///
-/// # 返回值
+/// 模板变量分组，同一组的变量来自同一数据来源，首次访问时一起计算
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum VarGroup {
+    /// 文件系统中的路径、大小与时间，不读取程序内容
+    File,
+    /// 版本信息
+    Version,
+    /// PE 文件头
+    Pe,
+    /// 数字签名
+    Signature,
+    /// 程序清单
+    Manifest,
+    /// 运行时、PortableApps.com 与内置的常见程序信息
+    App,
+}
+
+impl VarGroup {
+    /// 变量所属的分组
+    fn of(name: &str) -> Option<VarGroup> {
+        let root = name.split('.').next().unwrap_or_default();
+        // 各语言的版本信息，如 `product@en`
+        if root.contains('@') {
+            return Some(VarGroup::Version);
+        }
+        Some(match root {
+            "exec" | "stem" | "ext" | "parent" | "parent_name" | "size" | "size_kb" | "size_mb"
+            | "size_gb" | "size_tb" | "create_time" | "modified_time" | "accessed_time"
+            | "file" | "dir" => VarGroup::File,
+            "desc" | "desc_raw" | "product" | "product_raw" | "company" | "orig_filename"
+            | "orig_filename_raw" | "copyright" | "version" | "product_version"
+            | "internal_name" | "language" => VarGroup::Version,
+            "arch" | "arch_num" | "pe" => VarGroup::Pe,
+            "signer" | "signed_valid" | "signature" => VarGroup::Signature,
+            "manifest" | "manifest_name" | "manifest_name_raw" | "manifest_version"
+            | "manifest_arch" | "execution_level" | "dpi_aware" => VarGroup::Manifest,
+            "runtime" | "pa_name" | "pa_category" | "pa_publisher" | "known_name"
+            | "known_category" => VarGroup::App,
+            _ => return None,
+        })
+    }
+}
+
+/// 程序的模板变量
 ///
-/// 变量 HashMap
-fn render_var(path: &Path) -> HashMap<String, String> {
-    // 构造上下文（小写 key，不带{}）
+/// 变量在首次访问时按分组计算并缓存，同一程序的名称、图标、备注等模板共用一个上下文；
+/// 只使用文件系统变量（如 `{stem}`）的模板不会读取程序内容
+pub struct ProgramContext {
+    /// 程序路径
+    path: PathBuf,
+    /// 读取版本信息时优先使用的语言标识
+    langs: Vec<u16>,
+    /// 已计算的变量分组
+    groups: RefCell<HashMap<VarGroup, Value>>,
+}
+
+impl ProgramContext {
+    /// 创建程序的模板变量上下文
+    ///
+    /// # 参数
+    ///
+    /// - `path` - 程序路径
+    /// - `langs` - 读取版本信息时优先使用的语言标识
+    pub fn new(path: &Path, langs: &[u16]) -> Self {
+        ProgramContext {
+            path: path.to_path_buf(),
+            langs: langs.to_vec(),
+            groups: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// 渲染模板，模板有语法错误时为空
+    pub fn render(&self, template: &str) -> String {
+        compile(template)
+            .map(|template| render_compiled(&template, self))
+            .unwrap_or_default()
+    }
+
+    /// 变量所属的分组是否已经计算
+    #[cfg(test)]
+    pub fn is_loaded(&self, name: &str) -> bool {
+        VarGroup::of(name).is_some_and(|group| self.groups.borrow().contains_key(&group))
+    }
+
+    /// 计算一组变量
+    fn load(&self, group: VarGroup) -> Value {
+        let path = self.path.as_path();
+        match group {
+            VarGroup::File => file_vars(path),
+            VarGroup::Version => version_vars(path, &self.langs),
+            VarGroup::Pe => pe_vars(path),
+            VarGroup::Signature => signature_vars(path),
+            VarGroup::Manifest => manifest_vars(path),
+            VarGroup::App => app_vars(path),
+        }
+    }
+}
+
+impl TemplateContext for ProgramContext {
+    fn lookup(&self, name: &str) -> Option<Value> {
+        // 辅助：desc_or_stem
+        if name == "desc_or_stem" {
+            return self
+                .lookup("desc")
+                .filter(|desc| desc.as_str().is_some_and(|desc| !desc.is_empty()))
+                .or_else(|| self.lookup("stem"));
+        }
+
+        let group = VarGroup::of(name)?;
+        let mut groups = self.groups.borrow_mut();
+        groups
+            .entry(group)
+            .or_insert_with(|| self.load(group))
+            .lookup(name)
+    }
+}
+
+/// 将平铺的变量转换为结构化上下文
+fn into_context(vars: HashMap<String, String>) -> Map<String, Value> {
+    vars.into_iter()
+        .map(|(key, value)| (key, Value::String(value)))
+        .collect()
+}
+
+/// 文件系统变量：路径、大小与时间
+fn file_vars(path: &Path) -> Value {
     let mut vars: HashMap<String, String> = HashMap::new();
 
     // 程序路径
@@ -77,76 +207,6 @@ fn render_var(path: &Path) -> HashMap<String, String> {
             .to_string(),
     );
 
-    // 程序描述，清理控制字符，合并空白，若为网址或明显无意义则变空
-    let desc_raw = match get_exe_description(path) {
-        Ok(Some(v)) => v,
-        _ => String::new(),
-    };
-    vars.insert(
-        "desc".into(),
-        sanitize_description(&desc_raw).unwrap_or_default(),
-    );
-    vars.insert("desc_raw".into(), desc_raw);
-
-    // 产品名称
-    let product_raw = match get_exe_product_name(path) {
-        Ok(Some(v)) => v,
-        _ => String::new(),
-    };
-    vars.insert(
-        "product".into(),
-        sanitize_description(&product_raw).unwrap_or_default(),
-    );
-    vars.insert("product_raw".into(), product_raw.clone());
-
-    // 公司名称
-    vars.insert(
-        "company".into(),
-        match get_exe_company_name(path) {
-            Ok(Some(v)) => v,
-            _ => String::new(),
-        },
-    );
-
-    // 原始文件名
-    let orig_filename_raw = match get_exe_original_filename(path) {
-        Ok(Some(v)) => v,
-        _ => String::new(),
-    };
-    vars.insert(
-        "orig_filename".into(),
-        sanitize_orig_filename(&orig_filename_raw).unwrap_or_default(),
-    );
-    vars.insert("orig_filename_raw".into(), orig_filename_raw.clone());
-
-    // 版权信息
-    vars.insert(
-        "copyright".into(),
-        match get_exe_copyright(path) {
-            Ok(Some(v)) => v,
-            _ => String::new(),
-        },
-    );
-
-    // 程序版本
-    vars.insert(
-        "version".into(),
-        match get_exe_file_version(path) {
-            Ok(Some(v)) => v,
-            _ => String::new(),
-        },
-    );
-
-    // 程序架构
-    let (arch_label, arch_num) = match get_program_arch(path) {
-        Ok(0x014c) => (Some("x32".to_string()), Some("32".to_string())),
-        Ok(0x8664) => (Some("x64".to_string()), Some("64".to_string())),
-        Ok(0xAA64) => (Some("arm64".to_string()), Some("arm64".to_string())),
-        _ => (None, None),
-    };
-    vars.insert("arch".into(), arch_label.clone().unwrap_or_default());
-    vars.insert("arch_num".into(), arch_num.clone().unwrap_or_default());
-
     // 程序大小
     vars.insert(
         "size".into(),
@@ -187,16 +247,6 @@ fn render_var(path: &Path) -> HashMap<String, String> {
         },
     );
 
-    // 辅助：desc_or_stem
-    vars.insert(
-        "desc_or_stem".into(),
-        if !vars.get("desc").unwrap().is_empty() {
-            vars.get("desc").unwrap().clone()
-        } else {
-            vars.get("stem").unwrap().clone()
-        },
-    );
-
     // 创建时间
     vars.insert(
         "create_time".into(),
@@ -245,676 +295,681 @@ fn render_var(path: &Path) -> HashMap<String, String> {
         },
     );
 
-    vars
-}
+    let mut context = into_context(vars);
 
-/// 模板引擎结构体
-pub struct TemplateEngine {
-    // 缓存已解析的模板以提高性能
-    cache: HashMap<String, String>,
-}
+    // 程序文件
+    context.insert("file".into(), file_context(path));
 
-/// 模板引擎实现
-impl TemplateEngine {
-    /// 创建模板引擎实例
-    pub fn new() -> Self {
-        Self {
-            cache: HashMap::new(),
-        }
+    // 上级目录链，如 `dir.name`、`dir.parent.name`
+    if let Some(parent) = path.parent() {
+        context.insert("dir".into(), dir_context(parent));
     }
 
-    /// 渲染模板 - 支持任何实现了Serialize trait的类型
-    pub fn render<C>(&mut self, template: &str, context: &C) -> Result<String, String>
-    where
-        C: Serialize,
-    {
-        // 如果缓存中存在已解析的模板，则直接使用
-        if let Some(result) = self.cache.get(template) {
-            return Ok(result.clone());
-        }
+    Value::Object(context)
+}
 
-        // 将传入的context转换为serde_json::Value
-        let value_context = match serde_json::to_value(context) {
-            Ok(value) => value,
-            Err(_) => return Err("Unable to convert context to JSON value".to_string()),
-        };
+/// 版本信息变量
+fn version_vars(path: &Path, langs: &[u16]) -> Value {
+    let mut vars: HashMap<String, String> = HashMap::new();
 
-        // 手动扫描模板字符串，识别表达式
-        let mut result = String::new();
-        let mut i = 0;
-        let chars: Vec<char> = template.chars().collect();
-
-        while i < chars.len() {
-            if chars[i] == '{' {
-                // 查找表达式的结束位置
-                let start = i + 1;
-                let mut depth = 1;
-                let mut end = start;
-
-                while end < chars.len() && depth > 0 {
-                    if chars[end] == '{' {
-                        depth += 1;
-                    } else if chars[end] == '}' {
-                        depth -= 1;
-                    }
-                    end += 1;
-                }
+    // 版本信息，按语言优先级选择字符串表
+    let version_info = VersionInfo::read(path).ok().flatten().unwrap_or_default();
+    let query = |field: &str| {
+        version_info
+            .query_with(field, langs)
+            .unwrap_or_default()
+            .to_string()
+    };
 
-                if depth == 0 {
-                    // 提取表达式内容并计算值 - 使用字符索引而不是字节索引
-                    let expr_content: String = chars[start..end - 1].iter().collect();
-                    let value = self.evaluate_expression(&expr_content, &value_context)?;
-                    result.push_str(&value);
-                    i = end;
-                    continue;
-                }
-            }
+    // 程序描述，清理控制字符，合并空白，若为网址或明显无意义则变空
+    let desc_raw = query("FileDescription");
+    vars.insert(
+        "desc".into(),
+        sanitize_description(&desc_raw).unwrap_or_default(),
+    );
+    vars.insert("desc_raw".into(), desc_raw);
 
-            // 添加非表达式字符
-            result.push(chars[i]);
-            i += 1;
-        }
+    // 产品名称
+    let product_raw = query("ProductName");
+    vars.insert(
+        "product".into(),
+        sanitize_description(&product_raw).unwrap_or_default(),
+    );
+    vars.insert("product_raw".into(), product_raw.clone());
 
-        // 缓存结果
-        self.cache.insert(template.to_string(), result.clone());
+    // 公司名称
+    vars.insert("company".into(), query("CompanyName"));
 
-        Ok(result)
-    }
+    // 原始文件名
+    let orig_filename_raw = query("OriginalFilename");
+    vars.insert(
+        "orig_filename".into(),
+        sanitize_orig_filename(&orig_filename_raw).unwrap_or_default(),
+    );
+    vars.insert("orig_filename_raw".into(), orig_filename_raw.clone());
 
-    /// 计算表达式
-    fn evaluate_expression(&self, expr: &str, context: &Value) -> Result<String, String> {
-        let expr_trimmed = expr.trim();
+    // 版权信息
+    vars.insert("copyright".into(), query("LegalCopyright"));
 
-        // 检查是否是默认值表达式
-        if expr_trimmed.contains("??") {
-            let parts: Vec<&str> = expr_trimmed.split("??").map(|s| s.trim()).collect();
-            let value = self.evaluate_simple_expression(parts[0], context)?;
-            if !value.is_empty() {
-                return Ok(value);
-            }
-            return self.evaluate_simple_expression(parts[1], context);
-        }
+    // 程序版本
+    vars.insert(
+        "version".into(),
+        version_info
+            .fixed
+            .map(|fixed| fixed.file_version_string())
+            .unwrap_or_default(),
+    );
 
-        // 检查是否是三元表达式
-        if expr_trimmed.contains('?') {
-            return self.evaluate_ternary_expression(expr_trimmed, context);
-        }
+    // 产品版本，没有字符串时使用固定文件信息中的产品版本
+    vars.insert(
+        "product_version".into(),
+        version_info
+            .query_with("ProductVersion", langs)
+            .map(str::to_string)
+            .or_else(|| {
+                version_info
+                    .fixed
+                    .map(|fixed| fixed.product_version_string())
+            })
+            .unwrap_or_default(),
+    );
+
+    // 内部名称
+    vars.insert("internal_name".into(), query("InternalName"));
 
-        // 检查是否包含过滤器
-        if expr_trimmed.contains('|') {
-            let parts: Vec<&str> = expr_trimmed.split('|').map(|s| s.trim()).collect();
-            let value = self.evaluate_simple_expression(parts[0], context)?;
+    // 所选字符串表的语言，如 `zh-CN`
+    vars.insert(
+        "language".into(),
+        version_info
+            .ordered_tables(langs)
+            .first()
+            .map(|table| lang_tag(table.lang))
+            .unwrap_or_default(),
+    );
 
-            // 应用过滤器
-            return self.apply_filters(value, &parts[1..]);
+    // 各语言的版本信息，如 `product@en`、`desc@zh-CN`、`company@0804`
+    for (var, field, sanitize) in [
+        ("product", "ProductName", true),
+        ("desc", "FileDescription", true),
+        ("company", "CompanyName", false),
+    ] {
+        let value = |raw: &str| {
+            if sanitize {
+                sanitize_description(raw).unwrap_or_default()
+            } else {
+                raw.to_string()
+            }
+        };
+        for (tag, lang) in lang_tags() {
+            if let Some(raw) = version_info.query_lang(field, lang) {
+                vars.insert(format!("{}@{}", var, tag), value(raw));
+            }
         }
+        for table in &version_info.tables {
+            if let Some(raw) = table.strings.get(field) {
+                vars.insert(format!("{}@{:04x}", var, table.lang), value(raw));
+            }
+        }
+    }
 
-        // 计算简单表达式
-        self.evaluate_simple_expression(expr_trimmed, context)
+    let mut context = into_context(vars);
+
+    // 版本号各部分，`{version}` 仍为完整版本号
+    if let Some(fixed) = version_info.fixed {
+        let [major, minor, patch, build] = fixed.file_version;
+        context.insert(
+            "version".into(),
+            json!({
+                "value": fixed.file_version_string(),
+                "major": major,
+                "minor": minor,
+                "patch": patch,
+                "build": build,
+            }),
+        );
     }
 
-    /// 计算三元表达式
-    fn evaluate_ternary_expression(&self, expr: &str, context: &Value) -> Result<String, String> {
-        // 跟踪括号深度以正确识别三元表达式的各部分
-        let mut bracket_depth = 0;
-        let mut question_mark_pos = None;
-        let mut colon_pos = None;
-
-        // 第一遍扫描：找到第一个问号
-        for (i, ch) in expr.char_indices() {
-            match ch {
-                '{' => bracket_depth += 1,
-                '}' => {
-                    if bracket_depth > 0 {
-                        bracket_depth -= 1;
-                    }
-                }
-                '?' => {
-                    if bracket_depth == 0 && question_mark_pos.is_none() {
-                        question_mark_pos = Some(i);
-                        break;
-                    }
-                }
-                _ => {}
-            }
-        }
+    Value::Object(context)
+}
 
-        if let Some(q_pos) = question_mark_pos {
-            bracket_depth = 0;
-            // 第二遍扫描：找到匹配的冒号
-            for (i, ch) in expr.char_indices().skip(q_pos + 1) {
-                match ch {
-                    '{' => bracket_depth += 1,
-                    '}' => {
-                        if bracket_depth > 0 {
-                            bracket_depth -= 1;
-                        }
-                    }
-                    ':' => {
-                        if bracket_depth == 0 {
-                            colon_pos = Some(i);
-                            break;
-                        }
-                    }
-                    _ => {}
-                }
-            }
+/// PE 文件头变量
+fn pe_vars(path: &Path) -> Value {
+    let mut vars: HashMap<String, String> = HashMap::new();
 
-            if let Some(c_pos) = colon_pos {
-                let condition = &expr[..q_pos].trim();
-                let true_expr = &expr[q_pos + 1..c_pos].trim();
-                let false_expr = &expr[c_pos + 1..].trim();
+    // 程序架构
+    let (arch_label, arch_num) = match get_program_arch(path) {
+        Ok(0x014c) => (Some("x32".to_string()), Some("32".to_string())),
+        Ok(0x8664) => (Some("x64".to_string()), Some("64".to_string())),
+        Ok(0xAA64) => (Some("arm64".to_string()), Some("arm64".to_string())),
+        _ => (None, None),
+    };
+    vars.insert("arch".into(), arch_label.clone().unwrap_or_default());
+    vars.insert("arch_num".into(), arch_num.clone().unwrap_or_default());
 
-                let condition_result = self.evaluate_condition(condition, context)?;
+    let mut context = into_context(vars);
 
-                return if condition_result {
-                    self.evaluate_expression(true_expr, context)
-                } else {
-                    // 处理假值表达式可能包含的大括号
-                    let false_expr_trimmed = false_expr.trim();
-                    let false_expr_to_eval = if false_expr_trimmed.starts_with('{')
-                        && false_expr_trimmed.ends_with('}')
-                    {
-                        &false_expr_trimmed[1..false_expr_trimmed.len() - 1]
-                    } else {
-                        false_expr_trimmed
-                    };
-                    self.evaluate_expression(false_expr_to_eval, context)
-                };
-            }
-        }
+    // PE 文件头
+    context.insert("pe".into(), pe_context(path));
 
-        // 如果不是有效的三元表达式，返回空字符串
-        Ok("".to_string())
-    }
+    Value::Object(context)
+}
 
-    /// 计算简单表达式（变量、字面量等）
-    fn evaluate_simple_expression(&self, expr: &str, context: &Value) -> Result<String, String> {
-        let expr = expr.trim();
+/// 数字签名变量
+fn signature_vars(path: &Path) -> Value {
+    let mut vars: HashMap<String, String> = HashMap::new();
 
-        // 检查是否是字符串字面量
-        if (expr.starts_with('"') && expr.ends_with('"'))
-            || (expr.starts_with('\'') && expr.ends_with('\''))
-        {
-            // 去除引号
-            return Ok(expr[1..expr.len() - 1].to_string());
-        }
+    // 数字签名
+    let signature = Signature::read(path).ok().flatten();
+    vars.insert(
+        "signer".into(),
+        signature
+            .as_ref()
+            .and_then(|signature| signature.signer.clone())
+            .unwrap_or_default(),
+    );
+    vars.insert(
+        "signed_valid".into(),
+        signature
+            .as_ref()
+            .is_some_and(|signature| signature.verified)
+            .to_string(),
+    );
 
-        // 检查是否是数字字面量
-        if expr.parse::<f64>().is_ok() {
-            return Ok(expr.to_string());
-        }
+    let mut context = into_context(vars);
+
+    // 数字签名
+    context.insert(
+        "signature".into(),
+        match &signature {
+            Some(signature) => json!({
+                "subject": signature.signer.clone().unwrap_or_default(),
+                "issuer": signature.issuer.clone().unwrap_or_default(),
+                "timestamp": signature
+                    .timestamp
+                    .map(|time| {
+                        DateTime::<Local>::from(time)
+                            .format("%Y-%m-%d %H:%M:%S")
+                            .to_string()
+                    })
+                    .unwrap_or_default(),
+                "valid": signature.verified,
+            }),
+            None => json!({ "valid": false }),
+        },
+    );
 
-        // 检查是否包含字符串拼接
-        if expr.contains('+') {
-            let parts: Vec<&str> = expr.split('+').map(|s| s.trim()).collect();
-            let mut result = String::new();
+    Value::Object(context)
+}
 
-            for part in parts {
-                let value = self.evaluate_simple_expression(part, context)?;
-                result.push_str(&value);
-            }
+/// 程序清单变量
+fn manifest_vars(path: &Path) -> Value {
+    let mut vars: HashMap<String, String> = HashMap::new();
 
-            return Ok(result);
-        }
+    // 程序清单
+    let manifest = Manifest::read(path).ok().flatten().unwrap_or_default();
+    vars.insert(
+        "manifest_name".into(),
+        manifest.display_name().unwrap_or_default().to_string(),
+    );
+    vars.insert(
+        "manifest_name_raw".into(),
+        manifest.name.clone().unwrap_or_default(),
+    );
+    vars.insert(
+        "manifest_version".into(),
+        manifest.version.clone().unwrap_or_default(),
+    );
+    vars.insert(
+        "manifest_arch".into(),
+        manifest.processor_architecture.clone().unwrap_or_default(),
+    );
+    vars.insert(
+        "execution_level".into(),
+        manifest.execution_level.clone().unwrap_or_default(),
+    );
+    vars.insert("dpi_aware".into(), manifest.is_dpi_aware().to_string());
+
+    let mut context = into_context(vars);
+
+    // 程序清单
+    context.insert(
+        "manifest".into(),
+        json!({
+            "name": manifest.display_name().unwrap_or_default(),
+            "name_raw": manifest.name.clone().unwrap_or_default(),
+            "version": manifest.version.clone().unwrap_or_default(),
+            "arch": manifest.processor_architecture.clone().unwrap_or_default(),
+            "execution_level": manifest.execution_level.clone().unwrap_or_default(),
+            "dpi_aware": manifest.is_dpi_aware(),
+        }),
+    );
 
-        // 获取变量值
-        self.get_variable_value(expr, context)
-    }
+    Value::Object(context)
+}
 
-    /// 应用过滤器
-    fn apply_filters(&self, mut value: String, filters: &[&str]) -> Result<String, String> {
-        for filter_part in filters {
-            // 解析过滤器名称和参数
-            let mut filter_name = filter_part.trim();
-            let mut filter_arg: Option<String> = None;
+/// 运行时、PortableApps.com 与内置的常见程序信息变量
+fn app_vars(path: &Path) -> Value {
+    let mut vars: HashMap<String, String> = HashMap::new();
 
-            // 使用冒号:作为参数分隔符
-            if let Some(colon_pos) = self.find_top_level_char(filter_part, ':') {
-                filter_name = filter_part[..colon_pos].trim();
-                let arg = &filter_part[colon_pos + 1..].trim();
+    // 程序运行时
+    vars.insert("runtime".into(), detect_runtime(path).label().to_string());
 
-                // 直接使用参数，让各个过滤器自行处理引号（尤其是 replace 需要正确处理引号内的逗号）
-                filter_arg = Some(arg.to_string());
-            }
+    // PortableApps.com 程序信息
+    let portable_app = PortableAppInfo::for_program(path).unwrap_or_default();
+    vars.insert("pa_name".into(), portable_app.name.unwrap_or_default());
+    vars.insert(
+        "pa_category".into(),
+        portable_app.category.unwrap_or_default(),
+    );
+    vars.insert(
+        "pa_publisher".into(),
+        portable_app.publisher.unwrap_or_default(),
+    );
 
-            // 应用过滤器
-            value = match filter_name.to_ascii_lowercase().as_str() {
-                // 转大写
-                "upper" => value.to_uppercase(),
-                // 转小写
-                "lower" => value.to_lowercase(),
-                // 去除空格
-                "trim" => value.trim().to_string(),
-                // 首字母大写
-                "capitalize" => {
-                    let mut chars = value.chars();
-                    match chars.next() {
-                        None => value,
-                        Some(first) => {
-                            let mut result = String::with_capacity(value.len());
-                            result.extend(first.to_uppercase());
-                            result.extend(chars);
-                            result
-                        }
-                    }
-                }
-                // 切片操作
-                "slice" => {
-                    if let Some(arg) = &filter_arg {
-                        // 支持 "start:end" 或 'start:end' 或 start:end（引号可选）
-                        let mut s = arg.trim();
-                        if s.len() >= 2
-                            && ((s.starts_with('"') && s.ends_with('"'))
-                                || (s.starts_with('\'') && s.ends_with('\'')))
-                        {
-                            s = &s[1..s.len() - 1];
-                        }
-
-                        // 使用逗号作为分隔符
-                        let delimiter = if s.contains(',') { ',' } else { ' ' };
-
-                        // 按分隔符分割成 start 与 end（都可为空）
-                        let mut parts = s.splitn(2, delimiter);
-                        let start_str = parts.next().unwrap_or("").trim();
-                        let end_str = parts.next().unwrap_or("").trim();
-
-                        // 当前字符串按 Unicode 字符收集
-                        let chars: Vec<char> = value.chars().collect();
-                        let len = chars.len() as isize;
-
-                        // 解析 start（可选，支持负数）
-                        let start_res: Result<isize, _> = if start_str.is_empty() {
-                            Ok(0)
-                        } else {
-                            start_str.parse::<isize>()
-                        };
-                        let mut start_idx = match start_res {
-                            Ok(v) => {
-                                if v < 0 {
-                                    len + v
-                                } else {
-                                    v
-                                }
-                            }
-                            Err(_) => {
-                                // 参数无法解析 -> 跳过该 filter（保持 value 不变）
-                                continue;
-                            }
-                        };
-
-                        // 解析 end（可选，支持负数）
-                        let end_res: Result<isize, _> = if end_str.is_empty() {
-                            Ok(len)
-                        } else {
-                            end_str.parse::<isize>()
-                        };
-                        let mut end_idx = match end_res {
-                            Ok(v) => {
-                                if v < 0 {
-                                    len + v
-                                } else {
-                                    v
-                                }
-                            }
-                            Err(_) => {
-                                continue;
-                            }
-                        };
-
-                        // Clamp 到 [0, len]
-                        if start_idx < 0 {
-                            start_idx = 0;
-                        }
-                        if end_idx < 0 {
-                            end_idx = 0;
-                        }
-                        if start_idx > len {
-                            start_idx = len;
-                        }
-                        if end_idx > len {
-                            end_idx = len;
-                        }
-
-                        // 如果 start >= end -> 空
-                        if start_idx >= end_idx {
-                            value.clear();
-                        } else {
-                            let s_us = start_idx as usize;
-                            let e_us = end_idx as usize;
-                            value = chars[s_us..e_us].iter().collect();
-                        }
-                    }
-                    value
-                }
-                // 默认值
-                "default" => {
-                    if let Some(arg) = &filter_arg {
-                        if value.trim().is_empty() {
-                            arg.to_string()
-                        } else {
-                            value
-                        }
-                    } else {
-                        value
-                    }
-                }
-                // 标题大小写
-                "title" => {
-                    let mut out = String::with_capacity(value.len());
-                    let mut last_was_ws = true;
-                    for ch in value.chars() {
-                        if ch.is_whitespace() {
-                            out.push(ch);
-                            last_was_ws = true;
-                        } else {
-                            if last_was_ws {
-                                // 首字母：大写
-                                out.extend(ch.to_uppercase());
-                            } else {
-                                // 非首字母：转小写（以获得一致的 Title Case）
-                                for low in ch.to_lowercase() {
-                                    out.push(low);
-                                }
-                            }
-                            last_was_ws = false;
-                        }
-                    }
-                    out
-                }
-                // 字符串长度
-                "length" => value.chars().count().to_string(),
-                // 从字符串中删除指定内容
-                "cut" => {
-                    if let Some(arg) = &filter_arg {
-                        value.replace(arg, "")
-                    } else {
-                        value
-                    }
-                }
-                // 截断字符串
-                "truncate" => {
-                    if let Some(arg) = &filter_arg {
-                        if let Ok(n) = arg.parse::<usize>() {
-                            // 按 Unicode 字符截断
-                            value.chars().take(n).collect()
-                        } else {
-                            value
-                        }
-                    } else {
-                        value
-                    }
-                }
-                // 字符串替换
-                "replace" => {
-                    if let Some(arg) = &filter_arg {
-                        // 手动解析参数，正确处理引号内的逗号
-                        // 支持: old,new, 'old','new', "old","new" 等
-                        let parts = self.parse_quoted_args(arg, ',');
-
-                        if parts.len() >= 2 {
-                            value.replace(&parts[0], &parts[1])
-                        } else if parts.len() == 1 && !parts[0].is_empty() {
-                            // 只有一个参数时，删除匹配的内容
-                            value.replace(&parts[0], "")
-                        } else {
-                            value
-                        }
-                    } else {
-                        value
-                    }
-                }
-                // 日期格式化
-                "date" => {
-                    if let Some(arg) = &filter_arg {
-                        match NaiveDateTime::parse_from_str(&value, "%Y-%m-%d %H:%M:%S") {
-                            Ok(naive_date) => {
-                                // 获取本地时区偏移
-                                let local = Local::now();
-                                // 创建带有时区信息的DateTime
-                                let date_with_timezone =
-                                    DateTime::<Local>::from_naive_utc_and_offset(
-                                        naive_date,
-                                        *local.offset(),
-                                    );
-                                // 格式化输出
-                                date_with_timezone.format(arg).to_string()
-                            }
-                            Err(_) => value, // 解析失败则返回原始值
-                        }
-                    } else {
-                        value // 无参数则返回原始值
-                    }
-                }
-                _ => value,
-            };
-        }
+    // 内置的常见程序信息
+    let known_app = known_apps::find(path);
+    vars.insert(
+        "known_name".into(),
+        known_app
+            .and_then(|app| app.localized_name())
+            .unwrap_or_default(),
+    );
+    vars.insert(
+        "known_category".into(),
+        known_app
+            .and_then(|app| app.category.clone())
+            .unwrap_or_default(),
+    );
 
-        Ok(value)
-    }
+    Value::Object(into_context(vars))
+}
 
-    /// 解析带引号的参数列表（忽略引号内的分隔符）
-    fn parse_quoted_args(&self, s: &str, delimiter: char) -> Vec<String> {
-        let mut parts = Vec::new();
-        let mut in_single_quote = false;
-        let mut in_double_quote = false;
-        let mut escaped = false;
-        let mut current = String::new();
-        let mut i = 0usize;
-
-        while i < s.len() {
-            let ch = s[i..].chars().next().unwrap();
-            let ch_len = ch.len_utf8();
-
-            if !escaped {
-                match ch {
-                    '\\' => {
-                        escaped = true;
-                        current.push(ch);
-                        i += ch_len;
-                        continue;
-                    }
-                    '\'' if !in_double_quote => {
-                        in_single_quote = !in_single_quote;
-                        i += ch_len;
-                        continue;
-                    }
-                    '"' if !in_single_quote => {
-                        in_double_quote = !in_double_quote;
-                        i += ch_len;
-                        continue;
-                    }
-                    c if c == delimiter && !in_single_quote && !in_double_quote => {
-                        let part = current.trim().to_string();
-                        // 去除外层引号
-                        parts.push(self.strip_outer_quotes(&part));
-                        current.clear();
-                        i += ch_len;
-                        continue;
-                    }
-                    _ => {
-                        current.push(ch);
-                    }
-                }
-            } else {
-                escaped = false;
-                current.push(ch);
+/// PE 文件头中的链接时间、子系统与架构
+fn pe_context(path: &Path) -> Value {
+    let mut options = ParseOptions::default();
+    options.parse_attribute_certificates = false;
+    options.parse_tls_data = false;
+    let header = File::open(path)
+        .ok()
+        .and_then(|file| unsafe { Mmap::map(&file) }.ok())
+        .and_then(|mmap| {
+            let pe = PE::parse_with_opts(&mmap, &options).ok()?;
+            let subsystem = pe
+                .header
+                .optional_header
+                .map(|header| header.windows_fields.subsystem);
+            Some((
+                pe.header.coff_header.time_date_stamp,
+                subsystem,
+                pe.header.coff_header.machine,
+            ))
+        });
+    let Some((timestamp, subsystem, machine)) = header else {
+        return json!({});
+    };
+
+    json!({
+        // 可复现构建的链接时间为 0 或哈希值，仅 0 视为没有链接时间
+        "timestamp": DateTime::from_timestamp(timestamp as i64, 0)
+            .filter(|_| timestamp != 0)
+            .map(|time| {
+                DateTime::<Local>::from(time)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_default(),
+        "subsystem": match subsystem {
+            Some(1) => "native".to_string(),
+            Some(2) => "gui".to_string(),
+            Some(3) => "console".to_string(),
+            Some(10) => "efi".to_string(),
+            Some(other) => other.to_string(),
+            None => String::new(),
+        },
+        "machine": match machine {
+            0x014c => "x86".to_string(),
+            0x8664 => "x64".to_string(),
+            0xAA64 => "arm64".to_string(),
+            0x01c4 => "arm".to_string(),
+            other => format!("0x{:04x}", other),
+        },
+    })
+}
+
+/// 程序文件的名称、路径与大小
+fn file_context(path: &Path) -> Value {
+    let name = |value: Option<&std::ffi::OsStr>| {
+        value
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let size = path.metadata().map(|metadata| metadata.len()).ok();
+    let scaled = |unit: f64, precision: usize| {
+        size.map(|size| format!("{:.*}", precision, size as f64 / unit))
+            .unwrap_or_default()
+    };
+
+    json!({
+        "name": name(path.file_name()),
+        "stem": name(path.file_stem()),
+        "ext": name(path.extension()),
+        "path": path.display().to_string(),
+        "size": {
+            "value": size,
+            "kb": scaled(1024.0, 1),
+            "mb": scaled(1024.0 * 1024.0, 2),
+            "gb": scaled(1024.0 * 1024.0 * 1024.0, 3),
+            "human": size.map(human_size).unwrap_or_default(),
+        },
+    })
+}
+
+/// 目录的名称、路径与上级目录
+fn dir_context(dir: &Path) -> Value {
+    // 从根目录开始逐级嵌套
+    dir.ancestors()
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .fold(Value::Null, |parent, dir| {
+            let mut context = Map::new();
+            context.insert(
+                "name".into(),
+                Value::String(
+                    dir.file_name()
+                        .and_then(|s| s.to_str())
+                        .unwrap_or_default()
+                        .to_string(),
+                ),
+            );
+            context.insert("path".into(), Value::String(dir.display().to_string()));
+            if !parent.is_null() {
+                context.insert("parent".into(), parent);
             }
-            i += ch_len;
-        }
+            Value::Object(context)
+        })
+}
 
-        // 添加最后一个部分
-        if !current.is_empty() || !parts.is_empty() {
-            let part = current.trim().to_string();
-            parts.push(self.strip_outer_quotes(&part));
-        }
+/// 可读的文件大小，如 `512 B`、`1.5 MB`
+pub fn human_size(size: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", size, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// 模板字符串到编译结果的映射
+type CompiledCache = HashMap<String, Result<Arc<Template>, String>>;
+
+/// 已编译模板的缓存，同一模板只解析一次，解析失败的结果也会缓存
+static COMPILED: OnceLock<Mutex<CompiledCache>> = OnceLock::new();
+
+/// 编译模板，结果按模板字符串缓存
+///
+/// # 参数
+/// - `template`: 模板字符串
+///
+/// # 返回值
+/// - `Ok(Arc<Template>)`: 已编译的模板
+/// - `Err(...)`: 语法错误的说明（首次编译失败时输出警告）
+pub fn compile(template: &str) -> Result<Arc<Template>, String> {
+    let cache = COMPILED.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut cache = cache.lock().unwrap();
+    if let Some(compiled) = cache.get(template) {
+        return compiled.clone();
+    }
 
-        parts
+    let compiled = Template::parse(template).map(Arc::new);
+    if let Err(e) = &compiled {
+        write_console_key(
+            ConsoleType::Warning,
+            "config.invalid_template",
+            &t!("config.invalid_template", template = template, error = e),
+        );
     }
+    cache.insert(template.to_string(), compiled.clone());
+    compiled
+}
 
-    /// 去除字符串外层的引号（如果存在）
-    fn strip_outer_quotes(&self, s: &str) -> String {
-        let trimmed = s.trim();
-        if (trimmed.starts_with('"') && trimmed.ends_with('"'))
-            || (trimmed.starts_with('\'') && trimmed.ends_with('\''))
-        {
-            trimmed[1..trimmed.len() - 1].to_string()
-        } else {
-            trimmed.to_string()
+/// 使用上下文渲染已编译的模板，变量在用到时才从上下文中读取
+pub fn render_compiled(template: &Template, context: &dyn TemplateContext) -> String {
+    let mut result = String::new();
+    for segment in &template.segments {
+        match segment {
+            Segment::Text(text) => result.push_str(text),
+            Segment::Expr(expr) => result.push_str(&evaluate(expr, context)),
         }
     }
+    result
+}
 
-    /// 查找顶层字符（忽略引号、转义字符和嵌套括号内的字符）
-    fn find_top_level_char(&self, s: &str, target: char) -> Option<usize> {
-        let mut in_single = false;
-        let mut in_double = false;
-        let mut depth = 0i32;
-        let mut escaped = false;
-        let mut i = 0usize;
-
-        while i < s.len() {
-            let ch = s[i..].chars().next().unwrap();
-            let ch_len = ch.len_utf8();
-
-            if !escaped {
-                match ch {
-                    '\\' => {
-                        escaped = true;
-                        i += ch_len;
-                        continue;
-                    }
-                    '\'' if !in_double => {
-                        in_single = !in_single;
-                        i += ch_len;
-                        continue;
-                    }
-                    '"' if !in_single => {
-                        in_double = !in_double;
-                        i += ch_len;
-                        continue;
-                    }
-                    '{' if !in_single && !in_double => {
-                        depth += 1;
-                        i += ch_len;
-                        continue;
-                    }
-                    '}' if !in_single && !in_double => {
-                        if depth > 0 {
-                            depth -= 1;
-                        }
-                        i += ch_len;
-                        continue;
-                    }
-                    c if c == target && !in_single && !in_double && depth == 0 => {
-                        return Some(i);
-                    }
-                    _ => {}
-                }
+/// 计算表达式
+fn evaluate(expr: &Expr, context: &dyn TemplateContext) -> String {
+    match expr {
+        Expr::Literal(value) => value.clone(),
+        Expr::Var(name) => get_variable_value(name, context),
+        Expr::Not(inner) => (!is_truthy(&evaluate(inner, context))).to_string(),
+        Expr::Concat(left, right) => evaluate(left, context) + &evaluate(right, context),
+        Expr::Compare(op, left, right) => {
+            compare(*op, &evaluate(left, context), &evaluate(right, context)).to_string()
+        }
+        // 逻辑运算短路求值，未用到的变量不会读取
+        Expr::And(left, right) => (is_truthy(&evaluate(left, context))
+            && is_truthy(&evaluate(right, context)))
+        .to_string(),
+        Expr::Or(left, right) => (is_truthy(&evaluate(left, context))
+            || is_truthy(&evaluate(right, context)))
+        .to_string(),
+        Expr::Default(value, fallback) => {
+            let value = evaluate(value, context);
+            if value.is_empty() {
+                evaluate(fallback, context)
+            } else {
+                value
+            }
+        }
+        Expr::Conditional {
+            condition,
+            then,
+            otherwise,
+        } => {
+            if is_truthy(&evaluate(condition, context)) {
+                evaluate(then, context)
             } else {
-                escaped = false;
+                otherwise
+                    .as_ref()
+                    .map(|otherwise| evaluate(otherwise, context))
+                    .unwrap_or_default()
             }
-            i += ch_len;
         }
+        Expr::Filter(value, filter) => apply_filter(evaluate(value, context), filter),
+    }
+}
+
+/// 值是否为真：非空且不为 `false` 或 `0`
+fn is_truthy(value: &str) -> bool {
+    !value.is_empty() && value != "false" && value != "0"
+}
 
-        None
+/// 比较两个值，两边都是数字时按数值比较，否则按字符串比较
+fn compare(op: CompareOp, left: &str, right: &str) -> bool {
+    let ordering = match (left.parse::<f64>(), right.parse::<f64>()) {
+        (Ok(left), Ok(right)) => left.partial_cmp(&right),
+        _ => Some(left.cmp(right)),
+    };
+    let Some(ordering) = ordering else {
+        return op == CompareOp::Ne;
+    };
+    match op {
+        CompareOp::Eq => ordering.is_eq(),
+        CompareOp::Ne => ordering.is_ne(),
+        CompareOp::Gt => ordering.is_gt(),
+        CompareOp::Ge => ordering.is_ge(),
+        CompareOp::Lt => ordering.is_lt(),
+        CompareOp::Le => ordering.is_le(),
     }
+}
 
-    /// 计算条件表达式
-    fn evaluate_condition(&self, condition: &str, context: &Value) -> Result<bool, String> {
-        // 检查是否包含逻辑运算符
-        if condition.contains("&&") {
-            let parts: Vec<&str> = condition.split("&&").map(|s| s.trim()).collect();
-            for part in parts {
-                if !self.evaluate_condition(part, context)? {
-                    return Ok(false);
+/// 应用过滤器
+fn apply_filter(value: String, filter: &Filter) -> String {
+    let arg = filter.args.first();
+    match filter.kind {
+        // 转大写
+        FilterKind::Upper => value.to_uppercase(),
+        // 转小写
+        FilterKind::Lower => value.to_lowercase(),
+        // 去除空格
+        FilterKind::Trim => value.trim().to_string(),
+        // 首字母大写
+        FilterKind::Capitalize => {
+            let mut chars = value.chars();
+            match chars.next() {
+                None => value,
+                Some(first) => {
+                    let mut result = String::with_capacity(value.len());
+                    result.extend(first.to_uppercase());
+                    result.extend(chars);
+                    result
                 }
             }
-            return Ok(true);
         }
-
-        if condition.contains("||") {
-            let parts: Vec<&str> = condition.split("||").map(|s| s.trim()).collect();
-            for part in parts {
-                if self.evaluate_condition(part, context)? {
-                    return Ok(true);
+        // 切片操作
+        FilterKind::Slice => slice(value, &filter.args),
+        // 默认值
+        FilterKind::Default => match arg {
+            Some(arg) if value.trim().is_empty() => arg.to_string(),
+            _ => value,
+        },
+        // 标题大小写
+        FilterKind::Title => {
+            let mut out = String::with_capacity(value.len());
+            let mut last_was_ws = true;
+            for ch in value.chars() {
+                if ch.is_whitespace() {
+                    out.push(ch);
+                    last_was_ws = true;
+                } else {
+                    if last_was_ws {
+                        // 首字母：大写
+                        out.extend(ch.to_uppercase());
+                    } else {
+                        // 非首字母：转小写（以获得一致的 Title Case）
+                        out.extend(ch.to_lowercase());
+                    }
+                    last_was_ws = false;
                 }
             }
-            return Ok(false);
+            out
         }
-
-        // 检查是否包含比较运算符
-        // 注意：必须先检查较长的操作符，避免部分匹配
-        let operators = ["==", "!=", ">=", "<=", ">", "<"];
-        for op in operators.iter() {
-            if condition.contains(op) {
-                let parts: Vec<&str> = condition.split(op).map(|s| s.trim()).collect();
-                if parts.len() != 2 {
-                    return Err("比较表达式格式错误".to_string());
-                }
-
-                let left = self.evaluate_simple_expression(parts[0], context)?;
-                let right = self.evaluate_simple_expression(parts[1], context)?;
-
-                // 尝试将两边转换为数字进行比较
-                if let (Ok(left_num), Ok(right_num)) = (left.parse::<f64>(), right.parse::<f64>()) {
-                    return Ok(match *op {
-                        "==" => left_num == right_num,
-                        "!=" => left_num != right_num,
-                        ">" => left_num > right_num,
-                        ">=" => left_num >= right_num,
-                        "<" => left_num < right_num,
-                        "<=" => left_num <= right_num,
-                        _ => false,
-                    });
+        // 字符串长度
+        FilterKind::Length => value.chars().count().to_string(),
+        // 从字符串中删除指定内容
+        FilterKind::Cut => match arg {
+            Some(arg) => value.replace(arg.as_str(), ""),
+            None => value,
+        },
+        // 截断字符串（按 Unicode 字符）
+        FilterKind::Truncate => match arg.and_then(|arg| arg.trim().parse::<usize>().ok()) {
+            Some(n) => value.chars().take(n).collect(),
+            None => value,
+        },
+        // 字符串替换，只有一个参数时删除匹配的内容
+        FilterKind::Replace => match filter.args.as_slice() {
+            [old, new, ..] => value.replace(old.as_str(), new),
+            [old] if !old.is_empty() => value.replace(old.as_str(), ""),
+            _ => value,
+        },
+        // 日期格式化
+        FilterKind::Date => {
+            let Some(format) = arg else {
+                return value;
+            };
+            match NaiveDateTime::parse_from_str(&value, "%Y-%m-%d %H:%M:%S") {
+                Ok(naive_date) => {
+                    // 获取本地时区偏移
+                    let local = Local::now();
+                    // 创建带有时区信息的DateTime
+                    let date_with_timezone =
+                        DateTime::<Local>::from_naive_utc_and_offset(naive_date, *local.offset());
+                    // 格式化输出
+                    date_with_timezone.format(format).to_string()
                 }
-
-                // 作为字符串比较
-                return Ok(match *op {
-                    "==" => left == right,
-                    "!=" => left != right,
-                    ">" => left > right,
-                    ">=" => left >= right,
-                    "<" => left < right,
-                    "<=" => left <= right,
-                    _ => false,
-                });
+                Err(_) => value, // 解析失败则返回原始值
             }
         }
-
-        // 如果只是一个变量名，检查其是否为真值
-        let value = self.evaluate_simple_expression(condition, context)?;
-        Ok(!value.is_empty() && value != "false" && value != "0")
     }
+}
 
-    /// 从上下文中获取变量值
-    fn get_variable_value(&self, var_name: &str, context: &Value) -> Result<String, String> {
-        let var_name = var_name.trim();
+/// 按 Unicode 字符截取 `start,end`（都可省略，支持负数），参数无法解析时保持原值
+fn slice(value: String, args: &[String]) -> String {
+    // 也支持写成一个参数，如 `slice:"0,4"` 或 `slice:"0 4"`
+    let (start, end) = match args {
+        [range] => {
+            let delimiter = if range.contains(',') { ',' } else { ' ' };
+            let mut parts = range.splitn(2, delimiter);
+            (
+                parts.next().unwrap_or_default().to_string(),
+                parts.next().unwrap_or_default().to_string(),
+            )
+        }
+        [start, end, ..] => (start.clone(), end.clone()),
+        [] => return value,
+    };
 
-        // 支持点号访问对象属性，例如 user.name
-        let parts: Vec<&str> = var_name.split('.').collect();
-        let mut current = context;
+    let chars: Vec<char> = value.chars().collect();
+    let len = chars.len() as isize;
+    let index = |s: &str, default: isize| -> Option<isize> {
+        let s = s.trim();
+        let index = if s.is_empty() {
+            default
+        } else {
+            s.parse::<isize>().ok()?
+        };
+        // 负数从末尾计算，并限制在 [0, len]
+        let index = if index < 0 { len + index } else { index };
+        Some(index.clamp(0, len))
+    };
+    let (Some(start), Some(end)) = (index(&start, 0), index(&end, len)) else {
+        return value;
+    };
 
-        for part in parts {
-            match current {
-                Value::Object(map) => {
-                    if let Some(val) = map.get(part) {
-                        current = val;
-                    } else {
-                        return Ok("".to_string());
-                    }
-                }
-                _ => return Ok("".to_string()),
-            }
-        }
+    if start >= end {
+        String::new()
+    } else {
+        chars[start as usize..end as usize].iter().collect()
+    }
+}
 
-        // 将Value转换为字符串
-        Ok(match current {
-            Value::String(s) => s.clone(),
-            Value::Number(n) => n.to_string(),
-            Value::Bool(b) => b.to_string(),
-            Value::Null => "".to_string(),
-            _ => "".to_string(), // 对于数组和对象，返回空字符串
-        })
+/// 从上下文中获取变量值
+fn get_variable_value(var_name: &str, context: &dyn TemplateContext) -> String {
+    // 支持点号访问对象属性，例如 user.name
+    let Some(mut current) = context.lookup(var_name) else {
+        return "".to_string();
+    };
+
+    // 对象以其 value 成员作为值，如 `{version}` 与 `{version.major}`
+    if let Value::Object(map) = &current
+        && let Some(value) = map.get("value")
+    {
+        current = value.clone();
+    }
+
+    // 将Value转换为字符串
+    match current {
+        Value::String(s) => s,
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Null => "".to_string(),
+        _ => "".to_string(), // 对于数组和对象，返回空字符串
     }
 }
 
@@ -923,8 +978,13 @@ pub fn render_template<C>(template: &str, context: &C) -> Result<String, String>
 where
     C: Serialize,
 {
-    let mut engine = TemplateEngine::new();
-    engine.render(template, context)
+    // 将传入的context转换为serde_json::Value
+    let context = match serde_json::to_value(context) {
+        Ok(value) => value,
+        Err(_) => return Err("Unable to convert context to JSON value".to_string()),
+    };
+    let template = Template::parse(template)?;
+    Ok(render_compiled(&template, &context))
 }
 
 #[cfg(test)]
@@ -1060,4 +1120,124 @@ mod tests {
         let result = render_template(template, &context).unwrap();
         assert_eq!(result, "基础内容");
     }
+
+    #[test]
+    fn test_nested_context() {
+        let context = json!({
+            "version": { "value": "2.10.0.7", "major": 2, "minor": 10 },
+            "file": { "size": { "value": 1536, "human": "1.5 KB" } },
+            "dir": { "name": "bin", "parent": { "name": "App" } }
+        });
+
+        // 对象以其 value 成员作为值
+        let result =
+            render_template("{version} / {version.major}.{version.minor}", &context).unwrap();
+        assert_eq!(result, "2.10.0.7 / 2.10");
+        let result = render_template("{file.size} ({file.size.human})", &context).unwrap();
+        assert_eq!(result, "1536 (1.5 KB)");
+
+        // 嵌套成员参与比较与默认值
+        let result = render_template("{version.major >= 2 ? 'v2+' : 'v1'}", &context).unwrap();
+        assert_eq!(result, "v2+");
+        let result = render_template(
+            "{dir.parent.name} {dir.parent.parent.name ?? 'root'}",
+            &context,
+        )
+        .unwrap();
+        assert_eq!(result, "App root");
+    }
+
+    #[test]
+    fn test_operators_in_literals() {
+        let context = json!({ "desc": "What? Now? Yes | No", "name": "" });
+
+        // 字符串中的运算符不影响表达式结构
+        let result = render_template("{desc | replace:\"?\",\"\"}", &context).unwrap();
+        assert_eq!(result, "What Now Yes | No");
+        let result = render_template("{desc | replace:' | ','/'}", &context).unwrap();
+        assert_eq!(result, "What? Now? Yes/No");
+        let result = render_template("{name ?? 'C++'}", &context).unwrap();
+        assert_eq!(result, "C++");
+        let result = render_template("{name ? name : 'a ? b : c'}", &context).unwrap();
+        assert_eq!(result, "a ? b : c");
+    }
+
+    #[test]
+    fn test_literal_braces_and_escapes() {
+        let context = json!({ "id": "6D809377", "stem": "app", "name": "Tom" });
+
+        // `{{`、`}}` 输出大括号，文本中的反斜杠原样保留
+        let result = render_template("shell:AppsFolder\\{{{id}}}", &context).unwrap();
+        assert_eq!(result, "shell:AppsFolder\\{6D809377}");
+        let result = render_template("D:\\Icons\\{stem}.ico", &context).unwrap();
+        assert_eq!(result, "D:\\Icons\\app.ico");
+        let result = render_template("{{stem}} }", &context).unwrap();
+        assert_eq!(result, "{stem} }");
+
+        // 字符串转义
+        let result = render_template("{'It\\'s ' + name}", &context).unwrap();
+        assert_eq!(result, "It's Tom");
+        let result = render_template(r#"{"say \"hi\"" + ' C:\Tools\\'}"#, &context).unwrap();
+        assert_eq!(result, r#"say "hi" C:\Tools\"#);
+    }
+
+    #[test]
+    fn test_operator_precedence() {
+        let context = json!({ "a": "", "b": "x", "ver": "rc1", "one": 1, "two": 2 });
+
+        // `??` 优先级最低
+        let result = render_template("{a ?? b ? 'yes' : 'no'}", &context).unwrap();
+        assert_eq!(result, "yes");
+        // `&&` 先于 `||`，比较先于逻辑运算
+        let result = render_template("{one == 1 || two == 3 && a ? 'T' : 'F'}", &context).unwrap();
+        assert_eq!(result, "T");
+        let result =
+            render_template("{(one == 1 || two == 3) && a ? 'T' : 'F'}", &context).unwrap();
+        assert_eq!(result, "F");
+        // 过滤器只作用于前一个值，括号可改变顺序
+        let result = render_template("{'v' + ver | upper}", &context).unwrap();
+        assert_eq!(result, "vRC1");
+        let result = render_template("{('v' + ver) | upper}", &context).unwrap();
+        assert_eq!(result, "VRC1");
+        // 取反与省略的假值分支
+        let result = render_template("{!a ? 'empty' : 'set'}|{a ? 'set'}", &context).unwrap();
+        assert_eq!(result, "empty|");
+        // 不带参数的过滤器后的 `:` 属于三元表达式
+        let result = render_template("{b ? b | upper : 'none'}", &context).unwrap();
+        assert_eq!(result, "X");
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        for template in [
+            "{name",
+            "{name | uper}",
+            "{'abc}",
+            "{a ? }",
+            "{}",
+            "{a = b}",
+        ] {
+            assert!(Template::parse(template).is_err(), "{}", template);
+        }
+        let error = Template::parse("Hi {name | uper}").unwrap_err();
+        assert!(error.contains("uper"), "{}", error);
+    }
+
+    #[test]
+    fn test_compiled_cache() {
+        let first = compile("{stem} v{version.major}").unwrap();
+        let second = compile("{stem} v{version.major}").unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let context = json!({ "stem": "app", "version": { "value": "3.1", "major": 3 } });
+        assert_eq!(render_compiled(&first, &context), "app v3");
+    }
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(0), "0 B");
+        assert_eq!(human_size(1023), "1023 B");
+        assert_eq!(human_size(1536), "1.5 KB");
+        assert_eq!(human_size(5 * 1024 * 1024), "5.0 MB");
+    }
 }
@@ -0,0 +1,593 @@
+/// 已编译的模板
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    /// 模板片段
+    pub segments: Vec<Segment>,
+}
+
+/// 模板片段
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    /// 原样输出的文本（`{{`、`}}` 已还原为 `{`、`}`）
+    Text(String),
+    /// `{...}` 中的表达式
+    Expr(Expr),
+}
+
+/// 表达式
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// 字符串或数字字面量
+    Literal(String),
+    /// 变量，支持以 `.` 访问对象成员
+    Var(String),
+    /// 取反：`!a`
+    Not(Box<Expr>),
+    /// 字符串拼接：`a + b`
+    Concat(Box<Expr>, Box<Expr>),
+    /// 比较：`a == b`、`a >= b` 等
+    Compare(CompareOp, Box<Expr>, Box<Expr>),
+    /// 逻辑与：`a && b`
+    And(Box<Expr>, Box<Expr>),
+    /// 逻辑或：`a || b`
+    Or(Box<Expr>, Box<Expr>),
+    /// 默认值：`a ?? b`，`a` 为空时使用 `b`
+    Default(Box<Expr>, Box<Expr>),
+    /// 三元表达式：`cond ? then : else`，`else` 可省略
+    Conditional {
+        condition: Box<Expr>,
+        then: Box<Expr>,
+        otherwise: Option<Box<Expr>>,
+    },
+    /// 过滤器：`a | filter:arg1,arg2`
+    Filter(Box<Expr>, Filter),
+}
+
+/// 比较运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+/// 过滤器及其参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    /// 过滤器类型
+    pub kind: FilterKind,
+    /// 参数，均按文本处理
+    pub args: Vec<String>,
+}
+
+/// 过滤器类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterKind {
+    Upper,
+    Lower,
+    Trim,
+    Capitalize,
+    Title,
+    Length,
+    Slice,
+    Default,
+    Cut,
+    Truncate,
+    Replace,
+    Date,
+}
+
+impl FilterKind {
+    /// 按名称查找过滤器（不区分大小写）
+    fn from_name(name: &str) -> Option<FilterKind> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "upper" => FilterKind::Upper,
+            "lower" => FilterKind::Lower,
+            "trim" => FilterKind::Trim,
+            "capitalize" => FilterKind::Capitalize,
+            "title" => FilterKind::Title,
+            "length" => FilterKind::Length,
+            "slice" => FilterKind::Slice,
+            "default" => FilterKind::Default,
+            "cut" => FilterKind::Cut,
+            "truncate" => FilterKind::Truncate,
+            "replace" => FilterKind::Replace,
+            "date" => FilterKind::Date,
+            _ => return None,
+        })
+    }
+
+    /// 是否接受参数；不接受参数的过滤器后的 `:` 属于三元表达式
+    fn takes_args(self) -> bool {
+        matches!(
+            self,
+            FilterKind::Slice
+                | FilterKind::Default
+                | FilterKind::Cut
+                | FilterKind::Truncate
+                | FilterKind::Replace
+                | FilterKind::Date
+        )
+    }
+}
+
+/// 表达式中的词法单元
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// 变量名
+    Ident(String),
+    /// 字符串字面量（已处理转义）
+    Str(String),
+    /// 数字字面量
+    Number(String),
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    Question,
+    DoubleQuestion,
+    Colon,
+    Pipe,
+    OrOr,
+    AndAnd,
+    Not,
+    Plus,
+    Compare(CompareOp),
+    /// 模板结束
+    Eof,
+}
+
+impl Token {
+    /// 错误信息中显示的文本
+    fn describe(&self) -> String {
+        match self {
+            Token::Ident(name) => format!("`{}`", name),
+            Token::Str(s) => format!("string '{}'", s),
+            Token::Number(n) => format!("number {}", n),
+            Token::LBrace => "`{`".to_string(),
+            Token::RBrace => "`}`".to_string(),
+            Token::LParen => "`(`".to_string(),
+            Token::RParen => "`)`".to_string(),
+            Token::Question => "`?`".to_string(),
+            Token::DoubleQuestion => "`??`".to_string(),
+            Token::Colon => "`:`".to_string(),
+            Token::Pipe => "`|`".to_string(),
+            Token::OrOr => "`||`".to_string(),
+            Token::AndAnd => "`&&`".to_string(),
+            Token::Not => "`!`".to_string(),
+            Token::Plus => "`+`".to_string(),
+            Token::Compare(op) => format!(
+                "`{}`",
+                match op {
+                    CompareOp::Eq => "==",
+                    CompareOp::Ne => "!=",
+                    CompareOp::Gt => ">",
+                    CompareOp::Ge => ">=",
+                    CompareOp::Lt => "<",
+                    CompareOp::Le => "<=",
+                }
+            ),
+            Token::Eof => "end of template".to_string(),
+        }
+    }
+}
+
+/// 词法分析器，按字符扫描模板
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Lexer {
+    fn new(source: &str) -> Self {
+        Lexer {
+            chars: source.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_char_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek_char().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// 读取下一个词法单元，返回其起始位置（从 1 开始的列号）
+    fn next_token(&mut self) -> Result<(Token, usize), String> {
+        self.skip_whitespace();
+        let column = self.pos + 1;
+        let Some(ch) = self.peek_char() else {
+            return Ok((Token::Eof, column));
+        };
+        let next = self.peek_char_at(1);
+
+        // 双字符运算符
+        let pair = match (ch, next) {
+            ('?', Some('?')) => Some(Token::DoubleQuestion),
+            ('|', Some('|')) => Some(Token::OrOr),
+            ('&', Some('&')) => Some(Token::AndAnd),
+            ('=', Some('=')) => Some(Token::Compare(CompareOp::Eq)),
+            ('!', Some('=')) => Some(Token::Compare(CompareOp::Ne)),
+            ('>', Some('=')) => Some(Token::Compare(CompareOp::Ge)),
+            ('<', Some('=')) => Some(Token::Compare(CompareOp::Le)),
+            _ => None,
+        };
+        if let Some(token) = pair {
+            self.pos += 2;
+            return Ok((token, column));
+        }
+
+        let single = match ch {
+            '{' => Some(Token::LBrace),
+            '}' => Some(Token::RBrace),
+            '(' => Some(Token::LParen),
+            ')' => Some(Token::RParen),
+            '?' => Some(Token::Question),
+            ':' => Some(Token::Colon),
+            '|' => Some(Token::Pipe),
+            '!' => Some(Token::Not),
+            '+' => Some(Token::Plus),
+            '>' => Some(Token::Compare(CompareOp::Gt)),
+            '<' => Some(Token::Compare(CompareOp::Lt)),
+            _ => None,
+        };
+        if let Some(token) = single {
+            self.pos += 1;
+            return Ok((token, column));
+        }
+
+        if ch == '\'' || ch == '"' {
+            return Ok((Token::Str(self.read_string()?), column));
+        }
+        if ch.is_ascii_digit() || (ch == '-' && next.is_some_and(|c| c.is_ascii_digit())) {
+            return Ok((Token::Number(self.read_number()), column));
+        }
+        if ch.is_alphabetic() || ch == '_' {
+            return Ok((Token::Ident(self.read_ident()), column));
+        }
+        Err(format!(
+            "unexpected character `{}` at column {}",
+            ch, column
+        ))
+    }
+
+    /// 读取字符串字面量
+    ///
+    /// 反斜杠仅转义引号与反斜杠本身，其余反斜杠原样保留，以便直接书写 Windows 路径
+    fn read_string(&mut self) -> Result<String, String> {
+        let column = self.pos + 1;
+        let quote = self.chars[self.pos];
+        self.pos += 1;
+        let mut value = String::new();
+        while let Some(ch) = self.peek_char() {
+            self.pos += 1;
+            match ch {
+                '\\' => match self.peek_char() {
+                    Some(escaped @ ('\\' | '\'' | '"')) => {
+                        value.push(escaped);
+                        self.pos += 1;
+                    }
+                    _ => value.push('\\'),
+                },
+                c if c == quote => return Ok(value),
+                c => value.push(c),
+            }
+        }
+        Err(format!("unterminated string starting at column {}", column))
+    }
+
+    /// 读取数字字面量，可带负号与小数部分
+    fn read_number(&mut self) -> String {
+        let start = self.pos;
+        if self.peek_char() == Some('-') {
+            self.pos += 1;
+        }
+        while self.peek_char().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if self.peek_char() == Some('.') && self.peek_char_at(1).is_some_and(|c| c.is_ascii_digit())
+        {
+            self.pos += 1;
+            while self.peek_char().is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// 读取变量名，如 `version.major`、`product@zh-CN`
+    fn read_ident(&mut self) -> String {
+        let start = self.pos;
+        let mut has_lang = false;
+        while let Some(ch) = self.peek_char() {
+            match ch {
+                c if c.is_alphanumeric() || c == '_' || c == '.' => {}
+                '@' => has_lang = true,
+                // 语言标识中的连字符，如 `zh-CN`
+                '-' if has_lang => {}
+                _ => break,
+            }
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// 读取过滤器参数：以 `,` 分隔，每个参数为字符串字面量或不含空白的文本
+    ///
+    /// 文本参数遇到空白、`,`、`|`、`}`、`)` 结束，如 `replace:Hello,Hi`、`date:%Y-%m-%d`
+    fn read_filter_args(&mut self) -> Result<Vec<String>, String> {
+        let mut args = Vec::new();
+        loop {
+            self.skip_whitespace();
+            let arg = match self.peek_char() {
+                Some('\'' | '"') => self.read_string()?,
+                _ => {
+                    let start = self.pos;
+                    while self
+                        .peek_char()
+                        .is_some_and(|c| !c.is_whitespace() && !matches!(c, ',' | '|' | '}' | ')'))
+                    {
+                        self.pos += 1;
+                    }
+                    self.chars[start..self.pos].iter().collect()
+                }
+            };
+            args.push(arg);
+
+            self.skip_whitespace();
+            if self.peek_char() == Some(',') {
+                self.pos += 1;
+            } else {
+                return Ok(args);
+            }
+        }
+    }
+}
+
+/// 递归下降语法分析器
+///
+/// 优先级从低到高：`??`、三元表达式、`||`、`&&`、比较、`+`、`!`、过滤器 `|`
+struct Parser<'a> {
+    lexer: &'a mut Lexer,
+    /// 预读的词法单元及其列号
+    peeked: Option<(Token, usize)>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(lexer: &'a mut Lexer) -> Self {
+        Parser {
+            lexer,
+            peeked: None,
+        }
+    }
+
+    fn peek(&mut self) -> Result<&Token, String> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.lexer.next_token()?);
+        }
+        Ok(&self.peeked.as_ref().unwrap().0)
+    }
+
+    fn next(&mut self) -> Result<(Token, usize), String> {
+        match self.peeked.take() {
+            Some(token) => Ok(token),
+            None => self.lexer.next_token(),
+        }
+    }
+
+    /// 读取指定的词法单元，否则报错
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        let (token, column) = self.next()?;
+        if token == expected {
+            Ok(())
+        } else {
+            Err(format!(
+                "expected {} but found {} at column {}",
+                expected.describe(),
+                token.describe(),
+                column
+            ))
+        }
+    }
+
+    /// 表达式：`a ?? b`
+    fn parse_expression(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_conditional()?;
+        while self.peek()? == &Token::DoubleQuestion {
+            self.next()?;
+            let right = self.parse_conditional()?;
+            left = Expr::Default(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    /// 三元表达式：`cond ? then : else`，右结合
+    fn parse_conditional(&mut self) -> Result<Expr, String> {
+        let condition = self.parse_or()?;
+        if self.peek()? != &Token::Question {
+            return Ok(condition);
+        }
+        self.next()?;
+        let then = self.parse_expression()?;
+        let otherwise = if self.peek()? == &Token::Colon {
+            self.next()?;
+            Some(Box::new(self.parse_conditional()?))
+        } else {
+            None
+        };
+        Ok(Expr::Conditional {
+            condition: Box::new(condition),
+            then: Box::new(then),
+            otherwise,
+        })
+    }
+
+    /// `a || b`
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_and()?;
+        while self.peek()? == &Token::OrOr {
+            self.next()?;
+            let right = self.parse_and()?;
+            left = Expr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    /// `a && b`
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_comparison()?;
+        while self.peek()? == &Token::AndAnd {
+            self.next()?;
+            let right = self.parse_comparison()?;
+            left = Expr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    /// `a == b`、`a < b` 等
+    fn parse_comparison(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_concat()?;
+        while let Token::Compare(op) = *self.peek()? {
+            self.next()?;
+            let right = self.parse_concat()?;
+            left = Expr::Compare(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    /// `a + b`
+    fn parse_concat(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_unary()?;
+        while self.peek()? == &Token::Plus {
+            self.next()?;
+            let right = self.parse_unary()?;
+            left = Expr::Concat(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    /// `!a`
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        if self.peek()? == &Token::Not {
+            self.next()?;
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_filtered()
+    }
+
+    /// `a | filter | filter:arg`
+    fn parse_filtered(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_primary()?;
+        while self.peek()? == &Token::Pipe {
+            self.next()?;
+            let (token, column) = self.next()?;
+            let Token::Ident(name) = token else {
+                return Err(format!(
+                    "expected a filter name but found {} at column {}",
+                    token.describe(),
+                    column
+                ));
+            };
+            let kind = FilterKind::from_name(&name)
+                .ok_or_else(|| format!("unknown filter `{}` at column {}", name, column))?;
+
+            // 参数紧跟在过滤器名称后的 `:` 之后，此时没有预读的词法单元
+            let args = if kind.takes_args() && self.lexer.peek_char() == Some(':') {
+                self.lexer.pos += 1;
+                self.lexer.read_filter_args()?
+            } else {
+                Vec::new()
+            };
+            expr = Expr::Filter(Box::new(expr), Filter { kind, args });
+        }
+        Ok(expr)
+    }
+
+    /// 字面量、变量或括号（`{...}`、`(...)`）中的表达式
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        let (token, column) = self.next()?;
+        match token {
+            Token::Str(s) | Token::Number(s) => Ok(Expr::Literal(s)),
+            Token::Ident(name) => Ok(Expr::Var(name)),
+            Token::LBrace => {
+                let expr = self.parse_expression()?;
+                self.expect(Token::RBrace)?;
+                Ok(expr)
+            }
+            Token::LParen => {
+                let expr = self.parse_expression()?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            token => Err(format!(
+                "expected a value but found {} at column {}",
+                token.describe(),
+                column
+            )),
+        }
+    }
+}
+
+impl Template {
+    /// 解析模板
+    ///
+    /// `{...}` 中为表达式，`{{`、`}}` 输出 `{`、`}`，单独的 `}` 原样输出
+    ///
+    /// # 参数
+    /// - `source`: 模板字符串
+    ///
+    /// # 返回值
+    /// - `Ok(Template)`: 已编译的模板
+    /// - `Err(...)`: 语法错误的说明
+    pub fn parse(source: &str) -> Result<Template, String> {
+        let mut lexer = Lexer::new(source);
+        let mut segments = Vec::new();
+        let mut text = String::new();
+
+        while let Some(ch) = lexer.peek_char() {
+            let next = lexer.peek_char_at(1);
+            if (ch == '{' && next == Some('{')) || (ch == '}' && next == Some('}')) {
+                text.push(ch);
+                lexer.pos += 2;
+                continue;
+            }
+            if ch != '{' {
+                text.push(ch);
+                lexer.pos += 1;
+                continue;
+            }
+
+            let column = lexer.pos + 1;
+            lexer.pos += 1;
+            if !text.is_empty() {
+                segments.push(Segment::Text(std::mem::take(&mut text)));
+            }
+            let mut parser = Parser::new(&mut lexer);
+            if parser.peek()? == &Token::Eof {
+                return Err(format!("unclosed `{{` at column {}", column));
+            }
+            let expr = parser.parse_expression()?;
+            if parser.peek()? == &Token::Eof {
+                return Err(format!("unclosed `{{` at column {}", column));
+            }
+            parser.expect(Token::RBrace)?;
+            segments.push(Segment::Expr(expr));
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+
+        Ok(Template { segments })
+    }
+}
@@ -2,15 +2,19 @@ use super::*;
 use crate::find_software_best_exe;
 use std::fs::File;
 use std::path::Path;
+use std::sync::Mutex;
 use tempfile::TempDir;
 
+/// 扫描报告为全局状态，使用报告的测试需串行执行
+static REPORT_LOCK: Mutex<()> = Mutex::new(());
+
 #[cfg(test)]
 mod tests {
     // ============================================
     // parse_hotkey 函数测试
     // ============================================
 
-    use crate::config::{ConfigInfo, Lnk};
+    use crate::config::{Arch, ConfigInfo, Detection, FollowLinks, InstallerPolicy, Lnk, ProgramFilter};
     use crate::utils::parse_hotkey;
     use std::path::PathBuf;
 
@@ -163,6 +167,243 @@ hotkey = "Ctrl + Alt + T"
         assert_eq!(lnk.name, Some("Test App".to_string()));
         assert_eq!(lnk.hotkey, Some("Ctrl + Alt + T".to_string()));
     }
+
+    #[test]
+    fn test_toml_deserialize_detection() {
+        let config_content = r#"
+[detection]
+common_subdirs = ["runtime", "app/current"]
+scan_depth = 3
+"#;
+
+        let config: ConfigInfo = toml::from_str(config_content).unwrap();
+        assert_eq!(
+            config.detection.common_subdirs,
+            vec!["runtime", "app/current"]
+        );
+        assert_eq!(config.detection.scan_depth, 3);
+        // 未填写的字段保持默认值
+        assert_eq!(config.detection.max_upward_depth, 3);
+        assert!(config.detection.support_exts.contains(&"dll".to_string()));
+    }
+
+    #[test]
+    fn test_toml_deserialize_installers() {
+        let config: ConfigInfo = toml::from_str(r#"installers = "list""#).unwrap();
+        assert_eq!(config.installers, InstallerPolicy::List);
+
+        // 默认跳过安装程序
+        let config: ConfigInfo = toml::from_str("").unwrap();
+        assert_eq!(config.installers, InstallerPolicy::Skip);
+
+        assert!(toml::from_str::<ConfigInfo>(r#"installers = "run""#).is_err());
+    }
+
+    #[test]
+    fn test_toml_deserialize_follow_links() {
+        let config: ConfigInfo = toml::from_str(
+            r#"
+follow_links = "within_root"
+prefer_link_path = false
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.follow_links, FollowLinks::WithinRoot);
+        assert!(!config.prefer_link_path);
+
+        // 默认不跟随链接，快捷方式指向链接路径
+        let config: ConfigInfo = toml::from_str("").unwrap();
+        assert_eq!(config.follow_links, FollowLinks::Never);
+        assert!(config.prefer_link_path);
+    }
+
+    #[test]
+    fn test_toml_deserialize_arch() {
+        let config: ConfigInfo = toml::from_str(
+            r#"
+prefer_arch = "x86"
+keep_arch_variants = true
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.prefer_arch, Some(Arch::X86));
+        assert!(config.keep_arch_variants);
+
+        let config: ConfigInfo = toml::from_str("").unwrap();
+        assert_eq!(config.prefer_arch, None);
+        assert!(!config.keep_arch_variants);
+        assert_eq!(Arch::from_machine(0xAA64), Some(Arch::Arm64));
+    }
+
+    #[test]
+    fn test_toml_deserialize_filter() {
+        let config: ConfigInfo = toml::from_str(
+            r#"
+[filter]
+exclude_console = true
+min_size = 4096
+allowed_arch = ["x64", "arm64"]
+"#,
+        )
+        .unwrap();
+        assert!(config.filter.exclude_console);
+        assert_eq!(config.filter.min_size, Some(4096));
+        assert_eq!(config.filter.max_size, None);
+        assert!(!config.filter.require_signature);
+        assert_eq!(config.filter.allowed_arch, vec![Arch::X64, Arch::Arm64]);
+
+        let config: ConfigInfo = toml::from_str("").unwrap();
+        assert_eq!(config.filter, ProgramFilter::default());
+        assert!(config.trusted_signers.is_empty());
+
+        let config: ConfigInfo = toml::from_str(
+            r#"
+trusted_signers = ["Microsoft Corporation"]
+
+[filter]
+require_trusted_signer = true
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.trusted_signers, vec!["Microsoft Corporation"]);
+        assert!(config.filter.require_trusted_signer);
+    }
+
+    #[test]
+    fn test_toml_deserialize_limits() {
+        let config: ConfigInfo = toml::from_str(
+            r#"
+[limits]
+max_depth = 6
+time_budget = 300
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.limits.max_depth, Some(6));
+        assert_eq!(config.limits.time_budget, Some(300));
+        // 未填写的字段保持默认值
+        assert_eq!(config.limits.max_entries_per_dir, None);
+        assert!(
+            config
+                .limits
+                .heavy_dirs
+                .contains(&"node_modules".to_string())
+        );
+    }
+
+    #[test]
+    fn test_toml_deserialize_hybrid_root_folder() {
+        let config: ConfigInfo = toml::from_str(r#"hybrid_root_folder = "Tools""#).unwrap();
+        assert_eq!(config.hybrid_root_folder.as_deref(), Some("Tools"));
+
+        let config: ConfigInfo = toml::from_str("").unwrap();
+        assert_eq!(config.hybrid_root_folder, None);
+    }
+
+    #[test]
+    fn test_toml_deserialize_name_language() {
+        use crate::config::NameLanguage;
+
+        let config: ConfigInfo = toml::from_str(
+            r#"
+[template]
+name_language = "zh-CN"
+"#,
+        )
+        .unwrap();
+        let template = config.template.unwrap();
+        assert_eq!(
+            template.name_language,
+            NameLanguage::Single("zh-CN".to_string())
+        );
+        assert_eq!(template.name_language.lang_ids(), vec![0x0804]);
+
+        let config: ConfigInfo = toml::from_str(
+            r#"
+[template]
+name_language = ["zh-TW", "unknown", "en", "zh-TW"]
+"#,
+        )
+        .unwrap();
+        let template = config.template.unwrap();
+        assert_eq!(template.name_language.lang_ids(), vec![0x0404, 0x0409]);
+
+        let config: ConfigInfo = toml::from_str("").unwrap();
+        assert_eq!(
+            config.template.unwrap().name_language,
+            NameLanguage::default()
+        );
+    }
+
+    #[test]
+    fn test_toml_deserialize_icon_cache() {
+        let config: ConfigInfo = toml::from_str(r#"icon_cache = "C:\\Cache\\Icons""#).unwrap();
+        assert_eq!(config.icon_cache.as_deref(), Some("C:\\Cache\\Icons"));
+
+        let config: ConfigInfo = toml::from_str("").unwrap();
+        assert_eq!(config.icon_cache, None);
+    }
+
+    #[test]
+    fn test_toml_deserialize_roots() {
+        let config: ConfigInfo = toml::from_str(
+            r#"
+ignore = ["uninstall"]
+score_ratio = 0.3
+
+[[root]]
+path = "D:\\Apps"
+dest = "C:\\Links\\Apps"
+
+[[root]]
+path = "E:\\Games"
+create_dir = true
+ignore = ["launcher"]
+score_ratio = 0.5
+template = { name = "{stem}" }
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.root.len(), 2);
+        assert_eq!(config.root[0].path, "D:\\Apps");
+        assert_eq!(config.root[0].dest.as_deref(), Some("C:\\Links\\Apps"));
+        assert_eq!(config.root[0].create_dir, None);
+
+        // 扫描目录配置合并到全局配置上
+        let scoped = config.root[1].apply(&config);
+        assert!(scoped.root.is_empty());
+        assert_eq!(scoped.ignore, vec!["uninstall", "launcher"]);
+        assert_eq!(scoped.score_ratio, Some(0.5));
+        assert_eq!(
+            scoped.template.and_then(|t| t.name).as_deref(),
+            Some("{stem}")
+        );
+        assert_eq!(config.root[1].create_dir, Some(true));
+
+        let scoped = config.root[0].apply(&config);
+        assert_eq!(scoped.score_ratio, Some(0.3));
+    }
+
+    #[test]
+    fn test_detection_dir_matches() {
+        let list = vec!["bin".to_string(), "Engine/Binaries".to_string()];
+        assert!(Detection::dir_matches(
+            &list,
+            &PathBuf::from("D:\\Apps\\Tool\\BIN")
+        ));
+        assert!(Detection::dir_matches(
+            &list,
+            &PathBuf::from("D:\\Game\\engine\\binaries")
+        ));
+        assert!(!Detection::dir_matches(
+            &list,
+            &PathBuf::from("D:\\Game\\Binaries")
+        ));
+        assert!(!Detection::dir_matches(
+            &list,
+            &PathBuf::from("D:\\Apps\\cabinet")
+        ));
+    }
 }
 
 /// 测试创建基本的快捷方式
@@ -245,6 +486,1572 @@ fn test_get_shortcut_target_invalid_path() {
     assert!(result.is_err(), "读取不存在的快捷方式应该失败");
 }
 
+/// 测试安装程序识别
+#[test]
+fn test_detect_installer() {
+    use crate::utils::{detect_installer, InstallerKind};
+
+    let temp_dir = TempDir::new().unwrap();
+
+    // MSI 文件头
+    let msi_path = temp_dir.path().join("package.bin");
+    fs::write(
+        &msi_path,
+        [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1, 0, 0, 0, 0],
+    )
+    .unwrap();
+    assert_eq!(
+        detect_installer(&msi_path).unwrap(),
+        Some(InstallerKind::Msi)
+    );
+
+    // 不是 PE 文件
+    let text_path = temp_dir.path().join("fake.exe");
+    fs::write(&text_path, "not a program").unwrap();
+    assert_eq!(detect_installer(&text_path).unwrap(), None);
+
+    // 附加 ZIP 的普通程序（如 NW.js、LÖVE 应用）不是自解压程序
+    let program = include_bytes!("../tests/fixtures/unsigned.exe");
+    let mut appended = program.to_vec();
+    appended.extend_from_slice(b"PK\x03\x04\x14\x00\x00\x00\x08\x00");
+    let appended_path = temp_dir.path().join("game.exe");
+    fs::write(&appended_path, &appended).unwrap();
+    assert_eq!(detect_installer(&appended_path).unwrap(), None);
+
+    // 程序主体包含自解压程序特征字符串
+    let stub = b"This program cannot be run in DOS mode";
+    let offset = appended
+        .windows(stub.len())
+        .position(|window| window == stub)
+        .unwrap();
+    appended[offset..offset + 8].copy_from_slice(b"UnZipSFX");
+    let sfx_path = temp_dir.path().join("sfx.exe");
+    fs::write(&sfx_path, &appended).unwrap();
+    assert_eq!(
+        detect_installer(&sfx_path).unwrap(),
+        Some(InstallerKind::ZipSfx)
+    );
+
+    // 文件不存在
+    assert!(detect_installer(temp_dir.path().join("missing.exe")).is_err());
+}
+
+/// 测试程序运行时识别
+#[test]
+fn test_detect_runtime() {
+    use crate::utils::{detect_runtime, get_dotnet_main_assembly, is_runtime_helper, AppRuntime};
+
+    let temp_dir = TempDir::new().unwrap();
+
+    // Electron
+    let electron = temp_dir.path().join("Chat");
+    fs::create_dir_all(electron.join("resources")).unwrap();
+    File::create(electron.join("resources").join("app.asar")).unwrap();
+    let main_exe = electron.join("Chat.exe");
+    let update_exe = electron.join("Update.exe");
+    File::create(&main_exe).unwrap();
+    File::create(&update_exe).unwrap();
+    assert_eq!(detect_runtime(&main_exe), AppRuntime::Electron);
+    assert!(!is_runtime_helper(&main_exe, AppRuntime::Electron));
+    assert!(is_runtime_helper(&update_exe, AppRuntime::Electron));
+
+    // Squirrel 安装布局：Update.exe 位于根目录，应用位于 app-x.y.z 中
+    let squirrel = temp_dir.path().join("Discord");
+    let app_dir = squirrel.join("app-1.0.9").join("resources");
+    fs::create_dir_all(&app_dir).unwrap();
+    File::create(app_dir.join("app.asar")).unwrap();
+    let stub_exe = squirrel.join("Discord.exe");
+    let squirrel_update = squirrel.join("Update.exe");
+    File::create(&stub_exe).unwrap();
+    File::create(&squirrel_update).unwrap();
+    assert_eq!(detect_runtime(&stub_exe), AppRuntime::Electron);
+    assert_eq!(detect_runtime(&squirrel_update), AppRuntime::Electron);
+    assert!(is_runtime_helper(&squirrel_update, AppRuntime::Electron));
+    assert!(!is_runtime_helper(&stub_exe, AppRuntime::Electron));
+
+    // .NET apphost
+    let dotnet = temp_dir.path().join("Tool");
+    fs::create_dir_all(&dotnet).unwrap();
+    let apphost = dotnet.join("Tool.exe");
+    File::create(&apphost).unwrap();
+    File::create(dotnet.join("Tool.dll")).unwrap();
+    File::create(dotnet.join("Tool.runtimeconfig.json")).unwrap();
+    assert_eq!(detect_runtime(&apphost), AppRuntime::DotNet);
+    assert_eq!(
+        get_dotnet_main_assembly(&apphost),
+        Some(dotnet.join("Tool.dll"))
+    );
+
+    // 附带 Java 运行时
+    let java = temp_dir.path().join("Ide");
+    fs::create_dir_all(java.join("jre").join("bin")).unwrap();
+    let java_exe = java.join("jre").join("bin").join("java.exe");
+    File::create(&java_exe).unwrap();
+    let launcher = java.join("Ide.exe");
+    File::create(&launcher).unwrap();
+    assert_eq!(detect_runtime(&launcher), AppRuntime::Java);
+    assert!(!is_runtime_helper(&launcher, AppRuntime::Java));
+    assert_eq!(detect_runtime(&java_exe), AppRuntime::Java);
+    assert!(is_runtime_helper(&java_exe, AppRuntime::Java));
+
+    // 原生程序
+    let native = temp_dir.path().join("Native");
+    fs::create_dir_all(&native).unwrap();
+    let native_exe = native.join("Native.exe");
+    fs::write(&native_exe, "not a program").unwrap();
+    assert_eq!(detect_runtime(&native_exe), AppRuntime::Native);
+    assert_eq!(AppRuntime::Native.label(), "native");
+}
+
+/// 写入仅包含文件头的 PE 文件
+fn write_pe_header(path: &Path, machine: u16) {
+    let mut data = vec![0u8; 0x58];
+    data[..2].copy_from_slice(b"MZ");
+    data[0x3C..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+    data[0x40..0x44].copy_from_slice(b"PE\0\0");
+    data[0x44..0x46].copy_from_slice(&machine.to_le_bytes());
+    fs::write(path, data).unwrap();
+}
+
+/// 测试架构变体识别
+#[test]
+fn test_arch_variants() {
+    use crate::config::Arch;
+    use crate::utils::{arch_variant_key, ArchVariant, ArchVariants};
+
+    assert_eq!(arch_variant_key("PotPlayerMini64"), "potplayermini");
+    assert_eq!(arch_variant_key("PotPlayerMini"), "potplayermini");
+    assert_eq!(arch_variant_key("tool_x64"), "tool");
+    assert_eq!(arch_variant_key("x64"), "x64");
+
+    // 同目录中的架构变体
+    let temp_dir = TempDir::new().unwrap();
+    let tool = temp_dir.path().join("tool.exe");
+    let tool64 = temp_dir.path().join("tool64.exe");
+    let other = temp_dir.path().join("other.exe");
+    write_pe_header(&tool, 0x014c);
+    write_pe_header(&tool64, 0x8664);
+    write_pe_header(&other, 0x8664);
+
+    let arch_variants = ArchVariants::new();
+    assert_eq!(arch_variants.find(&tool).len(), 2);
+    assert!(arch_variants.find(&other).is_empty());
+    assert_eq!(
+        arch_variants.choose(&tool, Some(Arch::X64), false),
+        ArchVariant::Skip(tool64.clone())
+    );
+    assert_eq!(
+        arch_variants.choose(&tool64, Some(Arch::X64), false),
+        ArchVariant::Keep
+    );
+    assert_eq!(
+        arch_variants.choose(&tool64, Some(Arch::X86), false),
+        ArchVariant::Skip(tool.clone())
+    );
+    assert_eq!(
+        arch_variants.choose(&other, Some(Arch::X64), false),
+        ArchVariant::Keep
+    );
+
+    // 名称相近但机器类型相同，或后缀与机器类型不一致的程序不是架构变体
+    let util = temp_dir.path().join("util.exe");
+    let util32 = temp_dir.path().join("util32.exe");
+    let crc = temp_dir.path().join("crc.exe");
+    let crc32 = temp_dir.path().join("crc32.exe");
+    let setup_win32 = temp_dir.path().join("SetupWin32.exe");
+    let setup_win64 = temp_dir.path().join("SetupWin64.exe");
+    write_pe_header(&util, 0x014c);
+    write_pe_header(&util32, 0x014c);
+    write_pe_header(&crc, 0x014c);
+    write_pe_header(&crc32, 0x8664);
+    write_pe_header(&setup_win32, 0x8664);
+    write_pe_header(&setup_win64, 0x8664);
+    let arch_variants = ArchVariants::new();
+    assert!(arch_variants.find(&util32).is_empty());
+    assert!(arch_variants.find(&crc32).is_empty());
+    assert!(arch_variants.find(&setup_win32).is_empty());
+    assert_eq!(
+        arch_variants.choose(&util32, Some(Arch::X64), false),
+        ArchVariant::Keep
+    );
+    assert_eq!(arch_variants.find(&tool).len(), 2);
+
+    // 保留全部变体，追加架构后缀
+    let variant = arch_variants.choose(&tool, Some(Arch::X64), true);
+    assert_eq!(variant, ArchVariant::Suffix(Arch::X86));
+    assert_eq!(variant.suffix().as_deref(), Some("x86"));
+
+    // x86、x64 子目录中的架构变体
+    let app = temp_dir.path().join("App");
+    fs::create_dir_all(app.join("x86")).unwrap();
+    fs::create_dir_all(app.join("x64")).unwrap();
+    let app_x86 = app.join("x86").join("App.exe");
+    let app_x64 = app.join("x64").join("App.exe");
+    write_pe_header(&app_x86, 0x014c);
+    write_pe_header(&app_x64, 0x8664);
+    assert_eq!(
+        arch_variants.choose(&app_x86, Some(Arch::X64), false),
+        ArchVariant::Skip(app_x64.clone())
+    );
+}
+
+/// 测试忽略规则 - 通配符、相对路径、取反
+#[test]
+fn test_ignore_rules_patterns() {
+    use crate::rules::IgnoreRules;
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+
+    let mut config_info = ConfigInfo::default();
+    config_info.ignore = vec![
+        "bin".to_string(),
+        "*.old.exe".to_string(),
+        "!keep.old.exe".to_string(),
+        "/Tools/Temp".to_string(),
+        "**/Cache/**".to_string(),
+        "uninstall".to_string(),
+    ];
+    let rules = IgnoreRules::new(root, Some(&config_info));
+
+    // 不含通配符的规则匹配完整文件名，不再匹配包含该文本的文件名
+    assert!(rules.is_ignored(&root.join("App").join("bin"), true));
+    assert!(!rules.is_ignored(&root.join("App").join("cabinet.exe"), false));
+    assert!(rules.is_ignored(&root.join("App").join("uninstall.exe"), false));
+
+    // 通配符与取反
+    assert!(rules.is_ignored(&root.join("App").join("app.old.exe"), false));
+    assert!(!rules.is_ignored(&root.join("App").join("keep.old.exe"), false));
+
+    // 相对于扫描根目录的路径
+    assert!(rules.is_ignored(&root.join("Tools").join("Temp"), true));
+    assert!(!rules.is_ignored(&root.join("App").join("Tools").join("Temp"), true));
+    assert!(rules.is_ignored(&root.join("App").join("Cache").join("a.exe"), false));
+
+    // 父目录被忽略时，其中的文件同样被忽略
+    assert!(rules.is_ignored(&root.join("App").join("bin").join("main.exe"), false));
+
+    // 系统目录
+    assert!(rules.is_ignored(&root.join("$RECYCLE.BIN"), true));
+    assert!(!rules.is_ignored(&root.join("RecoveryTool"), true));
+}
+
+/// 测试忽略规则 - 目录级规则文件与包含列表
+#[test]
+fn test_ignore_rules_dir_file_and_include() {
+    use crate::rules::{IgnoreRules, IGNORE_FILE_NAME};
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let app_dir = root.join("App");
+    fs::create_dir_all(&app_dir).unwrap();
+    fs::write(
+        app_dir.join(IGNORE_FILE_NAME),
+        "# 注释\nhelper.exe\n/sub/\n",
+    )
+    .unwrap();
+
+    let mut config_info = ConfigInfo::default();
+    config_info.include = vec!["App/".to_string(), "!*.bak.exe".to_string()];
+    let rules = IgnoreRules::new(root, Some(&config_info));
+
+    // 目录级规则仅作用于其所在目录
+    assert!(rules.is_ignored(&app_dir.join("helper.exe"), false));
+    assert!(rules.is_ignored(&app_dir.join("sub"), true));
+    assert!(!rules.is_ignored(&root.join("helper.exe"), false));
+    assert!(!rules.is_ignored(&app_dir.join("main.exe"), false));
+
+    // 包含列表
+    assert!(rules.is_included(&app_dir.join("main.exe")));
+    assert!(!rules.is_included(&app_dir.join("main.bak.exe")));
+    assert!(!rules.is_included(&root.join("Other").join("main.exe")));
+}
+
+/// 测试目录配置 - 指定主程序、快捷方式属性与作用范围
+#[test]
+fn test_dir_overrides() {
+    use crate::config::DirKind;
+    use crate::overrides::DirOverrides;
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let app_dir = root.join("App");
+    fs::create_dir_all(app_dir.join("sub")).unwrap();
+    fs::create_dir_all(root.join("Other")).unwrap();
+    fs::write(
+        app_dir.join(".autoshortcut.toml"),
+        r#"
+main = "bin\App.exe"
+name = "My App"
+score_ratio = 0.5
+
+[[shortcut]]
+exec = "tool.exe"
+name = "Tool"
+"#,
+    )
+    .unwrap();
+
+    let overrides = DirOverrides::new(root);
+    let dir_override = overrides.load(&app_dir).expect("应该读取到目录配置");
+    assert_eq!(
+        dir_override.main_path(),
+        Some(app_dir.join("bin").join("App.exe"))
+    );
+    assert_eq!(dir_override.kind(), Some(DirKind::Green));
+    assert!(dir_override.is_main(&app_dir.join("BIN").join("app.exe")));
+    assert!(!dir_override.is_main(&app_dir.join("tool.exe")));
+
+    // 快捷方式属性覆盖全局配置
+    let mut global_lnk = Lnk::new("App.exe".to_string());
+    global_lnk.name = Some("Global".to_string());
+    global_lnk.args = Some("--global".to_string());
+    let lnk = dir_override
+        .config
+        .merge_lnk(&app_dir.join("bin").join("App.exe"), Some(global_lnk))
+        .unwrap();
+    assert_eq!(lnk.name, Some("My App".to_string()));
+    assert_eq!(lnk.args, Some("--global".to_string()));
+
+    // 评分阈值与程序信息作用于子目录
+    let mut global = ConfigInfo::default();
+    global.score_ratio = Some(0.3);
+    global.shortcut.push(Lnk::new("tool.exe".to_string()));
+    let scoped = overrides
+        .scoped_config(Some(&global), &app_dir.join("sub"))
+        .unwrap();
+    assert_eq!(scoped.score_ratio, Some(0.5));
+    assert_eq!(scoped.shortcut.len(), 2);
+    assert_eq!(
+        PathBuf::from(&scoped.shortcut[0].exec),
+        app_dir.join("tool.exe")
+    );
+    // 同一目录的合并结果只计算一次
+    assert!(Arc::ptr_eq(
+        &scoped,
+        &overrides
+            .scoped_config(Some(&global), &app_dir.join("sub"))
+            .unwrap()
+    ));
+
+    // 其它目录不受影响
+    assert!(
+        overrides
+            .scoped_config(Some(&global), &root.join("Other"))
+            .is_none()
+    );
+}
+
+/// 测试目录配置文件与忽略规则文件不影响目录结构识别
+#[test]
+fn test_scan_config_files_not_counted() {
+    use crate::rules::{IgnoreRules, IGNORE_FILE_NAME};
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let detection = Detection::default();
+
+    // 单文件程序目录中放入目录配置与忽略规则文件后仍为单文件程序目录
+    let tools = root.join("Tools");
+    fs::create_dir_all(&tools).unwrap();
+    for name in ["a.exe", "b.exe", ".autoshortcut.toml", IGNORE_FILE_NAME] {
+        File::create(tools.join(name)).unwrap();
+    }
+    assert!(is_single_file_dir(&tools, None, &detection));
+    File::create(tools.join("readme.txt")).unwrap();
+    assert!(!is_single_file_dir(&tools, None, &detection));
+
+    // 混合目录根目录中的目录配置文件不计为未知文件
+    let mixed = root.join("Mixed");
+    fs::create_dir_all(mixed.join("App")).unwrap();
+    for name in ["tool.exe", "autoshortcut.toml", IGNORE_FILE_NAME] {
+        File::create(mixed.join(name)).unwrap();
+    }
+    File::create(mixed.join("App").join("app.exe")).unwrap();
+    let rules = IgnoreRules::new(root, None);
+    let limits = ScanLimits::default();
+    assert!(is_hybrid_software_dir(
+        &limits.read_entries(&mixed, 1).unwrap(),
+        1,
+        &rules,
+        &detection,
+        &limits
+    ));
+}
+
+/// 测试目录类型识别时不读取超出扫描限制的子目录
+#[test]
+fn test_detection_respects_limits() {
+    use crate::rules::IgnoreRules;
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let detection = Detection::default();
+    let rules = IgnoreRules::new(root, None);
+    let limits = ScanLimits::default();
+
+    // 分类目录中只有 node_modules 时不视为分类目录
+    let category = root.join("Category");
+    fs::create_dir_all(category.join("node_modules")).unwrap();
+    File::create(category.join("node_modules").join("tool.exe")).unwrap();
+    let entries = |dir: &Path| limits.read_entries(dir, 1).unwrap();
+    assert!(!is_category_dir(
+        &entries(&category),
+        1,
+        &detection,
+        &limits
+    ));
+    fs::create_dir_all(category.join("App")).unwrap();
+    File::create(category.join("App").join("app.exe")).unwrap();
+    assert!(is_category_dir(&entries(&category), 1, &detection, &limits));
+
+    // 混合目录的子目录超出深度限制时不读取
+    let mixed = root.join("Mixed");
+    fs::create_dir_all(mixed.join("App")).unwrap();
+    File::create(mixed.join("tool.exe")).unwrap();
+    for name in ["app.exe", "other.exe"] {
+        File::create(mixed.join("App").join(name)).unwrap();
+    }
+    assert!(is_hybrid_software_dir(
+        &entries(&mixed),
+        1,
+        &rules,
+        &detection,
+        &limits
+    ));
+    let limits = ScanLimits {
+        max_depth: Some(1),
+        ..Default::default()
+    };
+    assert!(!is_hybrid_software_dir(
+        &entries(&mixed),
+        1,
+        &rules,
+        &detection,
+        &limits
+    ));
+}
+
+/// 测试混合目录：根目录中的程序按单文件程序处理并放在指定的子文件夹中，子目录作为独立的软件识别
+#[test]
+fn test_hybrid_pipeline() {
+    let temp_dir = TempDir::new().unwrap();
+    let apps = temp_dir.path().join("Apps");
+    let links = temp_dir.path().join("Links");
+
+    // 混合目录：根目录中的零散工具、绿色软件子目录、单文件程序子目录
+    let mixed = apps.join("Mixed");
+    fs::create_dir_all(mixed.join("Editor")).unwrap();
+    fs::create_dir_all(mixed.join("Single")).unwrap();
+    for path in [
+        mixed.join("convert.exe"),
+        mixed.join("resize.exe"),
+        mixed.join("Editor").join("Editor.exe"),
+        mixed.join("Editor").join("Editor.dll"),
+        mixed.join("Single").join("viewer.exe"),
+    ] {
+        File::create(path).unwrap();
+    }
+
+    let config_path = temp_dir.path().join("config.toml");
+    fs::write(&config_path, "hybrid_root_folder = \"Tools\"\n").unwrap();
+
+    let journal = Journal::new();
+    auto_shortcut(
+        &ScanRoot {
+            path: &apps,
+            lnk_path: Some(&links),
+            config: None,
+            journal: &journal,
+        },
+        Some(&config_path),
+        Some(&ConfigInfo::parse_config_file(&config_path).unwrap()),
+        ScanOptions {
+            only_match: false,
+            create_dir: false,
+            install_script: false,
+            install_parallel: false,
+            start: false,
+            list_mode: false,
+            use_filename: true,
+            score_ratio: Some(0.0),
+        },
+    )
+    .unwrap();
+
+    let mut shortcuts: Vec<(PathBuf, PathBuf)> = journal
+        .entries()
+        .into_iter()
+        .map(|entry| (entry.program, entry.shortcut))
+        .collect();
+    shortcuts.sort();
+    assert_eq!(
+        shortcuts,
+        vec![
+            (
+                mixed.join("Editor").join("Editor.exe"),
+                links.join("Editor.lnk")
+            ),
+            (
+                mixed.join("Single").join("viewer.exe"),
+                links.join("viewer.lnk")
+            ),
+            (
+                mixed.join("convert.exe"),
+                links.join("Tools").join("convert.lnk")
+            ),
+            (
+                mixed.join("resize.exe"),
+                links.join("Tools").join("resize.lnk")
+            ),
+        ]
+    );
+}
+
+/// 测试分类目录中的 MSI 安装包按安装程序列出
+#[test]
+fn test_list_msi_installer() {
+    use crate::report;
+
+    let temp_dir = TempDir::new().unwrap();
+    let apps = temp_dir.path().join("Apps");
+    fs::create_dir_all(apps.join("App")).unwrap();
+    File::create(apps.join("App").join("App.exe")).unwrap();
+    let package = apps.join("package.msi");
+    fs::write(
+        &package,
+        [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1, 0, 0, 0, 0],
+    )
+    .unwrap();
+
+    let config_path = temp_dir.path().join("config.toml");
+    fs::write(&config_path, "installers = \"list\"\n").unwrap();
+
+    let _guard = REPORT_LOCK.lock().unwrap();
+    report::enable();
+    auto_shortcut(
+        &ScanRoot {
+            path: &apps,
+            lnk_path: None,
+            config: None,
+            journal: &Journal::new(),
+        },
+        Some(&config_path),
+        Some(&ConfigInfo::parse_config_file(&config_path).unwrap()),
+        ScanOptions {
+            only_match: false,
+            create_dir: false,
+            install_script: false,
+            install_parallel: false,
+            start: false,
+            list_mode: true,
+            use_filename: false,
+            score_ratio: None,
+        },
+    )
+    .unwrap();
+
+    let json: serde_json::Value = serde_json::from_str(&report::to_json().unwrap()).unwrap();
+    let programs = json["programs"].as_array().unwrap();
+    assert!(programs.iter().any(|program| {
+        program["path"] == package.to_str().unwrap() && program["installer"] == "MSI"
+    }));
+}
+
+/// 测试文件唯一标识与链接路径解析
+#[test]
+fn test_file_id_and_resolve_link_path() {
+    use crate::utils::{get_file_id, resolve_link_path};
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir_a = temp_dir.path().join("A");
+    let dir_b = temp_dir.path().join("B");
+    fs::create_dir_all(&dir_a).unwrap();
+    fs::create_dir_all(&dir_b).unwrap();
+
+    // 同一目录的不同写法标识相同，不同目录标识不同
+    let id_a = get_file_id(&dir_a).unwrap();
+    assert_eq!(id_a, get_file_id(dir_b.join("..").join("A")).unwrap());
+    assert_ne!(id_a, get_file_id(&dir_b).unwrap());
+
+    // 解析后的路径不包含 `\\?\` 前缀
+    let resolved = resolve_link_path(&dir_b.join("..").join("A"));
+    assert!(!resolved.to_string_lossy().starts_with(r"\\?\"));
+    assert_eq!(get_file_id(&resolved).unwrap(), id_a);
+}
+
+/// 测试命令行扫描目录参数解析
+#[test]
+fn test_root_parser() {
+    use crate::cli::root_parser;
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().to_string_lossy().to_string();
+
+    let parsed = root_parser(&format!("{}=C:\\Links", root)).unwrap();
+    assert_eq!(parsed.path, root);
+    assert_eq!(parsed.dest.as_deref(), Some("C:\\Links"));
+
+    // 省略快捷方式位置
+    let parsed = root_parser(&root).unwrap();
+    assert!(parsed.dest.is_none());
+
+    // 扫描目录必须存在
+    assert!(
+        root_parser(&format!(
+            "{}=C:\\Links",
+            temp_dir.path().join("Missing").display()
+        ))
+        .is_err()
+    );
+}
+
+/// 测试扫描报告
+#[test]
+fn test_report_json() {
+    use crate::console::ConsoleType;
+    use crate::report;
+
+    let temp_dir = TempDir::new().unwrap();
+    let app = temp_dir.path().join("App");
+
+    let _guard = REPORT_LOCK.lock().unwrap();
+    report::enable();
+    assert!(report::is_enabled());
+    report::record_directory(&app, "green", "detected");
+    report::record_candidates(vec![report::CandidateRecord {
+        path: app.join("App.exe"),
+        score: 90,
+        threshold: 118,
+        breakdown: vec![report::ScoreItem {
+            metric: "gui".to_string(),
+            score: 50,
+        }],
+        selected: false,
+    }]);
+    report::record_message(
+        &ConsoleType::Warning,
+        "scan.main_not_recognized",
+        "not recognized",
+    );
+    // 仅记录警告、错误
+    report::record_message(&ConsoleType::Info, "directory.green", "green");
+
+    let report_path = temp_dir.path().join("report.json");
+    report::save(&report_path).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    let directories = json["directories"].as_array().unwrap();
+    assert!(
+        directories
+            .iter()
+            .any(|dir| dir["kind"] == "green" && dir["reason"] == "detected")
+    );
+    let candidates = json["candidates"].as_array().unwrap();
+    assert!(
+        candidates
+            .iter()
+            .any(|c| c["score"] == 90 && c["breakdown"][0]["metric"] == "gui")
+    );
+    let messages = json["messages"].as_array().unwrap();
+    assert!(
+        messages
+            .iter()
+            .any(|m| m["level"] == "warning" && m["key"] == "scan.main_not_recognized")
+    );
+    assert!(!messages.iter().any(|m| m["key"] == "directory.green"));
+}
+
+/// 测试扫描限制
+#[test]
+fn test_scan_limits() {
+    use crate::config::ScanLimits;
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let modules = root.join("web").join("node_modules");
+    let assets = root.join("Game").join("Assets");
+    fs::create_dir_all(&modules).unwrap();
+    fs::create_dir_all(&assets).unwrap();
+    for i in 0..5 {
+        File::create(assets.join(format!("{}.dat", i))).unwrap();
+    }
+
+    let limits = ScanLimits {
+        max_depth: Some(1),
+        max_entries_per_dir: Some(4),
+        ..Default::default()
+    };
+    assert_eq!(limits.check_dir(root, 0), None);
+    assert_eq!(limits.check_dir(&root.join("web"), 1), None);
+    assert_eq!(limits.check_dir(&modules, 2), Some("heavy_dir"));
+    assert_eq!(limits.check_dir(&assets, 2), Some("max_depth"));
+
+    // 文件数限制在读取目录时检查，默认不限制
+    assert!(ScanLimits::default().read_entries(&assets, 2).is_some());
+    let limits = ScanLimits {
+        max_entries_per_dir: Some(4),
+        ..Default::default()
+    };
+    assert!(limits.read_entries(&assets, 2).is_none());
+    assert_eq!(limits.read_entries(&root.join("Game"), 1).unwrap().len(), 1);
+    assert_eq!(limits.read_entries(root, 0).unwrap().len(), 2);
+}
+
+/// 测试程序过滤条件
+#[test]
+fn test_program_filter() {
+    use crate::config::ProgramFilter;
+
+    let temp_dir = TempDir::new().unwrap();
+    let stub = temp_dir.path().join("stub.exe");
+    fs::write(&stub, [0u8; 16]).unwrap();
+
+    assert_eq!(ProgramFilter::default().check(&stub, &[]), None);
+    let filter = ProgramFilter {
+        min_size: Some(1024),
+        ..Default::default()
+    };
+    assert_eq!(filter.check(&stub, &[]), Some("min_size"));
+    let filter = ProgramFilter {
+        max_size: Some(8),
+        ..Default::default()
+    };
+    assert_eq!(filter.check(&stub, &[]), Some("max_size"));
+    let filter = ProgramFilter {
+        allowed_arch: vec![Arch::X64],
+        ..Default::default()
+    };
+    assert_eq!(filter.check(&stub, &[]), Some("allowed_arch"));
+    let filter = ProgramFilter {
+        require_version_info: true,
+        ..Default::default()
+    };
+    assert_eq!(filter.check(&stub, &[]), Some("require_version_info"));
+    let filter = ProgramFilter {
+        require_signature: true,
+        ..Default::default()
+    };
+    assert_eq!(filter.check(&stub, &[]), Some("require_signature"));
+    let filter = ProgramFilter {
+        require_trusted_signer: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        filter.check(&stub, &["Example Corp".to_string()]),
+        Some("require_trusted_signer")
+    );
+}
+
+/// 测试数字签名的受信任签名者判断
+#[test]
+fn test_signature_trusted() {
+    use crate::authenticode::Signature;
+
+    let trusted = vec!["Microsoft Corporation".to_string()];
+    let signature = Signature {
+        signer: Some("Microsoft Corporation".to_string()),
+        digest_valid: true,
+        verified: true,
+        ..Default::default()
+    };
+    assert!(signature.is_trusted(&trusted));
+    assert!(signature.is_trusted(&[" microsoft corporation ".to_string()]));
+    assert!(!signature.is_trusted(&[]));
+
+    // 摘要不一致的签名不受信任
+    let tampered = Signature {
+        digest_valid: false,
+        verified: false,
+        ..signature.clone()
+    };
+    assert!(!tampered.is_trusted(&trusted));
+
+    // 摘要一致但签名者签名或证书链未通过验证的签名不受信任
+    let forged = Signature {
+        verified: false,
+        ..signature.clone()
+    };
+    assert!(!forged.is_trusted(&trusted));
+
+    // 没有签名的程序
+    let temp_dir = TempDir::new().unwrap();
+    let stub = temp_dir.path().join("stub.exe");
+    fs::write(&stub, [0u8; 16]).unwrap();
+    assert!(Signature::read(&stub).is_err());
+}
+
+/// 获取 PE 文件中安全目录（数据目录第 4 项）的偏移
+fn security_directory_offset(pe: &[u8]) -> usize {
+    let header = u32::from_le_bytes(pe[0x3C..0x40].try_into().unwrap()) as usize;
+    let optional_header = header + 24;
+    let data_directories =
+        match u16::from_le_bytes(pe[optional_header..optional_header + 2].try_into().unwrap()) {
+            // PE32+
+            0x20B => optional_header + 112,
+            _ => optional_header + 96,
+        };
+    data_directories + 4 * 8
+}
+
+/// 测试从签名程序中解析数字签名并校验程序摘要
+#[test]
+fn test_signature_digest() {
+    use crate::authenticode::Signature;
+
+    let signed = include_bytes!("../tests/fixtures/signed.exe");
+    let unsigned = include_bytes!("../tests/fixtures/unsigned.exe");
+
+    // 签名程序
+    let signature = Signature::from_pe(signed).unwrap().unwrap();
+    assert_eq!(signature.signer.as_deref(), Some("Anaconda, Inc."));
+    assert!(signature.issuer.as_ref().unwrap().contains("DigiCert"));
+    assert!(signature.timestamp.is_some());
+    assert!(signature.digest_valid);
+    // 只解析内容时不验证签名者签名与证书链
+    assert!(!signature.verified);
+    assert!(!signature.is_trusted(&["Anaconda, Inc.".to_string()]));
+
+    // 没有签名的程序
+    assert_eq!(Signature::from_pe(unsigned).unwrap(), None);
+
+    // 修改程序内容后摘要不一致
+    let mut patched = signed.to_vec();
+    patched[0x400] ^= 0xFF;
+    let signature = Signature::from_pe(&patched).unwrap().unwrap();
+    assert_eq!(signature.signer.as_deref(), Some("Anaconda, Inc."));
+    assert!(!signature.digest_valid);
+
+    // 摘要不一致的程序不经过系统验证
+    let temp_dir = TempDir::new().unwrap();
+    let patched_path = temp_dir.path().join("patched.exe");
+    fs::write(&patched_path, &patched).unwrap();
+    let signature = Signature::read(&patched_path).unwrap().unwrap();
+    assert!(!signature.digest_valid);
+    assert!(!signature.verified);
+
+    // 从其他程序移植的签名摘要不一致
+    let directory = security_directory_offset(signed);
+    let offset = u32::from_le_bytes(signed[directory..directory + 4].try_into().unwrap()) as usize;
+    let size = u32::from_le_bytes(signed[directory + 4..directory + 8].try_into().unwrap());
+    let mut transplanted = unsigned.to_vec();
+    let directory = security_directory_offset(&transplanted);
+    let certificate_offset = transplanted.len() as u32;
+    transplanted.extend_from_slice(&signed[offset..offset + size as usize]);
+    transplanted[directory..directory + 4].copy_from_slice(&certificate_offset.to_le_bytes());
+    transplanted[directory + 4..directory + 8].copy_from_slice(&size.to_le_bytes());
+    let signature = Signature::from_pe(&transplanted).unwrap().unwrap();
+    assert_eq!(signature.signer.as_deref(), Some("Anaconda, Inc."));
+    assert!(!signature.digest_valid);
+    assert!(!signature.verified);
+}
+
+/// 测试内置的常见程序信息
+#[test]
+fn test_known_apps() {
+    use crate::known_apps::{database, KnownApps};
+
+    // 内置数据库可以解析
+    let db = database().unwrap();
+    assert!(db.version >= 1);
+    assert!(!db.app.is_empty());
+    assert!(
+        db.app
+            .iter()
+            .all(|app| !app.exe.is_empty() && app.name.contains_key("en"))
+    );
+
+    let db = KnownApps::parse(
+        r#"
+version = 1
+
+[[app]]
+exe = ["PotPlayerMini64.exe", "PotPlayerMini.exe"]
+helpers = ["Uninstall.exe"]
+category = "Video"
+name = { en = "PotPlayer", zh-CN = "PotPlayer 播放器" }
+"#,
+    )
+    .unwrap();
+
+    let temp_dir = TempDir::new().unwrap();
+    let app_dir = temp_dir.path().join("PotPlayer");
+    fs::create_dir_all(&app_dir).unwrap();
+    let main = app_dir.join("PotPlayerMini64.exe");
+    let sibling = app_dir.join("potplayermini.exe");
+    let helper = app_dir.join("Uninstall.exe");
+    for path in [&main, &sibling, &helper] {
+        File::create(path).unwrap();
+    }
+
+    let app = db.find(&main).unwrap();
+    assert_eq!(app.category.as_deref(), Some("Video"));
+    assert_eq!(app.exe_rank(&main), Some(0));
+    assert_eq!(app.exe_rank(&sibling), Some(1));
+    assert!(db.is_helper(&helper));
+    assert!(!db.is_helper(&main));
+
+    // 主程序不存在时，同名程序不视为辅助程序
+    let other = temp_dir.path().join("Other");
+    fs::create_dir_all(&other).unwrap();
+    File::create(other.join("Uninstall.exe")).unwrap();
+    assert!(!db.is_helper(&other.join("Uninstall.exe")));
+    assert!(db.find(&other.join("Uninstall.exe")).is_none());
+}
+
+/// 构造版本信息中的数据块
+fn version_block(
+    key: &str,
+    value: &[u8],
+    value_length: u16,
+    is_text: bool,
+    children: &[Vec<u8>],
+) -> Vec<u8> {
+    let mut block = vec![0u8; 6];
+    block[2..4].copy_from_slice(&value_length.to_le_bytes());
+    block[4..6].copy_from_slice(&u16::from(is_text).to_le_bytes());
+    for unit in key.encode_utf16().chain([0]) {
+        block.extend(unit.to_le_bytes());
+    }
+    block.resize(block.len().next_multiple_of(4), 0);
+    block.extend(value);
+    for child in children {
+        block.resize(block.len().next_multiple_of(4), 0);
+        block.extend(child);
+    }
+    let length = block.len() as u16;
+    block[0..2].copy_from_slice(&length.to_le_bytes());
+    block
+}
+
+/// 构造版本信息中的字符串
+fn version_string(key: &str, value: &str) -> Vec<u8> {
+    let units: Vec<u16> = value.encode_utf16().chain([0]).collect();
+    let bytes: Vec<u8> = units.iter().flat_map(|unit| unit.to_le_bytes()).collect();
+    version_block(key, &bytes, units.len() as u16, true, &[])
+}
+
+/// 测试图标转换为 `.ico` 文件与 PNG 图片
+#[test]
+fn test_icon_conversion() {
+    use crate::icon::{IconGroup, IconImage};
+    use crate::pe_resource::ResourceName;
+
+    // 2x2 32 位 BMP：BITMAPINFOHEADER（高度包含掩码）+ 自下而上的 BGRA + 掩码
+    let mut dib = Vec::new();
+    for value in [40u32, 2, 4] {
+        dib.extend(value.to_le_bytes());
+    }
+    dib.extend(1u16.to_le_bytes());
+    dib.extend(32u16.to_le_bytes());
+    dib.extend([0u8; 24]);
+    dib.extend([0, 0, 255, 255, 0, 255, 0, 255, 255, 0, 0, 255, 0, 0, 0, 0]);
+    dib.extend([0u8; 8]);
+
+    let image = IconImage {
+        id: 1,
+        width: 2,
+        height: 2,
+        color_count: 0,
+        planes: 1,
+        bit_count: 32,
+        is_png: false,
+        data: dib.clone(),
+    };
+    let png = image.to_png().unwrap();
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+    assert_eq!(&png[12..16], b"IHDR");
+    assert_eq!(&png[16..24], &[0, 0, 0, 2, 0, 0, 0, 2]);
+    assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]));
+
+    let large = IconImage {
+        id: 2,
+        width: 256,
+        height: 256,
+        is_png: true,
+        data: png.clone(),
+        ..image.clone()
+    };
+    assert_eq!(large.to_png().unwrap(), png);
+
+    let group = IconGroup {
+        name: ResourceName::Id(1),
+        lang: 0x0409,
+        images: vec![image, large],
+    };
+    assert_eq!(group.max_size(), 256);
+    let ico = group.to_ico();
+    assert_eq!(&ico[..6], &[0, 0, 1, 0, 2, 0]);
+    // 第一个图标：2x2，数据紧跟在目录之后
+    assert_eq!(&ico[6..8], &[2, 2]);
+    assert_eq!(&ico[18..22], &(6u32 + 16 * 2).to_le_bytes());
+    // 256 像素的尺寸写入 0
+    assert_eq!(&ico[22..24], &[0, 0]);
+    assert_eq!(&ico[38..38 + dib.len()], &dib[..]);
+    assert_eq!(ico.len(), 38 + dib.len() + png.len());
+}
+
+/// 测试图标缓存的读取与清理
+#[test]
+fn test_icon_cache() {
+    use crate::icon_cache::{collect_garbage, load_icon, CacheIndex, INDEX_FILE_NAME};
+
+    let temp_dir = TempDir::new().unwrap();
+    let ico = temp_dir.path().join("app.ico");
+    fs::write(&ico, [0u8, 0, 1, 0, 0, 0]).unwrap();
+    assert_eq!(load_icon(&ico, 0).unwrap(), Some(vec![0u8, 0, 1, 0, 0, 0]));
+
+    // 不存在的目录无需清理
+    let cache = temp_dir.path().join("cache");
+    assert_eq!(collect_garbage(&cache).unwrap(), 0);
+
+    // 没有索引时不清理
+    fs::create_dir_all(&cache).unwrap();
+    File::create(cache.join("00000000000000c3.ico")).unwrap();
+    assert_eq!(collect_garbage(&cache).unwrap(), 0);
+    assert!(cache.join("00000000000000c3.ico").exists());
+
+    // 快捷方式已删除的图标、没有记录的图标被删除，用户自己的图标与其他文件保留
+    let link = temp_dir.path().join("App.lnk");
+    File::create(&link).unwrap();
+    let mut index = CacheIndex::default();
+    index.links.insert(
+        link.to_string_lossy().to_string(),
+        "00000000000000a1.ico".to_string(),
+    );
+    index.links.insert(
+        temp_dir
+            .path()
+            .join("Removed.lnk")
+            .to_string_lossy()
+            .to_string(),
+        "00000000000000b2.ico".to_string(),
+    );
+    fs::write(
+        cache.join(INDEX_FILE_NAME),
+        serde_json::to_string(&index).unwrap(),
+    )
+    .unwrap();
+    for name in [
+        "00000000000000a1.ico",
+        "00000000000000b2.ico",
+        "app.ico",
+        "notes.txt",
+    ] {
+        File::create(cache.join(name)).unwrap();
+    }
+
+    assert_eq!(collect_garbage(&cache).unwrap(), 2);
+    assert!(cache.join("00000000000000a1.ico").exists());
+    assert!(!cache.join("00000000000000b2.ico").exists());
+    assert!(!cache.join("00000000000000c3.ico").exists());
+    assert!(cache.join("app.ico").exists());
+    assert!(cache.join("notes.txt").exists());
+    let index: CacheIndex =
+        serde_json::from_str(&fs::read_to_string(cache.join(INDEX_FILE_NAME)).unwrap()).unwrap();
+    assert_eq!(index.links.len(), 1);
+
+    // 索引无法解析时不清理
+    File::create(cache.join("00000000000000d4.ico")).unwrap();
+    fs::write(cache.join(INDEX_FILE_NAME), "{").unwrap();
+    assert!(collect_garbage(&cache).is_err());
+    assert!(cache.join("00000000000000d4.ico").exists());
+}
+
+/// 测试模板的结构化上下文与平铺别名
+#[test]
+fn test_template_context() {
+    use crate::template::ProgramContext;
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path().join("Apps").join("Editor");
+    fs::create_dir_all(&dir).unwrap();
+    let stub = dir.join("editor.exe");
+    fs::write(&stub, [0u8; 2048]).unwrap();
+
+    let render = |template: &str| ProgramContext::new(&stub, &[]).render(template);
+    assert_eq!(
+        render("{file.name}|{file.stem}|{file.ext}"),
+        "editor.exe|editor|exe"
+    );
+    assert_eq!(render("{file.size}|{file.size.human}"), "2048|2.0 KB");
+    assert_eq!(render("{dir.name}|{dir.parent.name}"), "Editor|Apps");
+
+    // 原有的平铺变量名
+    assert_eq!(render("{stem}|{parent_name}|{size}"), "editor|Editor|2048");
+
+    // 不是 PE 文件时为空
+    assert_eq!(
+        render("{version}{version.major}{pe.machine}{signature.subject}"),
+        ""
+    );
+    assert_eq!(render("{signature.valid}"), "false");
+}
+
+/// 测试模板变量按需计算
+#[test]
+fn test_template_lazy_context() {
+    use crate::template::ProgramContext;
+
+    let temp_dir = TempDir::new().unwrap();
+    let stub = temp_dir.path().join("tool.exe");
+    fs::write(&stub, [0u8; 1024]).unwrap();
+
+    let context = ProgramContext::new(&stub, &[]);
+    assert_eq!(
+        context.render("{stem} ({file.size.human})"),
+        "tool (1.0 KB)"
+    );
+    assert!(context.is_loaded("stem"));
+    // 只使用文件系统变量时不读取程序
+    assert!(!context.is_loaded("version"));
+    assert!(!context.is_loaded("pe.machine"));
+    assert!(!context.is_loaded("signer"));
+
+    // 条件分支中未被选中的变量也不计算
+    assert_eq!(context.render("{stem ? stem : product}"), "tool");
+    assert!(!context.is_loaded("product"));
+
+    // 同一分组的变量一起计算，后续模板共用
+    assert_eq!(context.render("{product ? product : stem}"), "tool");
+    assert!(context.is_loaded("desc"));
+    assert!(!context.is_loaded("manifest_name"));
+}
+
+/// 测试程序清单解析
+#[test]
+fn test_manifest() {
+    use crate::manifest::Manifest;
+
+    let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<assembly xmlns="urn:schemas-microsoft-com:asm.v1" manifestVersion="1.0" xmlns:asmv3="urn:schemas-microsoft-com:asm.v3">
+  <assemblyIdentity type="win32" name="Contoso.Tools.Editor" version="2.1.0.0" processorArchitecture="amd64"/>
+  <dependency>
+    <dependentAssembly>
+      <assemblyIdentity type="win32" name="Microsoft.Windows.Common-Controls" version="6.0.0.0" processorArchitecture="*"/>
+    </dependentAssembly>
+  </dependency>
+  <trustInfo xmlns="urn:schemas-microsoft-com:asm.v3">
+    <security>
+      <requestedPrivileges>
+        <requestedExecutionLevel level="requireAdministrator" uiAccess="false"/>
+      </requestedPrivileges>
+    </security>
+  </trustInfo>
+  <asmv3:application>
+    <asmv3:windowsSettings>
+      <dpiAware xmlns="http://schemas.microsoft.com/SMI/2005/WindowsSettings">true/pm</dpiAware>
+    </asmv3:windowsSettings>
+  </asmv3:application>
+</assembly>"#;
+
+    // 资源数据末尾的填充
+    let mut data = xml.as_bytes().to_vec();
+    data.extend([0, 0, 0]);
+    let manifest = Manifest::parse(&data).unwrap();
+    assert_eq!(manifest.name.as_deref(), Some("Contoso.Tools.Editor"));
+    assert_eq!(manifest.version.as_deref(), Some("2.1.0.0"));
+    assert_eq!(manifest.processor_architecture.as_deref(), Some("amd64"));
+    assert_eq!(
+        manifest.execution_level.as_deref(),
+        Some("requireAdministrator")
+    );
+    assert_eq!(manifest.dpi_aware.as_deref(), Some("true/pm"));
+    assert_eq!(manifest.display_name(), Some("Editor"));
+    assert!(manifest.requires_admin());
+    assert!(manifest.is_dpi_aware());
+
+    // 带 BOM 的 UTF-16 清单
+    let utf16: Vec<u8> = [0xFF, 0xFE]
+        .into_iter()
+        .chain(xml.encode_utf16().flat_map(u16::to_le_bytes))
+        .collect();
+    assert_eq!(Manifest::parse(&utf16), Some(manifest));
+
+    // dpiAwareness 优先于 dpiAware，项目模板中的默认名称被忽略
+    let manifest = Manifest::parse(
+        br#"<assembly xmlns="urn:schemas-microsoft-com:asm.v1" manifestVersion="1.0">
+  <assemblyIdentity version="1.0.0.0" name="MyApplication.app"/>
+  <application xmlns="urn:schemas-microsoft-com:asm.v3">
+    <windowsSettings>
+      <dpiAware>true</dpiAware>
+      <dpiAwareness>unaware</dpiAwareness>
+    </windowsSettings>
+  </application>
+</assembly>"#,
+    )
+    .unwrap();
+    assert_eq!(manifest.display_name(), None);
+    assert_eq!(manifest.execution_level, None);
+    assert!(!manifest.is_dpi_aware());
+    assert!(!manifest.requires_admin());
+
+    assert_eq!(Manifest::parse(b"not xml"), None);
+}
+
+/// 测试语言名称
+#[test]
+fn test_lang_id_from_tag() {
+    use crate::version_info::lang_id_from_tag;
+
+    assert_eq!(lang_id_from_tag("en"), Some(0x0409));
+    assert_eq!(lang_id_from_tag("zh-cn"), Some(0x0804));
+    assert_eq!(lang_id_from_tag("zh_TW"), Some(0x0404));
+    assert_eq!(lang_id_from_tag("ja-JP"), Some(0x0411));
+    assert_eq!(lang_id_from_tag("0c04"), Some(0x0C04));
+    assert_eq!(lang_id_from_tag("unknown"), None);
+}
+
+/// 测试解析版本信息（多个字符串表）
+#[test]
+fn test_version_info() {
+    use crate::version_info::VersionInfo;
+
+    let mut fixed = Vec::new();
+    for value in [
+        0xFEEF_04BDu32,
+        0x0001_0000,
+        0x0002_0001,
+        0x0003_0004,
+        0x0002_0001,
+        0x0000_0000,
+        0x3F,
+        0,
+        0x0004_0004,
+        1,
+        0,
+        0,
+        0,
+    ] {
+        fixed.extend(value.to_le_bytes());
+    }
+    let string_file_info = version_block(
+        "StringFileInfo",
+        &[],
+        0,
+        true,
+        &[
+            version_block(
+                "080404b0",
+                &[],
+                0,
+                true,
+                &[
+                    version_string("ProductName", "示例程序"),
+                    version_string("CompanyName", "示例公司"),
+                ],
+            ),
+            version_block(
+                "040904b0",
+                &[],
+                0,
+                true,
+                &[
+                    version_string("ProductName", "Sample App"),
+                    version_string("OriginalFilename", "sample.exe"),
+                ],
+            ),
+        ],
+    );
+    let translation = [0x04u8, 0x08, 0xB0, 0x04, 0x09, 0x04, 0xB0, 0x04];
+    let var_file_info = version_block(
+        "VarFileInfo",
+        &[],
+        0,
+        true,
+        &[version_block("Translation", &translation, 8, false, &[])],
+    );
+    let data = version_block(
+        "VS_VERSION_INFO",
+        &fixed,
+        fixed.len() as u16,
+        false,
+        &[string_file_info, var_file_info],
+    );
+
+    let info = VersionInfo::parse(&data).unwrap();
+    let fixed = info.fixed.unwrap();
+    assert_eq!(fixed.file_version, [2, 1, 3, 4]);
+    assert_eq!(fixed.file_version_string(), "2.1.3.4");
+    assert_eq!(fixed.product_version, [2, 1, 0, 0]);
+    assert_eq!(info.translations, vec![(0x0804, 0x04B0), (0x0409, 0x04B0)]);
+    assert_eq!(info.tables.len(), 2);
+    assert_eq!(
+        info.tables[1]
+            .strings
+            .get("OriginalFilename")
+            .map(String::as_str),
+        Some("sample.exe")
+    );
+
+    // 按语言选择字符串表
+    assert_eq!(
+        info.query_with("ProductName", &[0x0409]),
+        Some("Sample App")
+    );
+    assert_eq!(
+        info.query_with("ProductName", &[0x0804, 0x0409]),
+        Some("示例程序")
+    );
+    // 相同主语言
+    assert_eq!(
+        info.query_with("ProductName", &[0x0404, 0x0409]),
+        Some("示例程序")
+    );
+    // 没有匹配的语言时按 Translation 的顺序
+    assert_eq!(info.query_with("ProductName", &[0x0411]), Some("示例程序"));
+    // 当前语言的字符串表没有该字段时查找其他字符串表
+    assert_eq!(info.query_with("CompanyName", &[0x0409]), Some("示例公司"));
+    assert_eq!(
+        info.query_with("OriginalFilename", &[0x0804]),
+        Some("sample.exe")
+    );
+    assert_eq!(info.query_with("Comments", &[0x0409]), None);
+
+    // 仅查询指定语言（或相同主语言）的字符串表
+    assert_eq!(info.query_lang("ProductName", 0x0809), Some("Sample App"));
+    assert_eq!(info.query_lang("CompanyName", 0x0409), None);
+    assert_eq!(info.query_lang("ProductName", 0x0411), None);
+
+    // 不是版本信息
+    assert!(VersionInfo::parse(&version_string("ProductName", "x")).is_none());
+    assert!(VersionInfo::parse(&[0u8; 4]).is_none());
+}
+
+/// 测试将扫描结果导出为配置文件
+#[test]
+fn test_export_config() {
+    use crate::export::{render_config, MAIN_NOT_RECOGNIZED};
+    use crate::report::{CandidateRecord, DirectoryRecord, Report, ScoreItem, ShortcutRecord};
+
+    let shortcut = |program: &str, name: &str| ShortcutRecord {
+        program: PathBuf::from(program),
+        name: name.to_string(),
+        dest: PathBuf::from(r"C:\Links"),
+        icon: Some(format!("{}#0", program)),
+        args: None,
+        work_dir: Some(r"D:\Apps".to_string()),
+    };
+    let report = Report {
+        version: "1.0.0".to_string(),
+        roots: vec![PathBuf::from(r"D:\Apps")],
+        directories: vec![DirectoryRecord {
+            path: PathBuf::from(r"D:\Apps\Broken"),
+            kind: "skipped".to_string(),
+            reason: MAIN_NOT_RECOGNIZED.to_string(),
+        }],
+        candidates: vec![CandidateRecord {
+            path: PathBuf::from(r"D:\Apps\Tool\Tool.exe"),
+            score: 135,
+            threshold: 118,
+            breakdown: vec![ScoreItem {
+                metric: "gui".to_string(),
+                score: 50,
+            }],
+            selected: true,
+        }],
+        shortcuts: vec![
+            shortcut(r"D:\Apps\Tool\Tool.exe", "Tool"),
+            shortcut(r"D:\Apps\A\setup.exe", "A"),
+            shortcut(r"D:\Apps\Bob's\setup.exe", r#"Bob's "B""#),
+        ],
+        ..Default::default()
+    };
+
+    let content = render_config(&report);
+    assert!(content.contains("# score 135 / 118: gui+50"));
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("AutoShortcut.toml");
+    fs::write(&config_path, &content).unwrap();
+    let config = ConfigInfo::parse_config_file(&config_path).unwrap();
+    assert!(config.only_match);
+    assert_eq!(config.ignore, vec![r"D:\Apps\Broken".to_string()]);
+    assert_eq!(config.shortcut.len(), 3);
+    // 文件名唯一时使用相对路径，否则使用完整路径
+    assert_eq!(config.shortcut[0].exec, "Tool.exe");
+    assert_eq!(config.shortcut[1].exec, r"D:\Apps\A\setup.exe");
+    assert_eq!(config.shortcut[0].name.as_deref(), Some("Tool"));
+    assert_eq!(
+        config.shortcut[0].icon.as_deref(),
+        Some(r"D:\Apps\Tool\Tool.exe")
+    );
+    assert_eq!(config.shortcut[0].dest.as_deref(), Some(r"C:\Links"));
+    assert_eq!(config.shortcut[0].work_dir.as_deref(), Some(r"D:\Apps"));
+    // 路径中的单引号与名称中的双引号
+    assert_eq!(config.shortcut[2].exec, r"D:\Apps\Bob's\setup.exe");
+    assert_eq!(config.shortcut[2].name.as_deref(), Some(r#"Bob's "B""#));
+}
+
+/// 测试交互模式的选择解析与配置文件记录
+#[test]
+fn test_interactive_decision() {
+    use crate::interactive::{
+        add_ignore_rule, add_shortcut_entries, is_ambiguous, parse_selection, Selection,
+    };
+    use crate::report::CandidateRecord;
+
+    assert_eq!(parse_selection("", 3), Some(Selection::Default));
+    assert_eq!(parse_selection("0", 3), Some(Selection::Ignore));
+    assert_eq!(
+        parse_selection("1, 3 1", 3),
+        Some(Selection::Programs(vec![0, 2]))
+    );
+    assert_eq!(parse_selection("4", 3), None);
+    assert_eq!(parse_selection("a", 3), None);
+
+    let candidate = |score, selected| CandidateRecord {
+        path: PathBuf::from("App.exe"),
+        score,
+        threshold: 50,
+        breakdown: Vec::new(),
+        selected,
+    };
+    assert!(is_ambiguous(&[candidate(120, true), candidate(115, false)]));
+    assert!(!is_ambiguous(&[candidate(120, true), candidate(60, false)]));
+    assert!(is_ambiguous(&[candidate(30, false)]));
+    assert!(!is_ambiguous(&[]));
+
+    // 追加到已有的 ignore 列表，保留注释
+    let content = "# comment\nignore = [\"temp\"]\n\n[template]\nname = \"{name}\"\n";
+    let result = add_ignore_rule(content, r"D:\Apps\Tool").unwrap();
+    assert!(result.starts_with("# comment\n"));
+    let config: ConfigInfo = toml::from_str(&result).unwrap();
+    assert_eq!(
+        config.ignore,
+        vec![r"D:\Apps\Tool".to_string(), "temp".to_string()]
+    );
+
+    // 没有 ignore 列表时插入到第一个表之前
+    let result = add_ignore_rule("[template]\r\nname = \"{name}\"\r\n", "Tool").unwrap();
+    assert!(result.starts_with("ignore = [\"Tool\"]\r\n[template]"));
+
+    // 双引号内未转义的 Windows 路径
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("AutoShortcut.toml");
+    let content = "dest = \"C:\\Links\"\nignore = [\"D:\\Temp\"]\n";
+    let result = add_ignore_rule(content, r"D:\Apps\Tool").unwrap();
+    fs::write(&config_path, &result).unwrap();
+    let config = ConfigInfo::parse_config_file(&config_path).unwrap();
+    assert_eq!(
+        config.ignore,
+        vec![r"D:\Apps\Tool".to_string(), r"D:\Temp".to_string()]
+    );
+
+    // 路径中的单引号
+    let program = PathBuf::from(r"D:\Apps\Bob's Tool\Tool.exe");
+    let result = add_shortcut_entries("ignore = []", std::slice::from_ref(&program));
+    assert!(result.contains(r#"exec = "D:\Apps\Bob's Tool\Tool.exe""#));
+    fs::write(&config_path, &result).unwrap();
+    let config = ConfigInfo::parse_config_file(&config_path).unwrap();
+    assert_eq!(PathBuf::from(&config.shortcut[0].exec), program);
+
+    // 启用转义的配置文件使用标准 TOML 字符串
+    let content = "enable_escape = true\nignore = []\n";
+    let result = add_shortcut_entries(content, std::slice::from_ref(&program));
+    let result = add_ignore_rule(&result, r"D:\Bob's").unwrap();
+    fs::write(&config_path, &result).unwrap();
+    let config = ConfigInfo::parse_config_file(&config_path).unwrap();
+    assert_eq!(PathBuf::from(&config.shortcut[0].exec), program);
+    assert_eq!(config.ignore, vec![r"D:\Bob's".to_string()]);
+}
+
+/// 测试多个扫描目录的快捷方式记录与冲突检测
+#[test]
+fn test_journal_conflicts() {
+    use crate::journal::Journal;
+
+    let temp_dir = TempDir::new().unwrap();
+    let root_a = temp_dir.path().join("A");
+    let root_b = temp_dir.path().join("B");
+    fs::create_dir_all(root_a.join("Tool")).unwrap();
+    fs::create_dir_all(&root_b).unwrap();
+    let program = root_a.join("Tool").join("Tool.exe");
+    File::create(&program).unwrap();
+    let other = root_b.join("Tool.exe");
+    File::create(&other).unwrap();
+    let links = temp_dir.path().join("Links");
+
+    let journal = Journal::new();
+    assert!(journal.find_program(&root_a, &program).is_none());
+    assert!(
+        journal
+            .record(&root_a, &program, &links.join("Tool.lnk"))
+            .is_none()
+    );
+
+    // 同一扫描目录中不视为冲突，重叠的扫描目录中跳过同一程序
+    assert!(journal.find_program(&root_a, &program).is_none());
+    let entry = journal
+        .find_program(&root_a.join("Tool"), &program)
+        .unwrap();
+    assert_eq!(entry.root, root_a);
+
+    // 不同程序因同名被重命名
+    let entry = journal
+        .record(&root_b, &other, &links.join("Tool (2).lnk"))
+        .unwrap();
+    assert_eq!(entry.program, program);
+    assert!(
+        journal
+            .record(&root_b, &other, &links.join("Tool (x).lnk"))
+            .is_none()
+    );
+    assert_eq!(journal.entries().len(), 3);
+}
+
+/// 测试 PortableApps.com 格式的程序信息
+#[test]
+fn test_portable_app_info() {
+    use crate::portable::{PortableAppInfo, APP_ICON_PATH, APP_INFO_PATH};
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("FooPortable");
+    fs::create_dir_all(root.join(r"App\AppInfo")).unwrap();
+    fs::create_dir_all(root.join(r"App\Foo")).unwrap();
+    fs::write(
+        root.join(APP_INFO_PATH),
+        "\u{feff}[Format]\r\nType=PortableApps.comFormat\r\n\r\n[Details]\r\nName=Foo Portable\r\nPublisher=Foo Team\r\nCategory=Utilities\r\n\r\n[Control]\r\nIcons=1\r\nStart=FooPortable.exe\r\n",
+    )
+    .unwrap();
+    File::create(root.join(APP_ICON_PATH)).unwrap();
+    let launcher = root.join("FooPortable.exe");
+    File::create(&launcher).unwrap();
+    let program = root.join(r"App\Foo\Foo.exe");
+    File::create(&program).unwrap();
+
+    let app = PortableAppInfo::load(&root).unwrap();
+    assert_eq!(app.name.as_deref(), Some("Foo Portable"));
+    assert_eq!(app.publisher.as_deref(), Some("Foo Team"));
+    assert_eq!(app.category.as_deref(), Some("Utilities"));
+    assert_eq!(app.launcher(), Some(launcher.clone()));
+    assert_eq!(app.icon(), Some(root.join(APP_ICON_PATH)));
+
+    // 程序本体位于 App 子目录中，同样属于该软件
+    let found = PortableAppInfo::for_program(&program).unwrap();
+    assert_eq!(found.root, root);
+    assert!(PortableAppInfo::load(temp_dir.path()).is_none());
+
+    // 仅为启动器补充名称、图标，配置文件中的名称优先
+    let lnk = app.merge_lnk(&launcher, None).unwrap();
+    assert_eq!(lnk.name.as_deref(), Some("Foo Portable"));
+    assert!(lnk.icon.is_some());
+    let mut configured = Lnk::new(launcher.to_string_lossy().to_string());
+    configured.name = Some("Foo".to_string());
+    let lnk = app.merge_lnk(&launcher, Some(configured)).unwrap();
+    assert_eq!(lnk.name.as_deref(), Some("Foo"));
+    assert!(app.merge_lnk(&program, None).is_none());
+
+    // UTF-16 LE 编码
+    let utf16: Vec<u8> = "\u{feff}[Details]\r\nName=Bar\r\n"
+        .encode_utf16()
+        .flat_map(|unit| unit.to_le_bytes())
+        .collect();
+    fs::write(root.join(APP_INFO_PATH), utf16).unwrap();
+    let app = PortableAppInfo::load(&root).unwrap();
+    assert_eq!(app.name.as_deref(), Some("Bar"));
+    assert!(app.launcher().is_none());
+}
+
 /// 测试评分算法 - 配置文件匹配项得分最高
 #[test]
 fn test_scoring_config_match() {
@@ -266,7 +2073,7 @@ fn test_scoring_config_match() {
     let result = find_software_best_exe(
         &test_dir,
         Some(&config_info),
-        temp_dir.path(),
+        &IgnoreRules::new(temp_dir.path(), Some(&config_info)),
         0.0, // score_ratio 为 0 表示不过滤任何得分
         true,
     );
@@ -292,7 +2099,13 @@ fn test_scoring_name_parent_match() {
     let other_exe = app_dir.join("Other.exe");
     File::create(&other_exe).unwrap();
 
-    let result = find_software_best_exe(&app_dir, None, temp_dir.path(), 0.0, true);
+    let result = find_software_best_exe(
+        &app_dir,
+        None,
+        &IgnoreRules::new(temp_dir.path(), None),
+        0.0,
+        true,
+    );
 
     assert!(result.is_some(), "应该找到一个 exe 文件");
     let (_, found_path) = result.unwrap();
@@ -312,7 +2125,13 @@ fn test_scoring_gui_preference() {
     let test_exe = app_dir.join("test.exe");
     File::create(&test_exe).unwrap();
 
-    let result = find_software_best_exe(&app_dir, None, temp_dir.path(), 0.0, true);
+    let result = find_software_best_exe(
+        &app_dir,
+        None,
+        &IgnoreRules::new(temp_dir.path(), None),
+        0.0,
+        true,
+    );
 
     // 测试应该能够运行（即使找不到真正的 GUI 程序）
     // 由于创建的不是真正的 exe，可能找不到任何文件
@@ -344,7 +2163,13 @@ fn test_scoring_ignore_list() {
     config_info.ignore.push("uninstall".to_string());
     config_info.ignore.push("setup".to_string());
 
-    let result = find_software_best_exe(&app_dir, Some(&config_info), temp_dir.path(), 0.0, true);
+    let result = find_software_best_exe(
+        &app_dir,
+        Some(&config_info),
+        &IgnoreRules::new(temp_dir.path(), Some(&config_info)),
+        0.0,
+        true,
+    );
 
     // 如果找到文件，应该是 Main.exe（不被忽略的）
     if result.is_some() {
@@ -360,7 +2185,13 @@ fn test_scoring_empty_directory() {
     let empty_dir = temp_dir.path().join("Empty");
     fs::create_dir_all(&empty_dir).unwrap();
 
-    let result = find_software_best_exe(&empty_dir, None, temp_dir.path(), 0.0, true);
+    let result = find_software_best_exe(
+        &empty_dir,
+        None,
+        &IgnoreRules::new(temp_dir.path(), None),
+        0.0,
+        true,
+    );
 
     assert!(result.is_none(), "空目录不应该返回任何文件");
 }
@@ -380,7 +2211,7 @@ fn test_scoring_threshold() {
     let result = find_software_best_exe(
         &app_dir,
         None,
-        temp_dir.path(),
+        &IgnoreRules::new(temp_dir.path(), None),
         2.0, // 200% 的阈值，只有非常高的分才能通过
         true,
     );
@@ -402,7 +2233,13 @@ fn test_scoring_nested_directories() {
     let deep_exe = sub_dir.join("deep.exe");
     File::create(&deep_exe).unwrap();
 
-    let result = find_software_best_exe(&root_dir, None, temp_dir.path(), 0.0, true);
+    let result = find_software_best_exe(
+        &root_dir,
+        None,
+        &IgnoreRules::new(temp_dir.path(), None),
+        0.0,
+        true,
+    );
 
     // 应该能扫描到子目录中的 exe
     if result.is_some() {
@@ -428,7 +2265,13 @@ fn test_scoring_multiple_exe_selection() {
     let config_exe = app_dir.join("config.exe");
     File::create(&config_exe).unwrap();
 
-    let result = find_software_best_exe(&app_dir, None, temp_dir.path(), 0.0, true);
+    let result = find_software_best_exe(
+        &app_dir,
+        None,
+        &IgnoreRules::new(temp_dir.path(), None),
+        0.0,
+        true,
+    );
 
     if result.is_some() {
         let (_, found_path) = result.unwrap();
@@ -450,7 +2293,13 @@ fn test_scoring_app_root_detection() {
     let dll_path = install_dir.join("myapp.dll");
     File::create(&dll_path).unwrap();
 
-    let result = find_software_best_exe(&install_dir, None, temp_dir.path(), 0.0, true);
+    let result = find_software_best_exe(
+        &install_dir,
+        None,
+        &IgnoreRules::new(temp_dir.path(), None),
+        0.0,
+        true,
+    );
 
     // 应该能找到 exe
     if result.is_some() {
@@ -474,7 +2323,13 @@ fn test_scoring_special_characters() {
     let exe_path = app_dir.join("MyApp_2024.exe");
     File::create(&exe_path).unwrap();
 
-    let result = find_software_best_exe(&app_dir, None, temp_dir.path(), 0.0, true);
+    let result = find_software_best_exe(
+        &app_dir,
+        None,
+        &IgnoreRules::new(temp_dir.path(), None),
+        0.0,
+        true,
+    );
 
     // 应该能处理特殊字符
     if result.is_some() {
@@ -1,21 +1,29 @@
 use aho_corasick::AhoCorasick;
+use crate::config::Arch;
+use crate::icon::read_icon_groups;
+use crate::version_info::VersionInfo;
 use anyhow::{anyhow, bail, Result};
 use goblin::pe::options::ParseOptions;
 use goblin::pe::subsystem::IMAGE_SUBSYSTEM_WINDOWS_GUI;
 use goblin::pe::PE;
 use memmap2::Mmap;
 use std::collections::HashMap;
-use std::ffi::{c_void, OsStr, OsString};
-use std::fs::File;
+use std::ffi::{c_void, OsString};
+use std::fs::{read_dir, File, Metadata, OpenOptions};
 use std::io::ErrorKind;
 use std::option::Option;
-use std::os::windows::ffi::{OsStrExt, OsStringExt};
-use std::path::{Path, PathBuf};
+use std::os::windows::ffi::OsStringExt;
+use std::os::windows::fs::{MetadataExt, OpenOptionsExt};
+use std::os::windows::io::AsRawHandle;
+use std::path::{Component, Path, PathBuf, Prefix};
+use std::sync::{Arc, Mutex};
 use std::{env, ptr, slice};
 use windows::core::{Interface, BOOL, GUID, HSTRING, PCWSTR, PWSTR};
-use windows::Win32::Foundation::{CloseHandle, MAX_PATH};
+use windows::Win32::Foundation::{CloseHandle, HANDLE, MAX_PATH};
 use windows::Win32::Storage::FileSystem::{
-    GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW, VS_FIXEDFILEINFO,
+    GetDriveTypeW, GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION, FILE_ATTRIBUTE_OFFLINE,
+    FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS, FILE_ATTRIBUTE_RECALL_ON_OPEN,
+    FILE_FLAG_BACKUP_SEMANTICS,
 };
 use windows::Win32::System::Com::{
     CoCreateInstance, CoInitializeEx, CoTaskMemFree, CoUninitialize,
@@ -24,10 +32,13 @@ use windows::Win32::System::Com::{
 use windows::Win32::System::Diagnostics::ToolHelp::{
     CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
 };
-use windows::Win32::System::SystemInformation::{GetNativeSystemInfo, SYSTEM_INFO};
+use windows::Win32::System::SystemInformation::{
+    GetNativeSystemInfo, PROCESSOR_ARCHITECTURE_AMD64, PROCESSOR_ARCHITECTURE_ARM64,
+    PROCESSOR_ARCHITECTURE_INTEL, SYSTEM_INFO,
+};
 use windows::Win32::System::Threading::{GetCurrentProcess, GetCurrentProcessId, IsWow64Process};
 use windows::Win32::UI::Shell::{
-    ExtractIconExW, FOLDERID_Desktop, FOLDERID_Documents, FOLDERID_Downloads, FOLDERID_Favorites,
+    FOLDERID_Desktop, FOLDERID_Documents, FOLDERID_Downloads, FOLDERID_Favorites,
     FOLDERID_Music, FOLDERID_Pictures, FOLDERID_ProgramFilesX86, FOLDERID_Programs,
     FOLDERID_PublicDesktop, FOLDERID_PublicDocuments, FOLDERID_PublicDownloads,
     FOLDERID_PublicMusic, FOLDERID_PublicPictures, FOLDERID_PublicVideos, FOLDERID_QuickLaunch,
@@ -596,6 +607,93 @@ pub fn replace_ignore_case(input: &str, pattern: &str, value: &str) -> String {
     out
 }
 
+/// 获取文件或目录的唯一标识（卷序列号、文件索引）
+///
+/// 通过符号链接、目录联接访问同一目录时标识相同，可用于检测循环
+///
+/// # 参数
+/// - `path`: 文件或目录路径
+///
+/// # 返回值
+/// - `Ok((u32, u64))`: 卷序列号、文件索引
+/// - `Err(...)`：打开文件或查询失败
+pub fn get_file_id(path: impl AsRef<Path>) -> Result<(u32, u64)> {
+    // 仅查询属性，不读取内容；目录需要 FILE_FLAG_BACKUP_SEMANTICS
+    let file = OpenOptions::new()
+        .access_mode(0)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS.0)
+        .open(path)?;
+
+    let mut info = BY_HANDLE_FILE_INFORMATION::default();
+    unsafe { GetFileInformationByHandle(HANDLE(file.as_raw_handle()), &mut info)? };
+
+    Ok((
+        info.dwVolumeSerialNumber,
+        ((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64,
+    ))
+}
+
+/// 判断文件是否为云文件占位符（如 OneDrive 仅联机可用的文件）
+///
+/// 读取占位符会触发下载或访问失败，扫描时应跳过
+///
+/// # 参数
+/// - `metadata`: 文件元数据
+pub fn is_cloud_placeholder(metadata: &Metadata) -> bool {
+    let attributes = metadata.file_attributes();
+    attributes
+        & (FILE_ATTRIBUTE_OFFLINE.0
+            | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS.0
+            | FILE_ATTRIBUTE_RECALL_ON_OPEN.0)
+        != 0
+}
+
+/// 判断文件是否位于可移动磁盘、网络位置或光盘上
+///
+/// # 参数
+/// - `path`: 文件路径
+pub fn is_removable_or_network(path: &Path) -> bool {
+    // GetDriveTypeW 的返回值
+    const DRIVE_REMOVABLE: u32 = 2;
+    const DRIVE_REMOTE: u32 = 4;
+    const DRIVE_CDROM: u32 = 5;
+
+    let root = match path.components().next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => {
+                format!("{}:\\", letter as char)
+            }
+            // UNC 路径
+            Prefix::UNC(..) | Prefix::VerbatimUNC(..) => return true,
+            _ => return false,
+        },
+        _ => return false,
+    };
+    let drive_type = unsafe { GetDriveTypeW(&HSTRING::from(root)) };
+    matches!(drive_type, DRIVE_REMOVABLE | DRIVE_REMOTE | DRIVE_CDROM)
+}
+
+/// 获取链接解析后的真实路径
+///
+/// # 参数
+/// - `path`: 文件路径
+///
+/// # 返回值
+/// - 解析符号链接、目录联接后的路径（去除 `\\?\` 前缀），解析失败时返回原路径
+pub fn resolve_link_path(path: &Path) -> PathBuf {
+    let Ok(resolved) = path.canonicalize() else {
+        return path.to_path_buf();
+    };
+    let resolved = resolved.to_string_lossy();
+    if let Some(unc) = resolved.strip_prefix(r"\\?\UNC\") {
+        PathBuf::from(format!(r"\\{}", unc))
+    } else if let Some(local) = resolved.strip_prefix(r"\\?\") {
+        PathBuf::from(local)
+    } else {
+        PathBuf::from(resolved.as_ref())
+    }
+}
+
 /// 判断是否运行在64位系统中
 ///
 /// # 返回值
@@ -728,126 +826,39 @@ pub fn normalize_app_name(name: &str) -> String {
         .replace(".exe", "") // 移除扩展名
 }
 
-/// 读取文件的 version resource 到 Vec<u8>
-fn get_version_info_data(path: &Path) -> Result<Vec<u8>> {
-    // Convert file path to wide string (UTF-16) null-terminated
-    let wide_path = HSTRING::from(path);
-
-    // Get size of version info
-    let mut dummy: u32 = 0;
-    let size = unsafe { GetFileVersionInfoSizeW(PCWSTR(wide_path.as_ptr()), Some(&mut dummy)) };
-    if size == 0 {
-        return Err(std::io::Error::last_os_error().into());
-    }
-
-    // Allocate buffer for version info
-    let mut data: Vec<u8> = vec![0; size as usize];
-    unsafe {
-        GetFileVersionInfoW(
-            PCWSTR(wide_path.as_ptr()),
-            None,
-            size,
-            data.as_mut_ptr() as *mut _,
-        )?;
-    }
-
-    Ok(data)
-}
-
-/// 在 version resource data 中查询指定的字符串字段（例如 "FileDescription", "ProductName", "OriginalFilename"）
-fn query_string_from_version(data: &[u8], field: &str) -> Result<Option<String>> {
-    // 先查询 Translation 列表 (\VarFileInfo\Translation)，取第一个 lang/codepage
-    let trans_key: Vec<u16> = OsStr::new("\\VarFileInfo\\Translation")
-        .encode_wide()
-        .chain(Some(0))
-        .collect();
-
-    let mut trans_ptr: *mut core::ffi::c_void = std::ptr::null_mut();
-    let mut trans_len: u32 = 0;
-
-    let ok = unsafe {
-        VerQueryValueW(
-            data.as_ptr() as *const _,
-            PCWSTR(trans_key.as_ptr()),
-            &mut trans_ptr,
-            &mut trans_len,
-        )
-    };
-
-    if !ok.as_bool() || trans_ptr.is_null() || trans_len < 4 {
-        // 没有 Translation 信息，返回 None
-        return Ok(None);
-    }
-
-    // trans_ptr 指向 WORD[2] 或多个，取第一个（word = u16）
-    let trans_words =
-        unsafe { std::slice::from_raw_parts(trans_ptr as *const u16, (trans_len / 2) as usize) };
-    if trans_words.len() < 2 {
+/// 查询程序版本信息中的字符串字段（例如 "FileDescription", "ProductName", "OriginalFilename"）
+///
+/// 按当前界面语言选择字符串表，没有该字段时依次查找其他语言的字符串表
+fn query_string_from_version(path: &Path, field: &str) -> Result<Option<String>> {
+    let Some(info) = VersionInfo::read(path)? else {
         return Ok(None);
-    }
-    let lang_id = trans_words[0];
-    let codepage = trans_words[1];
-
-    // 构造查询字符串，例如: \StringFileInfo\040904E4\FileDescription
-    let subblock = format!(
-        "\\StringFileInfo\\{:04x}{:04x}\\{}",
-        lang_id, codepage, field
-    );
-    let wide_subblock = HSTRING::from(subblock);
-
-    let mut value_ptr: *mut core::ffi::c_void = std::ptr::null_mut();
-    let mut value_len: u32 = 0;
-
-    let ok2 = unsafe {
-        VerQueryValueW(
-            data.as_ptr() as *const _,
-            PCWSTR(wide_subblock.as_ptr()),
-            &mut value_ptr,
-            &mut value_len,
-        )
     };
-
-    if ok2.as_bool() && !value_ptr.is_null() && value_len > 0 {
-        // value_len 是宽字符数（u16），把它转成 Rust String
-        let slice =
-            unsafe { std::slice::from_raw_parts(value_ptr as *const u16, value_len as usize) };
-        // 寻找 null 终止符（有时 value_len 包含末尾的 0，有时不包含）
-        let end = slice.iter().position(|&c| c == 0).unwrap_or(slice.len());
-        let s = String::from_utf16(&slice[..end])?;
-        return Ok(Some(s));
-    }
-
-    Ok(None)
+    Ok(info.query(field).map(str::to_string))
 }
 
 /// 获取exe产品名称
 pub fn get_exe_product_name(path: &Path) -> Result<Option<String>> {
-    let data = get_version_info_data(path)?;
-    query_string_from_version(&data, "ProductName")
+    query_string_from_version(path, "ProductName")
 }
 
 /// 获取程序描述
 pub fn get_exe_description(path: &Path) -> Result<Option<String>> {
-    let data = get_version_info_data(path)?;
-    query_string_from_version(&data, "FileDescription")
+    query_string_from_version(path, "FileDescription")
 }
 
 /// 获取exe公司名称
 pub fn get_exe_company_name(path: &Path) -> Result<Option<String>> {
-    let data = get_version_info_data(path)?;
-    query_string_from_version(&data, "CompanyName")
+    query_string_from_version(path, "CompanyName")
 }
 
 /// 获取exe版权信息
 pub fn get_exe_copyright(path: &Path) -> Result<Option<String>> {
-    let data = get_version_info_data(path)?;
-    query_string_from_version(&data, "LegalCopyright")
+    query_string_from_version(path, "LegalCopyright")
 }
 
 /// 获取exe原始文件名
 pub fn get_exe_original_filename(path: &Path) -> Result<Option<String>> {
-    let data = get_version_info_data(path)?;
-    query_string_from_version(&data, "OriginalFilename")
+    query_string_from_version(path, "OriginalFilename")
 }
 
 /// 获取exe产品版本
@@ -864,13 +875,12 @@ pub fn get_exe_original_filename(path: &Path) -> Result<Option<String>> {
 /// - 此函数通过查询 PE 文件的版本资源，获取产品版本信息。
 /// - 版本信息通常包含在文件的资源部分，格式为 "Major.Minor.Patch.Build"。
 pub fn get_exe_product_version(path: &Path) -> Result<Option<String>> {
-    let data = get_version_info_data(path)?;
-    query_string_from_version(&data, "ProductVersion")
+    query_string_from_version(path, "ProductVersion")
 }
 
 /// 获取EXE程序的文件版本。
 ///
-/// 此函数通过 Windows API 查询 PE 文件的版本信息，提取其中的数字版本号。
+/// 此函数解析 PE 文件的版本资源，提取其中的数字版本号（VS_FIXEDFILEINFO）。
 ///
 /// # 参数:
 /// - `path`: 可执行文件的路径。
@@ -880,85 +890,10 @@ pub fn get_exe_product_version(path: &Path) -> Result<Option<String>> {
 /// - `Ok(None)`: 如果文件没有版本信息，或者无法获取。
 /// - `Err(error)`: 如果在读取或解析过程中发生错误。
 pub fn get_exe_file_version(path: &Path) -> Result<Option<String>> {
-    // 将 Rust Path 转换为 Windows API 所需的宽字符串 (UTF-16)
-    let path_wide = HSTRING::from(path);
-
-    // 获取版本信息块的大小
-    let mut handle = 0u32;
-    let info_size =
-        unsafe { GetFileVersionInfoSizeW(PCWSTR(path_wide.as_ptr()), Some(&mut handle)) };
-
-    if info_size == 0 {
-        // 文件没有版本信息，或者文件不存在/无法访问
-        return Ok(None);
-    }
-
-    // 分配缓冲区来存储版本信息
-    let mut info_buffer = vec![0u8; info_size as usize];
-
-    // 获取版本信息到缓冲区
-    if unsafe {
-        GetFileVersionInfoW(
-            PCWSTR(path_wide.as_ptr()),
-            Some(handle),
-            info_size,
-            info_buffer.as_mut_ptr() as *mut std::ffi::c_void,
-        )
-    }
-    .is_err()
-    {
-        // 无法获取版本信息
+    let Some(info) = VersionInfo::read(path)? else {
         return Ok(None);
-    }
-
-    // 查询固定文件信息结构体 (VS_FIXEDFILEINFO)，它位于版本信息块的根路径 "\\"
-    let mut value_ptr = std::ptr::null_mut();
-    let mut value_len = 0u32;
-
-    // 查询字符串为 "\\"，表示根信息
-    let query_wide: Vec<u16> = OsStr::new("\\")
-        .encode_wide()
-        .chain(std::iter::once(0))
-        .collect();
-
-    let success_query = unsafe {
-        VerQueryValueW(
-            info_buffer.as_ptr() as *mut std::ffi::c_void,
-            PCWSTR(query_wide.as_ptr()),
-            &mut value_ptr,
-            &mut value_len,
-        )
     };
-
-    if success_query == BOOL(0) || value_len == 0 {
-        // 未能查询到固定文件信息
-        return Ok(None);
-    }
-
-    // 将获取到的指针转换为 VS_FIXEDFILEINFO 结构体
-    // 确保返回的长度足够容纳 VS_FIXEDFILEINFO 结构体
-    if value_len < std::mem::size_of::<VS_FIXEDFILEINFO>() as u32 {
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            "Returned data too small for VS_FIXEDFILEINFO",
-        )
-        .into());
-    }
-
-    let fixed_file_info = unsafe { *(value_ptr as *const VS_FIXEDFILEINFO) };
-
-    // 从 VS_FIXEDFILEINFO 结构体中提取版本组件
-    // dwFileVersionMS 包含主版本和次版本 (高 16 位是主版本，低 16 位是次版本)
-    let major = (fixed_file_info.dwFileVersionMS >> 16) as u16;
-    let minor = (fixed_file_info.dwFileVersionMS & 0xFFFF) as u16;
-    // dwFileVersionLS 包含修订版本和构建版本 (高 16 位是修订版本，低 16 位是构建版本)
-    let patch = (fixed_file_info.dwFileVersionLS >> 16) as u16;
-    let build = (fixed_file_info.dwFileVersionLS & 0xFFFF) as u16;
-
-    // 格式化版本字符串
-    let version_string = format!("{}.{}.{}.{}", major, minor, patch, build);
-
-    Ok(Some(version_string))
+    Ok(info.fixed.map(|fixed| fixed.file_version_string()))
 }
 
 /// 获取当前系统的处理器架构。
@@ -1070,35 +1005,627 @@ pub fn is_gui_program(program: impl AsRef<Path>) -> Result<bool> {
     }
 }
 
-/// 判断程序是否有数字签名
+/// 安装程序、自解压程序类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InstallerKind {
+    /// Nullsoft Scriptable Install System
+    Nsis,
+    /// Inno Setup
+    InnoSetup,
+    /// InstallShield
+    InstallShield,
+    /// WiX Burn 捆绑安装包
+    WixBurn,
+    /// 7-Zip 自解压程序
+    SevenZipSfx,
+    /// WinRAR 自解压程序
+    RarSfx,
+    /// ZIP 自解压程序
+    ZipSfx,
+    /// Windows Installer 安装包
+    Msi,
+}
+
+impl InstallerKind {
+    /// 安装程序类型名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            InstallerKind::Nsis => "NSIS",
+            InstallerKind::InnoSetup => "Inno Setup",
+            InstallerKind::InstallShield => "InstallShield",
+            InstallerKind::WixBurn => "WiX Burn",
+            InstallerKind::SevenZipSfx => "7-Zip SFX",
+            InstallerKind::RarSfx => "RAR SFX",
+            InstallerKind::ZipSfx => "ZIP SFX",
+            InstallerKind::Msi => "MSI",
+        }
+    }
+}
+
+/// 判断程序是否为安装程序或自解压程序
+///
+/// 依次检查：MSI 文件头、PE 区段名称、版本信息字符串、最后一个区段之后的附加数据（overlay）签名
 ///
 /// # 参数
 /// - `program`: 程序路径
 ///
 /// # 返回值
-/// - `Ok(bool)`: 是否有数字签名
-/// - `Err(...)`：读取或解析失败
-pub fn exe_has_signature(program: impl AsRef<Path>) -> Result<bool> {
+/// - `Ok(Some(InstallerKind))`: 安装程序类型
+/// - `Ok(None)`: 不是安装程序
+/// - `Err(...)`：读取文件失败
+pub fn detect_installer(program: impl AsRef<Path>) -> Result<Option<InstallerKind>> {
+    /// OLE 复合文档签名（MSI）
+    const OLE_SIGNATURE: &[u8] = &[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+    /// overlay 中搜索签名的最大长度
+    const OVERLAY_SCAN_SIZE: usize = 64 * 1024;
+    /// overlay 签名
+    const OVERLAY_SIGNATURES: &[(&[u8], InstallerKind)] = &[
+        (b"NullsoftInst", InstallerKind::Nsis),
+        (b"Inno Setup Setup Data", InstallerKind::InnoSetup),
+        (b"zlb\x1a", InstallerKind::InnoSetup),
+        (b"7z\xBC\xAF\x27\x1C", InstallerKind::SevenZipSfx),
+        (b"Rar!\x1A\x07", InstallerKind::RarSfx),
+    ];
+    /// ZIP 文件头（附加 ZIP 的程序不一定是自解压程序，如 NW.js、LÖVE 应用）
+    const ZIP_SIGNATURE: &[u8] = b"PK\x03\x04";
+    /// ZIP 自解压程序主体的最大长度
+    const ZIP_SFX_STUB_SIZE: usize = 1024 * 1024;
+    /// ZIP 自解压程序主体中的特征字符串（不区分大小写）
+    const ZIP_SFX_MARKERS: &[&[u8]] = &[
+        b"UnZipSFX",
+        b"Info-ZIP",
+        b"Self-Extractor",
+        b"self-extracting",
+    ];
     const IMAGE_DIRECTORY_ENTRY_SECURITY: usize = 4;
 
+    let program = program.as_ref();
     let file = File::open(program)?;
     let mmap = unsafe { Mmap::map(&file)? };
 
-    let pe = PE::parse(&mmap)?;
-    if let Some(optional_header) = pe.header.optional_header.as_ref() {
-        if let Some(entry) = optional_header
+    // MSI 安装包
+    if program
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("msi"))
+        || mmap.starts_with(OLE_SIGNATURE)
+    {
+        return Ok(Some(InstallerKind::Msi));
+    }
+
+    let mut options = ParseOptions::default();
+    options.parse_attribute_certificates = false;
+    options.parse_tls_data = false;
+    let pe = match PE::parse_with_opts(&mmap, &options) {
+        Ok(pe) => pe,
+        Err(_) => return Ok(None),
+    };
+
+    // 区段名称
+    for section in &pe.sections {
+        let name = section.name().unwrap_or_default().to_ascii_lowercase();
+        match name.as_str() {
+            ".ndata" => return Ok(Some(InstallerKind::Nsis)),
+            ".wixburn" => return Ok(Some(InstallerKind::WixBurn)),
+            "_winzip_" => return Ok(Some(InstallerKind::ZipSfx)),
+            _ => {}
+        }
+    }
+
+    // 版本信息字符串
+    if let Ok(Some(info)) = VersionInfo::from_pe(&mmap) {
+        for field in ["FileDescription", "ProductName", "Comments", "InternalName"] {
+            let value = match info.query(field) {
+                Some(v) => v.to_ascii_lowercase(),
+                None => continue,
+            };
+            let kind = if value.contains("nullsoft") {
+                Some(InstallerKind::Nsis)
+            } else if value.contains("inno setup") {
+                Some(InstallerKind::InnoSetup)
+            } else if value.contains("installshield") {
+                Some(InstallerKind::InstallShield)
+            } else if value.contains("7z setup sfx")
+                || value.contains("7-zip sfx")
+                || value.contains("7zsfx")
+            {
+                Some(InstallerKind::SevenZipSfx)
+            } else if value.contains("winrar") && value.contains("sfx") {
+                Some(InstallerKind::RarSfx)
+            } else if value.contains("winzip self-extractor") {
+                Some(InstallerKind::ZipSfx)
+            } else {
+                None
+            };
+            if kind.is_some() {
+                return Ok(kind);
+            }
+        }
+    }
+
+    // 附加数据：最后一个区段之后、数字签名之前的数据
+    let overlay_start = pe
+        .sections
+        .iter()
+        .map(|s| s.pointer_to_raw_data as usize + s.size_of_raw_data as usize)
+        .max()
+        .unwrap_or(0);
+    let mut overlay_end = mmap.len();
+    if let Some(Some((_, security))) = pe.header.optional_header.as_ref().and_then(|header| {
+        header
             .data_directories
             .data_directories
             .get(IMAGE_DIRECTORY_ENTRY_SECURITY)
+    }) {
+        // 数字签名目录中的地址为文件偏移
+        let security_start = security.virtual_address as usize;
+        if security.size > 0 && security_start >= overlay_start {
+            overlay_end = overlay_end.min(security_start);
+        }
+    }
+    if overlay_start == 0 || overlay_start >= overlay_end {
+        return Ok(None);
+    }
+    let overlay = &mmap[overlay_start..overlay_end.min(overlay_start + OVERLAY_SCAN_SIZE)];
+    for (signature, kind) in OVERLAY_SIGNATURES {
+        if overlay
+            .windows(signature.len())
+            .any(|window| window == *signature)
+        {
+            return Ok(Some(*kind));
+        }
+    }
+
+    // 附加 ZIP 且程序主体为自解压程序
+    if overlay
+        .windows(ZIP_SIGNATURE.len())
+        .any(|window| window == ZIP_SIGNATURE)
+        && overlay_start <= ZIP_SFX_STUB_SIZE
+    {
+        let stub = &mmap[..overlay_start];
+        if ZIP_SFX_MARKERS.iter().any(|marker| {
+            stub.windows(marker.len())
+                .any(|window| window.eq_ignore_ascii_case(marker))
+        }) {
+            return Ok(Some(InstallerKind::ZipSfx));
+        }
+    }
+
+    Ok(None)
+}
+
+/// 程序运行时类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AppRuntime {
+    /// 原生程序
+    Native,
+    /// Electron 应用
+    Electron,
+    /// .NET 应用
+    DotNet,
+    /// Java 应用（附带 Java 运行时）
+    Java,
+}
+
+impl AppRuntime {
+    /// 运行时类型名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            AppRuntime::Native => "native",
+            AppRuntime::Electron => "electron",
+            AppRuntime::DotNet => "dotnet",
+            AppRuntime::Java => "java",
+        }
+    }
+}
+
+/// Electron 应用附带的辅助程序（不含扩展名）
+const ELECTRON_HELPERS: &[&str] = &[
+    "update",
+    "squirrel",
+    "chrome_crashpad_handler",
+    "crashpad_handler",
+    "elevate",
+    "notification_helper",
+];
+
+/// .NET 应用附带的辅助程序（不含扩展名）
+const DOTNET_HELPERS: &[&str] = &["createdump"];
+
+/// 向上查找 Java 运行时目录的最大层数
+const JAVA_RUNTIME_DEPTH: usize = 3;
+
+/// 判断目录名是否为 Java 运行时目录（`jre`、`jdk`、`jre1.8.0_301` 等）
+fn is_java_runtime_dir_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.starts_with("jre") || name.starts_with("jdk")
+}
+
+/// 判断程序是否位于 Java 运行时目录中
+fn is_in_java_runtime(program: &Path) -> bool {
+    program
+        .ancestors()
+        .skip(1)
+        .take(JAVA_RUNTIME_DEPTH)
+        .filter_map(|dir| dir.file_name())
+        .any(|name| is_java_runtime_dir_name(&name.to_string_lossy()))
+}
+
+/// 识别程序的运行时类型
+///
+/// 依次检查：Electron 资源包（`resources\app.asar`，Squirrel 安装布局中为 `app-*\resources\app.asar`）、
+/// .NET 运行时配置（`*.runtimeconfig.json`）与 CLR 头、附带的 Java 运行时目录（`jre\`）
+///
+/// # 参数
+/// - `program`: 程序路径
+///
+/// # 返回值
+/// - `AppRuntime`: 运行时类型，无法识别时为 `Native`
+pub fn detect_runtime(program: &Path) -> AppRuntime {
+    /// CLR 运行时头（IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR）
+    const IMAGE_DIRECTORY_ENTRY_CLR: usize = 14;
+
+    let Some(dir) = program.parent() else {
+        return AppRuntime::Native;
+    };
+
+    // Electron：resources\app.asar 或 resources\app\package.json
+    if is_electron_dir(dir) {
+        return AppRuntime::Electron;
+    }
+    // Squirrel 安装布局：根目录中为 Update.exe 与启动程序，应用位于 app-x.y.z\ 中
+    if let Ok(entries) = read_dir(dir) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if entry
+                .file_name()
+                .to_string_lossy()
+                .to_ascii_lowercase()
+                .starts_with("app-")
+                && path.is_dir()
+                && is_electron_dir(&path)
+            {
+                return AppRuntime::Electron;
+            }
+        }
+    }
+
+    // .NET：依赖框架的 apphost 附带 runtimeconfig.json，.NET Framework 程序带有 CLR 头
+    let stem = program.file_stem().unwrap_or_default().to_string_lossy();
+    if dir.join(format!("{}.runtimeconfig.json", stem)).is_file() {
+        return AppRuntime::DotNet;
+    }
+    if let Ok(file) = File::open(program)
+        && let Ok(mmap) = unsafe { Mmap::map(&file) }
+    {
+        let mut options = ParseOptions::default();
+        options.parse_attribute_certificates = false;
+        options.parse_tls_data = false;
+        if let Ok(pe) = PE::parse_with_opts(&mmap, &options)
+            && let Some(Some((_, clr))) = pe.header.optional_header.as_ref().and_then(|header| {
+                header
+                    .data_directories
+                    .data_directories
+                    .get(IMAGE_DIRECTORY_ENTRY_CLR)
+            })
+            && clr.size > 0
         {
-            // entry: &Option<(usize, DataDirectory)>
-            if let Some((_rva, data_dir)) = entry.as_ref() {
-                return Ok(data_dir.size > 0);
+            return AppRuntime::DotNet;
+        }
+    }
+
+    // Java：程序位于 Java 运行时中，或所在目录附带 Java 运行时
+    if is_in_java_runtime(program) {
+        return AppRuntime::Java;
+    }
+    if let Ok(entries) = read_dir(dir) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.is_dir()
+                && is_java_runtime_dir_name(&entry.file_name().to_string_lossy())
+                && ["java.exe", "javaw.exe"]
+                    .iter()
+                    .any(|exe| path.join("bin").join(exe).is_file())
+            {
+                return AppRuntime::Java;
             }
         }
     }
 
-    Ok(false)
+    AppRuntime::Native
+}
+
+/// 判断目录是否为 Electron 应用目录（包含 `resources\app.asar` 或 `resources\app\package.json`）
+fn is_electron_dir(dir: &Path) -> bool {
+    let resources = dir.join("resources");
+    resources.join("app.asar").is_file() || resources.join("app").join("package.json").is_file()
+}
+
+/// 判断程序是否为运行时附带的辅助程序（如 Electron 的 `Update.exe`、Java 运行时中的 `java.exe`）
+///
+/// # 参数
+/// - `program`: 程序路径
+/// - `runtime`: 程序的运行时类型
+pub fn is_runtime_helper(program: &Path, runtime: AppRuntime) -> bool {
+    let stem = program
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_ascii_lowercase();
+    match runtime {
+        AppRuntime::Native => false,
+        AppRuntime::Electron => ELECTRON_HELPERS.contains(&stem.as_str()),
+        AppRuntime::DotNet => DOTNET_HELPERS.contains(&stem.as_str()),
+        AppRuntime::Java => is_in_java_runtime(program),
+    }
+}
+
+/// 获取 .NET apphost 对应的主程序集（同目录下的同名 dll）
+///
+/// # 参数
+/// - `program`: 程序路径
+pub fn get_dotnet_main_assembly(program: &Path) -> Option<PathBuf> {
+    Some(program.with_extension("dll")).filter(|dll| dll.is_file())
+}
+
+/// 架构变体所在的目录名（不区分大小写）
+const ARCH_DIR_NAMES: &[&str] = &[
+    "x86", "x32", "x64", "win32", "win64", "amd64", "arm64", "32bit", "64bit", "bin32", "bin64",
+];
+
+/// 架构变体的名称后缀（先匹配较长的后缀）
+const ARCH_SUFFIXES: &[&str] = &[
+    "arm64", "amd64", "win64", "win32", "x64", "x86", "x32", "64", "32",
+];
+
+/// 系统架构
+pub fn get_native_arch_kind() -> Option<Arch> {
+    match get_native_arch() {
+        arch if arch == PROCESSOR_ARCHITECTURE_INTEL.0 => Some(Arch::X86),
+        arch if arch == PROCESSOR_ARCHITECTURE_AMD64.0 => Some(Arch::X64),
+        arch if arch == PROCESSOR_ARCHITECTURE_ARM64.0 => Some(Arch::Arm64),
+        _ => None,
+    }
+}
+
+/// 架构优先顺序：首选架构，然后是系统可以运行的架构（原生架构优先）
+///
+/// # 参数
+/// - `prefer`: 首选架构
+pub fn arch_preference(prefer: Option<Arch>) -> Vec<Arch> {
+    let native = match get_native_arch_kind() {
+        Some(Arch::Arm64) => vec![Arch::Arm64, Arch::X64, Arch::X86],
+        Some(Arch::X86) => vec![Arch::X86],
+        _ => vec![Arch::X64, Arch::X86],
+    };
+    let mut order: Vec<Arch> = prefer.into_iter().collect();
+    order.extend(native.into_iter().filter(|arch| Some(*arch) != prefer));
+    order
+}
+
+/// 获取架构变体的比较名称（去除架构后缀，如 `PotPlayerMini64` -> `potplayermini`）
+///
+/// # 参数
+/// - `stem`: 程序文件名（不含扩展名）
+pub fn arch_variant_key(stem: &str) -> String {
+    let name = normalize_app_name(stem);
+    match arch_suffix(&name) {
+        Some(suffix) => name[..name.len() - suffix.len()].to_string(),
+        None => name,
+    }
+}
+
+/// 获取名称中的架构后缀（名称仅为架构后缀时除外）
+///
+/// # 参数
+/// - `name`: 规范化后的程序名称
+fn arch_suffix(name: &str) -> Option<&'static str> {
+    ARCH_SUFFIXES
+        .iter()
+        .find(|suffix| name.len() > suffix.len() && name.ends_with(*suffix))
+        .copied()
+}
+
+/// 判断架构后缀与程序的实际架构是否一致（如 64 位的 `crc32.exe` 不是 32 位变体）
+fn arch_suffix_matches(suffix: &str, arch: Arch) -> bool {
+    match suffix {
+        "arm64" => arch == Arch::Arm64,
+        "64" => arch != Arch::X86,
+        "amd64" | "win64" | "x64" => arch == Arch::X64,
+        _ => arch == Arch::X86,
+    }
+}
+
+/// 判断目录是否为架构变体目录（如 `x86`、`x64`）
+fn is_arch_dir(dir: &Path) -> bool {
+    dir.file_name().is_some_and(|name| {
+        ARCH_DIR_NAMES
+            .iter()
+            .any(|arch_dir| name.to_string_lossy().eq_ignore_ascii_case(arch_dir))
+    })
+}
+
+/// 架构变体分组
+type ArchVariantGroup = Vec<(PathBuf, Arch)>;
+
+/// 获取程序所属架构变体分组的目录（架构变体目录中的程序为上级目录）
+///
+/// # 参数
+/// - `program`: 程序路径
+fn arch_group_dir(program: &Path) -> Option<&Path> {
+    let parent = program.parent()?;
+    if is_arch_dir(parent) {
+        parent.parent().or(Some(parent))
+    } else {
+        Some(parent)
+    }
+}
+
+/// 读取目录中的全部架构变体分组
+///
+/// 在目录，以及其中的架构变体目录（`x86\`、`x64\` 等）中，按比较名称、版本分组；
+/// 仅读取比较名称相同的程序的版本与架构
+///
+/// # 参数
+/// - `group_dir`: 目录路径
+///
+/// # 返回值
+/// - `Vec<ArchVariantGroup>`: 每组中每种架构仅有一个程序，且至少有两种架构
+fn read_arch_variant_groups(group_dir: &Path) -> Vec<ArchVariantGroup> {
+    // 候选目录：所在目录（或上级目录）及其中的架构变体目录
+    let mut dirs = vec![group_dir.to_path_buf()];
+    if let Ok(entries) = read_dir(group_dir) {
+        dirs.extend(
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|path| path.is_dir() && is_arch_dir(path)),
+        );
+    }
+
+    // 按比较名称分组
+    let mut by_key: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for dir in dirs {
+        let Ok(entries) = read_dir(&dir) else {
+            continue;
+        };
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            if !path.is_file()
+                || !path
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("exe"))
+            {
+                continue;
+            }
+            let key = arch_variant_key(&path.file_stem().unwrap_or_default().to_string_lossy());
+            by_key.entry(key).or_default().push(path);
+        }
+    }
+
+    let mut groups = Vec::new();
+    for paths in by_key.into_values().filter(|paths| paths.len() > 1) {
+        // 按版本分组
+        let mut by_version: Vec<(Option<String>, ArchVariantGroup)> = Vec::new();
+        for path in paths {
+            let version = get_exe_file_version(&path).ok().flatten();
+            let Some(arch) = get_program_arch(&path).ok().and_then(Arch::from_machine) else {
+                continue;
+            };
+            // 名称中的架构后缀与机器类型不一致时，只是名称相近的其它程序
+            let name = normalize_app_name(&path.file_stem().unwrap_or_default().to_string_lossy());
+            if arch_suffix(&name).is_some_and(|suffix| !arch_suffix_matches(suffix, arch)) {
+                continue;
+            }
+            match by_version.iter_mut().find(|(v, _)| *v == version) {
+                Some((_, variants)) => variants.push((path, arch)),
+                None => by_version.push((version, vec![(path, arch)])),
+            }
+        }
+
+        // 每种架构仅有一个程序，且至少两种架构（机器类型确实不同）时才视为架构变体
+        for (_, variants) in by_version {
+            let mut arches: Vec<Arch> = variants.iter().map(|(_, arch)| *arch).collect();
+            arches.sort_by_key(|arch| arch.label());
+            arches.dedup();
+            if arches.len() >= 2 && arches.len() == variants.len() {
+                groups.push(variants);
+            }
+        }
+    }
+    groups
+}
+
+/// 目录中的架构变体（按目录缓存，每个目录只读取一次）
+#[derive(Default)]
+pub struct ArchVariants {
+    /// 架构变体分组缓存
+    cache: Mutex<HashMap<PathBuf, Arc<Vec<ArchVariantGroup>>>>,
+}
+
+impl ArchVariants {
+    /// 创建架构变体缓存
+    pub fn new() -> Self {
+        ArchVariants::default()
+    }
+
+    /// 查找程序的架构变体（包括程序自身）
+    ///
+    /// 在程序所在目录，以及同级的架构变体目录（`x86\`、`x64\` 等）中，查找比较名称、版本相同的程序
+    ///
+    /// # 参数
+    /// - `program`: 程序路径
+    ///
+    /// # 返回值
+    /// - `Vec<(程序路径, 架构)>`: 不同架构的变体，少于两种架构时为空
+    pub fn find(&self, program: &Path) -> Vec<(PathBuf, Arch)> {
+        let Some(group_dir) = arch_group_dir(program) else {
+            return Vec::new();
+        };
+        let groups = {
+            let mut cache = self.cache.lock().unwrap();
+            match cache.get(group_dir) {
+                Some(groups) => Arc::clone(groups),
+                None => {
+                    let groups = Arc::new(read_arch_variant_groups(group_dir));
+                    cache.insert(group_dir.to_path_buf(), Arc::clone(&groups));
+                    groups
+                }
+            }
+        };
+        groups
+            .iter()
+            .find(|group| group.iter().any(|(path, _)| path == program))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// 判断程序作为架构变体的处理方式
+    ///
+    /// # 参数
+    /// - `program`: 程序路径
+    /// - `prefer`: 首选架构（默认为系统架构）
+    /// - `keep_both`: 保留全部变体
+    pub fn choose(&self, program: &Path, prefer: Option<Arch>, keep_both: bool) -> ArchVariant {
+        let variants = self.find(program);
+        let Some(arch) = variants
+            .iter()
+            .find(|(path, _)| path == program)
+            .map(|(_, arch)| *arch)
+        else {
+            return ArchVariant::Keep;
+        };
+        if keep_both {
+            return ArchVariant::Suffix(arch);
+        }
+
+        let preferred = arch_preference(prefer)
+            .into_iter()
+            .find_map(|arch| variants.iter().find(|(_, variant)| *variant == arch));
+        match preferred {
+            Some((path, _)) if path != program => ArchVariant::Skip(path.clone()),
+            _ => ArchVariant::Keep,
+        }
+    }
+}
+
+/// 架构变体的处理方式
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArchVariant {
+    /// 不是架构变体，或为首选架构
+    Keep,
+    /// 跳过，已为首选架构的变体创建快捷方式
+    Skip(PathBuf),
+    /// 保留全部变体，快捷方式名称追加架构后缀
+    Suffix(Arch),
+}
+
+impl ArchVariant {
+    /// 快捷方式名称后缀
+    pub fn suffix(&self) -> Option<String> {
+        match self {
+            ArchVariant::Suffix(arch) => Some(arch.label().to_string()),
+            _ => None,
+        }
+    }
 }
 
 /// 判断程序是否有图标
@@ -1109,14 +1636,8 @@ pub fn exe_has_signature(program: impl AsRef<Path>) -> Result<bool> {
 /// # 返回值
 /// - `bool`: 是否有图标
 pub fn has_icon_in_program(program: &Path) -> bool {
-    // 将 Path 转换为 Windows API 所需的宽字符串 (UTF-16)
-    let wide_path = HSTRING::from(program);
-
-    // 获取文件中包含的图标总数
-    let icon_count = unsafe { ExtractIconExW(PCWSTR(wide_path.as_ptr()), -1, None, None, 0) };
-
-    // 如果返回的图标数量大于 0，则表示存在图标
-    icon_count > 0
+    // 读取程序资源中的图标组
+    read_icon_groups(program).is_ok_and(|groups| !groups.is_empty())
 }
 
 /// 从字符串解析图标路径、图标索引
@@ -0,0 +1,396 @@
+use crate::pe_resource::{find_resources, read_u16, read_u32, RT_VERSION};
+use anyhow::Result;
+use memmap2::Mmap;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+
+/// `VS_FIXEDFILEINFO` 的签名
+const FIXED_FILE_INFO_SIGNATURE: u32 = 0xFEEF_04BD;
+
+/// 英语（美国）语言标识
+pub const LANG_EN_US: u16 = 0x0409;
+
+/// 语言名称与语言标识
+const LANG_TAGS: &[(&str, u16)] = &[
+    ("en", 0x0409),
+    ("en-US", 0x0409),
+    ("en-GB", 0x0809),
+    ("zh-CN", 0x0804),
+    ("zh-Hans", 0x0804),
+    ("zh-TW", 0x0404),
+    ("zh-Hant", 0x0404),
+    ("zh-HK", 0x0C04),
+    ("ja", 0x0411),
+    ("ko", 0x0412),
+    ("de", 0x0407),
+    ("fr", 0x040C),
+    ("es", 0x0C0A),
+    ("it", 0x0410),
+    ("pt-BR", 0x0416),
+    ("pt", 0x0816),
+    ("ru", 0x0419),
+];
+
+/// 固定文件信息（`VS_FIXEDFILEINFO`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FixedFileInfo {
+    /// 文件版本（主版本、次版本、修订版本、构建版本）
+    pub file_version: [u16; 4],
+    /// 产品版本（主版本、次版本、修订版本、构建版本）
+    pub product_version: [u16; 4],
+    /// 文件标志
+    pub file_flags: u32,
+    /// 目标操作系统
+    pub file_os: u32,
+    /// 文件类型
+    pub file_type: u32,
+}
+
+impl FixedFileInfo {
+    /// 文件版本，格式为 "Major.Minor.Patch.Build"
+    pub fn file_version_string(&self) -> String {
+        let [major, minor, patch, build] = self.file_version;
+        format!("{}.{}.{}.{}", major, minor, patch, build)
+    }
+
+    /// 产品版本，格式为 "Major.Minor.Patch.Build"
+    pub fn product_version_string(&self) -> String {
+        let [major, minor, patch, build] = self.product_version;
+        format!("{}.{}.{}.{}", major, minor, patch, build)
+    }
+}
+
+/// 字符串表（`StringTable`），对应一种语言与代码页
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StringTable {
+    /// 语言标识
+    pub lang: u16,
+    /// 代码页
+    pub codepage: u16,
+    /// 字段与值，如 `ProductName`
+    pub strings: BTreeMap<String, String>,
+}
+
+/// 版本信息（`VS_VERSIONINFO`）
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct VersionInfo {
+    /// 固定文件信息
+    pub fixed: Option<FixedFileInfo>,
+    /// 全部字符串表
+    pub tables: Vec<StringTable>,
+    /// `VarFileInfo\Translation` 中的语言与代码页
+    pub translations: Vec<(u16, u16)>,
+}
+
+/// 版本信息中的数据块
+struct Block<'a> {
+    /// 名称
+    key: String,
+    /// 是否为文本值
+    is_text: bool,
+    /// 值
+    value: &'a [u8],
+    /// 子数据块所在的数据
+    children: &'a [u8],
+}
+
+impl<'a> Block<'a> {
+    /// 读取数据块
+    ///
+    /// # 参数
+    /// - `data`: 数据块所在的数据（从 4 字节对齐的位置开始）
+    /// - `offset`: 数据块的偏移
+    ///
+    /// # 返回值
+    /// - `Some((Block, 下一个数据块的偏移))`
+    /// - `None`: 数据不完整
+    fn read(data: &'a [u8], offset: usize) -> Option<(Block<'a>, usize)> {
+        let length = read_u16(data, offset)? as usize;
+        let value_length = read_u16(data, offset + 2)? as usize;
+        let is_text = read_u16(data, offset + 4)? == 1;
+        if length < 6 {
+            return None;
+        }
+        let end = (offset + length).min(data.len());
+
+        // 以 0 结尾的 UTF-16 名称
+        let mut position = offset + 6;
+        let mut key = Vec::new();
+        while position + 2 <= end {
+            let unit = read_u16(data, position)?;
+            position += 2;
+            if unit == 0 {
+                break;
+            }
+            key.push(unit);
+        }
+        let value_start = align(position).min(end);
+
+        // 文本值的长度以字符计，部分程序以字节计，因此字符串值读取到数据块末尾
+        let value_bytes = if is_text {
+            value_length * 2
+        } else {
+            value_length
+        };
+        let value_end = (value_start + value_bytes).min(end);
+        let children_start = align(value_end).min(end);
+        let value = if is_text && value_length > 0 {
+            &data[value_start..end]
+        } else {
+            &data[value_start..value_end]
+        };
+
+        let block = Block {
+            key: String::from_utf16_lossy(&key),
+            is_text,
+            value,
+            children: &data[children_start..end],
+        };
+        Some((block, align(offset + length)))
+    }
+
+    /// 子数据块
+    fn children(&self) -> Vec<Block<'a>> {
+        let mut blocks = Vec::new();
+        let mut offset = 0;
+        while offset < self.children.len() {
+            let Some((block, next)) = Block::read(self.children, offset) else {
+                break;
+            };
+            blocks.push(block);
+            offset = next;
+        }
+        blocks
+    }
+
+    /// 文本值（截断到第一个 0）
+    fn text(&self) -> String {
+        let units: Vec<u16> = self
+            .value
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .take_while(|&unit| unit != 0)
+            .collect();
+        String::from_utf16_lossy(&units)
+    }
+}
+
+/// 按 4 字节对齐
+fn align(offset: usize) -> usize {
+    (offset + 3) & !3
+}
+
+impl VersionInfo {
+    /// 解析版本信息资源（`RT_VERSION`）
+    ///
+    /// # 参数
+    /// - `data`: 资源数据
+    ///
+    /// # 返回值
+    /// - `Some(VersionInfo)`: 解析成功
+    /// - `None`: 不是有效的版本信息
+    pub fn parse(data: &[u8]) -> Option<VersionInfo> {
+        let (root, _) = Block::read(data, 0)?;
+        if root.key != "VS_VERSION_INFO" {
+            return None;
+        }
+
+        let mut info = VersionInfo {
+            fixed: parse_fixed_file_info(root.value),
+            ..Default::default()
+        };
+        for child in root.children() {
+            match child.key.as_str() {
+                "StringFileInfo" => {
+                    for table in child.children() {
+                        // 名称为 8 位十六进制数，如 `040904b0`
+                        let id = u32::from_str_radix(&table.key, 16).unwrap_or(0);
+                        let strings = table
+                            .children()
+                            .iter()
+                            .filter(|string| string.is_text || !string.value.is_empty())
+                            .map(|string| (string.key.clone(), string.text()))
+                            .collect();
+                        info.tables.push(StringTable {
+                            lang: (id >> 16) as u16,
+                            codepage: id as u16,
+                            strings,
+                        });
+                    }
+                }
+                "VarFileInfo" => {
+                    for var in child.children() {
+                        if var.key == "Translation" {
+                            info.translations
+                                .extend(var.value.chunks_exact(4).map(|pair| {
+                                    (
+                                        u16::from_le_bytes([pair[0], pair[1]]),
+                                        u16::from_le_bytes([pair[2], pair[3]]),
+                                    )
+                                }));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Some(info)
+    }
+
+    /// 读取 PE 文件中的版本信息
+    ///
+    /// # 参数
+    /// - `bytes`: PE 文件内容
+    ///
+    /// # 返回值
+    /// - `Ok(Some(VersionInfo))`: 读取成功
+    /// - `Ok(None)`: 没有版本信息
+    /// - `Err(...)`: 不是有效的 PE 文件
+    pub fn from_pe(bytes: &[u8]) -> Result<Option<VersionInfo>> {
+        Ok(find_resources(bytes, RT_VERSION)?
+            .iter()
+            .find_map(|resource| VersionInfo::parse(resource.data)))
+    }
+
+    /// 读取程序的版本信息
+    ///
+    /// # 参数
+    /// - `path`: 程序路径
+    pub fn read(path: &Path) -> Result<Option<VersionInfo>> {
+        let file = File::open(path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+        VersionInfo::from_pe(&mmap)
+    }
+
+    /// 按语言优先级排列字符串表
+    ///
+    /// 优先级：指定语言 > 相同主语言 > `Translation` 中的顺序 > 字符串表的顺序
+    ///
+    /// # 参数
+    /// - `langs`: 优先使用的语言标识
+    pub fn ordered_tables(&self, langs: &[u16]) -> Vec<&StringTable> {
+        let rank = |table: &StringTable| -> usize {
+            if let Some(index) = langs.iter().position(|&lang| lang == table.lang) {
+                return index * 2;
+            }
+            if let Some(index) = langs
+                .iter()
+                .position(|&lang| lang & 0x3FF == table.lang & 0x3FF)
+            {
+                return index * 2 + 1;
+            }
+            let translation = self
+                .translations
+                .iter()
+                .position(|&(lang, codepage)| lang == table.lang && codepage == table.codepage);
+            langs.len() * 2 + translation.unwrap_or(self.translations.len())
+        };
+        let mut tables: Vec<&StringTable> = self.tables.iter().collect();
+        tables.sort_by_key(|table| rank(table));
+        tables
+    }
+
+    /// 按语言优先级查询字段，当前语言的字符串表没有该字段时依次查找其他字符串表
+    ///
+    /// # 参数
+    /// - `field`: 字段名称，如 `ProductName`
+    /// - `langs`: 优先使用的语言标识
+    pub fn query_with(&self, field: &str, langs: &[u16]) -> Option<&str> {
+        self.ordered_tables(langs)
+            .into_iter()
+            .find_map(|table| table.strings.get(field).filter(|value| !value.is_empty()))
+            .map(String::as_str)
+    }
+
+    /// 查询指定语言（或相同主语言）的字符串表中的字段，不查找其他语言
+    ///
+    /// # 参数
+    /// - `field`: 字段名称，如 `ProductName`
+    /// - `lang`: 语言标识
+    pub fn query_lang(&self, field: &str, lang: u16) -> Option<&str> {
+        let find = |matches: &dyn Fn(u16) -> bool| {
+            self.tables
+                .iter()
+                .filter(|table| matches(table.lang))
+                .find_map(|table| table.strings.get(field).filter(|value| !value.is_empty()))
+                .map(String::as_str)
+        };
+        find(&|table_lang| table_lang == lang)
+            .or_else(|| find(&|table_lang| table_lang & 0x3FF == lang & 0x3FF))
+    }
+
+    /// 按系统语言查询字段
+    ///
+    /// # 参数
+    /// - `field`: 字段名称，如 `ProductName`
+    pub fn query(&self, field: &str) -> Option<&str> {
+        self.query_with(field, &preferred_langs())
+    }
+}
+
+/// 解析固定文件信息
+fn parse_fixed_file_info(value: &[u8]) -> Option<FixedFileInfo> {
+    if read_u32(value, 0)? != FIXED_FILE_INFO_SIGNATURE {
+        return None;
+    }
+    let split = |ms: u32, ls: u32| [(ms >> 16) as u16, ms as u16, (ls >> 16) as u16, ls as u16];
+    Some(FixedFileInfo {
+        file_version: split(read_u32(value, 8)?, read_u32(value, 12)?),
+        product_version: split(read_u32(value, 16)?, read_u32(value, 20)?),
+        file_flags: read_u32(value, 28)?,
+        file_os: read_u32(value, 32)?,
+        file_type: read_u32(value, 36)?,
+    })
+}
+
+/// 语言名称对应的语言标识
+///
+/// # 参数
+/// - `tag`: 语言名称，如 `zh-CN`、`ja-JP`，或 4 位十六进制的语言标识，如 `0804`
+///
+/// # 返回值
+/// - `Some(u16)`: 语言标识
+/// - `None`: 无法识别的语言名称
+pub fn lang_id_from_tag(tag: &str) -> Option<u16> {
+    let tag = tag.trim().replace('_', "-");
+    if tag.len() == 4
+        && let Ok(lang) = u16::from_str_radix(&tag, 16)
+    {
+        return Some(lang);
+    }
+    let find = |tag: &str| {
+        LANG_TAGS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(tag))
+            .map(|&(_, lang)| lang)
+    };
+    // 没有完全匹配时只比较语言部分，如 `ja-JP`
+    find(&tag).or_else(|| find(tag.split('-').next()?))
+}
+
+/// 语言标识对应的语言名称，没有对应名称时为 4 位十六进制语言标识，如 `0c0a`
+pub fn lang_tag(lang: u16) -> String {
+    LANG_TAGS
+        .iter()
+        .find(|&&(_, id)| id == lang)
+        .map(|(name, _)| name.to_string())
+        .unwrap_or_else(|| format!("{:04x}", lang))
+}
+
+/// 全部可识别的语言名称
+pub fn lang_tags() -> impl Iterator<Item = (&'static str, u16)> {
+    LANG_TAGS.iter().copied()
+}
+
+/// 优先使用的语言标识：系统语言 > 英语
+pub fn preferred_langs() -> Vec<u16> {
+    let lang = sys_locale::get_locale()
+        .and_then(|locale| lang_id_from_tag(&locale))
+        .unwrap_or(LANG_EN_US);
+    if lang == LANG_EN_US {
+        vec![lang]
+    } else {
+        vec![lang, LANG_EN_US]
+    }
+}